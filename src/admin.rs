@@ -0,0 +1,284 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::cli::{load_config, save_config};
+use crate::config::ServerConfig;
+use crate::error_feed::ErrorFeed;
+use crate::request_log::RequestLog;
+use crate::site::SiteManager;
+use crate::status;
+
+/// Generate a fresh bearer token for the admin API, persisting it into
+/// `config` if it doesn't already have one.
+pub fn ensure_admin_token(config: &mut ServerConfig) -> String {
+    if let Some(token) = &config.admin_token {
+        return token.clone();
+    }
+    let token: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+    config.admin_token = Some(token.clone());
+    token
+}
+
+struct AdminState {
+    config_path: PathBuf,
+    token: String,
+    site_manager: Arc<SiteManager>,
+    request_log: Arc<RequestLog>,
+    error_feed: Arc<ErrorFeed>,
+}
+
+#[derive(Deserialize)]
+struct ParkRequest {
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct SecureRequest {
+    domain: String,
+}
+
+#[derive(Deserialize)]
+struct LogLevelRequest {
+    /// `EnvFilter` directives, e.g. `"info,mini::php_fpm=debug"`.
+    filter: String,
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+fn json_response<T: Serialize>(status: StatusCode, value: &T) -> Response<Body> {
+    let body = serde_json::to_string(value).unwrap_or_else(|_| "{}".to_string());
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+fn error_response(status: StatusCode, message: impl ToString) -> Response<Body> {
+    json_response(status, &ErrorBody { error: message.to_string() })
+}
+
+fn is_authorized(req: &Request<Body>, token: &str) -> bool {
+    req.headers()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        == Some(format!("Bearer {token}").as_str())
+}
+
+async fn read_json_body<T: for<'de> Deserialize<'de>>(req: Request<Body>) -> Result<T> {
+    let bytes = hyper::body::to_bytes(req.into_body()).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+async fn handle_park(req: Request<Body>, state: &AdminState) -> Response<Body> {
+    let body: ParkRequest = match read_json_body(req).await {
+        Ok(body) => body,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, e),
+    };
+
+    let mut config = match load_config(&state.config_path) {
+        Ok(config) => config,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+    config.add_parked_path(body.path.clone());
+    if let Err(e) = save_config(&config, &state.config_path) {
+        return error_response(StatusCode::INTERNAL_SERVER_ERROR, e);
+    }
+
+    json_response(StatusCode::OK, &serde_json::json!({ "parked": body.path }))
+}
+
+async fn handle_secure(req: Request<Body>, state: &AdminState) -> Response<Body> {
+    let body: SecureRequest = match read_json_body(req).await {
+        Ok(body) => body,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, e),
+    };
+
+    let mut config = match load_config(&state.config_path) {
+        Ok(config) => config,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+    let site = match config.sites.get_mut(&body.domain) {
+        Some(site) => site,
+        None => {
+            return error_response(
+                StatusCode::NOT_FOUND,
+                format!("{} is not linked", body.domain),
+            )
+        }
+    };
+    site.secure = true;
+    if let Err(e) = save_config(&config, &state.config_path) {
+        return error_response(StatusCode::INTERNAL_SERVER_ERROR, e);
+    }
+
+    json_response(StatusCode::OK, &serde_json::json!({ "secured": body.domain }))
+}
+
+/// Change the live log level filter, e.g. `{"filter": "info,mini::php_fpm=debug"}`,
+/// without restarting the process.
+async fn handle_log_level(req: Request<Body>) -> Response<Body> {
+    let body: LogLevelRequest = match read_json_body(req).await {
+        Ok(body) => body,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, e),
+    };
+
+    if let Err(e) = crate::error_log::set_filter(&body.filter) {
+        return error_response(StatusCode::BAD_REQUEST, e);
+    }
+
+    json_response(StatusCode::OK, &serde_json::json!({ "filter": body.filter }))
+}
+
+/// Cumulative usage stats for every site `SiteManager` currently knows
+/// about, keyed by domain.
+async fn handle_site_stats(state: &AdminState) -> Response<Body> {
+    let sites = state.site_manager.list_sites(None).await;
+    let stats: std::collections::HashMap<_, _> =
+        sites.iter().map(|site| (site.domain().to_string(), site.stats())).collect();
+    json_response(StatusCode::OK, &stats)
+}
+
+/// A [`crate::site::SiteStatus`] snapshot for every site `SiteManager`
+/// currently knows about - `/api/sites` only reflects `config.yaml`, which
+/// misses anything discovered by a parked-directory scan, so this is the
+/// endpoint that actually matches what the daemon is serving.
+async fn handle_site_statuses(state: &AdminState) -> Response<Body> {
+    let sites = state.site_manager.list_sites(None).await;
+    let statuses: Vec<_> = sites.iter().map(|site| site.status()).collect();
+    json_response(StatusCode::OK, &statuses)
+}
+
+/// Parse `?limit=N` off a request's query string, if present.
+fn limit_param(req: &Request<Body>) -> Option<usize> {
+    req.uri()
+        .query()
+        .and_then(|query| query.split('&').find_map(|pair| pair.strip_prefix("limit=")))
+        .and_then(|value| value.parse().ok())
+}
+
+/// The most recent proxied requests, newest first - a lightweight,
+/// poll-based stand-in for a devtools network tab. There's no server push
+/// here (no other admin endpoint streams either); a client gets "real
+/// time" by polling this on an interval.
+async fn handle_recent_requests(req: Request<Body>, state: &AdminState) -> Response<Body> {
+    json_response(StatusCode::OK, &state.request_log.recent(limit_param(&req)))
+}
+
+/// The most recent 5xx responses and proxy/FastCGI failures, newest first -
+/// so "it just 502'd once" is diagnosable after the fact instead of only
+/// visible in the scrollback of `error_log`.
+async fn handle_recent_errors(req: Request<Body>, state: &AdminState) -> Response<Body> {
+    json_response(StatusCode::OK, &state.error_feed.recent(limit_param(&req)))
+}
+
+/// Liveness check for a container orchestrator's health probe - unauthenticated, since a kubelet
+/// or `docker healthcheck` has no way to carry the admin bearer token, and there's nothing here an
+/// unauthenticated caller couldn't already infer from the port accepting connections at all.
+fn handle_health() -> Response<Body> {
+    json_response(StatusCode::OK, &serde_json::json!({ "status": "ok" }))
+}
+
+async fn handle(req: Request<Body>, state: Arc<AdminState>) -> Result<Response<Body>, Infallible> {
+    if (req.method(), req.uri().path()) == (&Method::GET, "/api/health") {
+        return Ok(handle_health());
+    }
+    if !is_authorized(&req, &state.token) {
+        return Ok(error_response(StatusCode::UNAUTHORIZED, "unauthorized"));
+    }
+
+    let response = match (req.method(), req.uri().path()) {
+        (&Method::GET, "/api/status") => match load_config(&state.config_path) {
+            Ok(config) => json_response(StatusCode::OK, &status::gather_status(&config)),
+            Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+        },
+        (&Method::GET, "/api/sites") => match load_config(&state.config_path) {
+            Ok(config) => {
+                let sites: Vec<_> = config.sites.values().collect();
+                json_response(StatusCode::OK, &sites)
+            }
+            Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+        },
+        (&Method::GET, "/api/sites/stats") => handle_site_stats(&state).await,
+        (&Method::GET, "/api/sites/status") => handle_site_statuses(&state).await,
+        (&Method::GET, "/api/requests/recent") => handle_recent_requests(req, &state).await,
+        (&Method::GET, "/api/errors/recent") => handle_recent_errors(req, &state).await,
+        (&Method::POST, "/api/park") => handle_park(req, &state).await,
+        (&Method::POST, "/api/secure") => handle_secure(req, &state).await,
+        (&Method::POST, "/api/log-level") => handle_log_level(req).await,
+        _ => error_response(StatusCode::NOT_FOUND, "not found"),
+    };
+
+    Ok(response)
+}
+
+/// Serve the admin REST API on `addr` until the process exits.
+pub async fn serve(
+    addr: SocketAddr,
+    config_path: PathBuf,
+    token: String,
+    site_manager: Arc<SiteManager>,
+    request_log: Arc<RequestLog>,
+    error_feed: Arc<ErrorFeed>,
+) -> Result<()> {
+    let state = Arc::new(AdminState { config_path, token, site_manager, request_log, error_feed });
+
+    let make_svc = make_service_fn(move |_conn| {
+        let state = state.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(req, state.clone()))) }
+    });
+
+    info!("Admin API listening on {addr}");
+    Server::bind(&addr).serve(make_svc).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ensure_admin_token_generates_and_persists_once() {
+        let mut config = ServerConfig::default();
+        assert!(config.admin_token.is_none());
+
+        let token = ensure_admin_token(&mut config);
+        assert_eq!(token.len(), 32);
+        assert_eq!(config.admin_token, Some(token.clone()));
+
+        // Calling again returns the same token instead of rotating it.
+        assert_eq!(ensure_admin_token(&mut config), token);
+    }
+
+    #[tokio::test]
+    async fn test_health_endpoint_bypasses_the_auth_check() {
+        let state = Arc::new(AdminState {
+            config_path: PathBuf::from("/nonexistent/config.yaml"),
+            token: "secret".to_string(),
+            site_manager: Arc::new(SiteManager::new(Arc::new(crate::registry::DriverRegistry::new()))),
+            request_log: Arc::new(RequestLog::new(10)),
+            error_feed: Arc::new(ErrorFeed::new(10)),
+        });
+
+        let req = Request::builder().method(Method::GET).uri("/api/health").body(Body::empty()).unwrap();
+        let response = handle(req, state).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
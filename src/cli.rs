@@ -0,0 +1,909 @@
+use std::env;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+use crate::config::{ServerConfig, SiteConfig};
+use crate::registry::DriverRegistry;
+
+#[derive(Parser)]
+#[command(name = "mini", version, about = "A local development server manager")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+
+    /// Emit machine-readable JSON instead of human-readable text
+    #[arg(long, global = true)]
+    pub json: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Guided first-run install: config/data dirs, CA, resolver, service,
+    /// and optionally parking ~/Sites
+    Init {
+        /// Accept every prompt, for scripted installs
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Register a directory as a parked directory (defaults to the cwd)
+    Park { path: Option<PathBuf> },
+    /// Remove a parked directory (defaults to the cwd)
+    Forget { path: Option<PathBuf> },
+    /// List parked directories, the sites found in each, and why a folder
+    /// might be missing (unreadable directory, domain conflict)
+    Paths,
+    /// Link a directory as a site (defaults to the cwd, domain defaults to its folder name)
+    Link {
+        domain: Option<String>,
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+    /// Unlink a site
+    Unlink { domain: String },
+    /// List linked sites
+    Links,
+    /// Secure a site with a locally-trusted TLS certificate
+    Secure { domain: String },
+    /// Remove TLS from a site
+    Unsecure { domain: String },
+    /// Isolate a site to a specific PHP version
+    Isolate { domain: String, php: String },
+    /// Remove PHP version isolation from a site
+    Unisolate { domain: String },
+    /// Switch the default PHP version used by sites without their own
+    /// isolated version (`mini use php@8.3` or `mini use 8.3`)
+    Use { php: String },
+    /// Enable Xdebug for a site's pool, for step-debugging
+    Xdebug { domain: String },
+    /// Disable Xdebug for a site's pool
+    Unxdebug { domain: String },
+    /// Proxy a domain to another local URL
+    Proxy { domain: String, target: String },
+    /// Remove a proxy
+    Unproxy { domain: String },
+    /// Detect and manage a project's docker-compose stack (defaults to the
+    /// cwd) - `up` brings the stack up and proxies the linked site's domain
+    /// to the detected web service's port
+    Compose {
+        #[command(subcommand)]
+        action: ComposeAction,
+    },
+    /// View or change the TLD used for linked/parked sites
+    Tld { tld: Option<String> },
+    /// Apply the low-resource config profile (single worker thread, no
+    /// metrics/tracing, smaller buffers and connection limits) - for a
+    /// Raspberry Pi or small VM running mini as a tiny LAN dev server
+    LowResource,
+    /// Start the mini daemon
+    Start {
+        /// Run in the foreground instead of forking a background process.
+        /// Used internally to re-exec the daemon; not meant to be passed
+        /// directly by users.
+        #[arg(long, hide = true)]
+        foreground: bool,
+    },
+    /// Stop the mini daemon
+    Stop,
+    /// Restart the mini daemon, optionally scoped to a single subsystem
+    Restart {
+        #[arg(long)]
+        service: Option<ServiceTarget>,
+    },
+    /// Show daemon and site status
+    Status,
+    /// Tail the access/error and framework logs for a site
+    Logs {
+        domain: String,
+        /// Keep printing new lines as they're appended
+        #[arg(short = 'f', long)]
+        follow: bool,
+    },
+    /// Open a site in the default browser (defaults to the cwd's site)
+    Open { domain: Option<String> },
+    /// Show which driver would serve a directory (defaults to the cwd)
+    Which { path: Option<PathBuf> },
+    /// Expose a site publicly through a tunnel provider (defaults to the cwd's site)
+    Share {
+        domain: Option<String>,
+        /// Tunnel provider to use (defaults to ngrok)
+        #[arg(long, value_enum)]
+        provider: Option<TunnelProviderKind>,
+        /// Requested subdomain (Expose provider only)
+        #[arg(long)]
+        subdomain: Option<String>,
+        /// `user:password` to gate the tunnel behind HTTP basic auth (Expose provider only)
+        #[arg(long)]
+        basic_auth: Option<String>,
+    },
+    /// Stop an active share (defaults to the cwd's site)
+    Unshare { domain: Option<String> },
+    /// Print the public URL for an active share (defaults to the cwd's site)
+    FetchShareUrl { domain: Option<String> },
+    /// Manage the systemd unit that supervises the mini daemon (Linux only)
+    Service {
+        #[command(subcommand)]
+        action: ServiceAction,
+    },
+    /// Start, stop, and list managed database/cache containers (e.g. `mini
+    /// services start mysql`)
+    Services {
+        #[command(subcommand)]
+        action: ServicesAction,
+    },
+    /// Check for a newer release, verify it, and swap in the new binary
+    SelfUpdate,
+    /// Run a self-hosted relay server, the counterpart to the daemon's relay
+    /// client (`relay_client.server_addr` in the config file) - meant to run
+    /// on a VPS, not alongside a local `mini start`
+    RelayServer {
+        /// Address relay clients (mini daemons) connect to and register on
+        #[arg(long, default_value = "0.0.0.0:7473")]
+        control_listen: String,
+        /// Address visitor HTTP traffic arrives on
+        #[arg(long, default_value = "0.0.0.0:80")]
+        public_listen: String,
+        /// TLS certificate presented to connecting relay clients
+        #[arg(long)]
+        cert: PathBuf,
+        /// TLS key matching `--cert`
+        #[arg(long)]
+        key: PathBuf,
+        /// Required `Hello.token` for every registering client
+        #[arg(long)]
+        token: Option<String>,
+        /// Public URL template handed back on registration, with
+        /// `{domain}` substituted for the registering client's domain
+        #[arg(long, default_value = "https://{domain}")]
+        url_template: String,
+    },
+    /// Reverse every system mutation mini made (daemon, service unit, certs,
+    /// config directory)
+    Uninstall {
+        /// Leave the config directory in place
+        #[arg(long)]
+        keep_config: bool,
+    },
+}
+
+/// A single subsystem within the daemon, for a `restart --service` scoped
+/// restart rather than bouncing the whole process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ServiceTarget {
+    Dns,
+    Http,
+    Tls,
+}
+
+/// Which tunnel provider `mini share` should use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum TunnelProviderKind {
+    #[default]
+    Ngrok,
+    Cloudflared,
+    Expose,
+}
+
+fn tunnel_provider(
+    kind: TunnelProviderKind,
+    config: &ServerConfig,
+    subdomain: Option<String>,
+    basic_auth: Option<String>,
+) -> Box<dyn crate::share::TunnelProvider> {
+    match kind {
+        TunnelProviderKind::Ngrok => {
+            Box::new(crate::share::NgrokProvider { auth_token: config.ngrok_auth_token.clone() })
+        }
+        TunnelProviderKind::Cloudflared => Box::new(crate::share::CloudflaredProvider {
+            tunnel_token: config.cloudflared_tunnel_token.clone(),
+        }),
+        TunnelProviderKind::Expose => Box::new(crate::share::ExposeProvider {
+            token: config.expose_token.clone(),
+            server: config.expose_server.clone(),
+            subdomain,
+            basic_auth,
+        }),
+    }
+}
+
+impl ServiceTarget {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ServiceTarget::Dns => "dns",
+            ServiceTarget::Http => "http",
+            ServiceTarget::Tls => "tls",
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum ServiceAction {
+    /// Generate and install the systemd unit, then enable and start it
+    Install {
+        /// Install a per-user unit instead of a system-wide one
+        #[arg(long)]
+        user: bool,
+    },
+    /// Stop, disable, and remove the systemd unit
+    Uninstall {
+        /// Target the per-user unit instead of the system-wide one
+        #[arg(long)]
+        user: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ComposeAction {
+    /// Detect a compose file's web service and bring the stack up, proxying
+    /// the linked site's domain to the service's published port
+    Up {
+        /// Directory containing the compose file (defaults to the cwd)
+        path: Option<PathBuf>,
+    },
+    /// Tear the compose stack down and remove the domain's proxy target
+    Down {
+        /// Directory containing the compose file (defaults to the cwd)
+        path: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ServicesAction {
+    /// Start a managed service's container, pulling its default image on
+    /// first run
+    Start { kind: crate::services::ManagedServiceKind },
+    /// Stop a managed service's container, without removing it
+    Stop { kind: crate::services::ManagedServiceKind },
+    /// List every managed service kind and whether its container is running
+    List,
+}
+
+/// Default location of the persisted `ServerConfig`.
+/// `~/.mini/config.yaml` on Unix; `%APPDATA%\mini\config.yaml` on Windows,
+/// since `HOME` isn't the conventional place for per-user app state there.
+/// Falls back to the current directory if the relevant variable isn't set.
+///
+/// This is the one place mini's Windows support is more than an assumption
+/// today - `privileges.rs`'s setuid drop, `systemd.rs`'s service install,
+/// and the DNS server's port-53 bind all still carry unix-only
+/// expectations a real Windows port would need to revisit.
+pub fn default_config_path() -> PathBuf {
+    #[cfg(windows)]
+    {
+        let appdata = env::var("APPDATA").unwrap_or_else(|_| ".".to_string());
+        PathBuf::from(appdata).join("mini").join("config.yaml")
+    }
+    #[cfg(not(windows))]
+    {
+        let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
+        PathBuf::from(home).join(".mini").join("config.yaml")
+    }
+}
+
+pub(crate) fn load_config(path: &PathBuf) -> Result<ServerConfig> {
+    if path.exists() {
+        Ok(ServerConfig::from_yaml(path)?)
+    } else {
+        Ok(ServerConfig::default())
+    }
+}
+
+pub(crate) fn save_config(config: &ServerConfig, path: &PathBuf) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    config.to_yaml(path)?;
+    Ok(())
+}
+
+fn domain_for(domain: Option<String>, path: &PathBuf, tld: &str) -> Result<String> {
+    match domain {
+        Some(d) => Ok(d),
+        None => {
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .ok_or_else(|| anyhow::anyhow!("could not determine a domain name from {path:?}"))?;
+            Ok(format!("{name}{tld}"))
+        }
+    }
+}
+
+/// Run a parsed CLI command against the config file at `config_path`.
+pub fn dispatch(command: Command, config_path: &PathBuf, json: bool) -> Result<()> {
+    let mut config = load_config(config_path)?;
+
+    match command {
+        Command::Init { yes } => {
+            let report = crate::init::run(&mut config, config_path, yes)?;
+            save_config(&config, config_path)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            }
+        }
+        Command::Park { path } => {
+            let path = path.unwrap_or(env::current_dir()?);
+            let path_str = path.to_string_lossy().to_string();
+            config.add_parked_path(path_str.clone());
+            save_config(&config, config_path)?;
+            println!("Parked {path_str}");
+        }
+        Command::Forget { path } => {
+            let path = path.unwrap_or(env::current_dir()?);
+            let path_str = path.to_string_lossy().to_string();
+            config.remove_parked_path(path_str.clone());
+            save_config(&config, config_path)?;
+            println!("Forgot {path_str}");
+        }
+        Command::Paths => {
+            let report = crate::parking::describe_parked_paths(&config.parked_paths);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                report.print_human();
+            }
+        }
+        Command::Link { domain, path } => {
+            let path = path.unwrap_or(env::current_dir()?);
+            let domain = domain_for(domain, &path, &config.tld)?;
+            config.add_site(
+                domain.clone(),
+                SiteConfig {
+                    root_dir: path.to_string_lossy().to_string(),
+                    domain: domain.clone(),
+                    secure: false,
+                    php_version: None,
+                    env_vars: Default::default(),
+                    driver: None,
+                    notes: None,
+                    tags: Vec::new(),
+                    php_ini: Default::default(),
+                    xdebug: false,
+                    proxy_target: None,
+                },
+            );
+            save_config(&config, config_path)?;
+            println!("Linked {domain} -> {}", path.display());
+        }
+        Command::Unlink { domain } => {
+            config.remove_site(&domain);
+            save_config(&config, config_path)?;
+            println!("Unlinked {domain}");
+        }
+        Command::Links => {
+            if json {
+                let sites: Vec<_> = config.sites.values().collect();
+                println!("{}", serde_json::to_string_pretty(&sites)?);
+            } else if config.sites.is_empty() {
+                println!("No linked sites.");
+            } else {
+                for site in config.sites.values() {
+                    println!(
+                        "{:<30} {:<8} {:<10} {:<6} {}",
+                        site.domain,
+                        if site.secure { "https" } else { "http" },
+                        site.driver.as_deref().unwrap_or("-"),
+                        site.php_version.as_deref().unwrap_or("-"),
+                        site.root_dir
+                    );
+                }
+            }
+        }
+        Command::Secure { domain } => {
+            let site = config
+                .sites
+                .get_mut(&domain)
+                .ok_or_else(|| anyhow::anyhow!("{domain} is not linked"))?;
+            site.secure = true;
+            save_config(&config, config_path)?;
+            println!("Secured {domain}");
+        }
+        Command::Unsecure { domain } => {
+            let site = config
+                .sites
+                .get_mut(&domain)
+                .ok_or_else(|| anyhow::anyhow!("{domain} is not linked"))?;
+            site.secure = false;
+            save_config(&config, config_path)?;
+            println!("Unsecured {domain}");
+        }
+        Command::Isolate { domain, php } => {
+            let available = crate::php::discover();
+            if !available.iter().any(|installation| installation.version == php) {
+                if available.is_empty() {
+                    anyhow::bail!("no PHP installations detected; install PHP {php} before isolating to it");
+                }
+                let versions: Vec<&str> = available
+                    .iter()
+                    .map(|installation| installation.version.as_str())
+                    .collect();
+                anyhow::bail!("PHP {php} not found; available versions: {}", versions.join(", "));
+            }
+            let site = config
+                .sites
+                .get_mut(&domain)
+                .ok_or_else(|| anyhow::anyhow!("{domain} is not linked"))?;
+            site.php_version = Some(php.clone());
+            save_config(&config, config_path)?;
+            println!("Isolated {domain} to PHP {php}");
+        }
+        Command::Unisolate { domain } => {
+            let site = config
+                .sites
+                .get_mut(&domain)
+                .ok_or_else(|| anyhow::anyhow!("{domain} is not linked"))?;
+            site.php_version = None;
+            save_config(&config, config_path)?;
+            println!("Removed PHP isolation from {domain}");
+        }
+        Command::Use { php } => {
+            let requested = php.strip_prefix("php@").unwrap_or(&php);
+            let available = crate::php::discover();
+            if !available.iter().any(|installation| installation.version == requested) {
+                if available.is_empty() {
+                    anyhow::bail!("no PHP installations detected; install PHP {requested} before switching to it");
+                }
+                let versions: Vec<&str> = available
+                    .iter()
+                    .map(|installation| installation.version.as_str())
+                    .collect();
+                anyhow::bail!("PHP {requested} not found; available versions: {}", versions.join(", "));
+            }
+            config.default_php_version = Some(requested.to_string());
+            save_config(&config, config_path)?;
+            println!("Now using PHP {requested} by default");
+        }
+        Command::Xdebug { domain } => {
+            let site = config
+                .sites
+                .get_mut(&domain)
+                .ok_or_else(|| anyhow::anyhow!("{domain} is not linked"))?;
+            site.xdebug = true;
+            save_config(&config, config_path)?;
+            println!("Enabled Xdebug for {domain}");
+        }
+        Command::Unxdebug { domain } => {
+            let site = config
+                .sites
+                .get_mut(&domain)
+                .ok_or_else(|| anyhow::anyhow!("{domain} is not linked"))?;
+            site.xdebug = false;
+            save_config(&config, config_path)?;
+            println!("Disabled Xdebug for {domain}");
+        }
+        Command::Proxy { domain, target } => {
+            anyhow::bail!("proxying {domain} to {target} is not implemented yet");
+        }
+        Command::Unproxy { domain } => {
+            anyhow::bail!("unproxy {domain} is not implemented yet");
+        }
+        Command::Compose { action } => match action {
+            ComposeAction::Up { path } => {
+                let path = path.unwrap_or(env::current_dir()?);
+                let file = crate::compose::compose_file(&path)
+                    .ok_or_else(|| anyhow::anyhow!("no docker-compose.yml/compose.yaml found in {}", path.display()))?;
+                let service = crate::compose::detect_web_service(&file)?.ok_or_else(|| {
+                    anyhow::anyhow!("{} has no service with a published host port", file.display())
+                })?;
+
+                crate::compose::up(&file)?;
+
+                let domain = crate::open::resolve_domain(&config, None, &path)?;
+                let target = format!("http://127.0.0.1:{}", service.host_port);
+                if let Some(site) = config.sites.get_mut(&domain) {
+                    site.proxy_target = Some(target.clone());
+                    save_config(&config, config_path)?;
+                }
+                println!("Brought up {} ({})", file.display(), service.name);
+                println!("Proxying {domain} -> {target}");
+            }
+            ComposeAction::Down { path } => {
+                let path = path.unwrap_or(env::current_dir()?);
+                let file = crate::compose::compose_file(&path)
+                    .ok_or_else(|| anyhow::anyhow!("no docker-compose.yml/compose.yaml found in {}", path.display()))?;
+
+                crate::compose::down(&file)?;
+
+                if let Ok(domain) = crate::open::resolve_domain(&config, None, &path) {
+                    if let Some(site) = config.sites.get_mut(&domain) {
+                        site.proxy_target = None;
+                        save_config(&config, config_path)?;
+                    }
+                }
+                println!("Brought down {}", file.display());
+            }
+        },
+        Command::Tld { tld } => match tld {
+            Some(tld) => {
+                let report = crate::tld::change(&mut config, &tld)?;
+                save_config(&config, config_path)?;
+                if json {
+                    println!(
+                        "{}",
+                        serde_json::to_string_pretty(&serde_json::json!({
+                            "tld": config.tld,
+                            "renamed": report.renamed,
+                        }))?
+                    );
+                } else {
+                    println!("TLD set to {tld}");
+                    for (old, new) in report.renamed {
+                        println!("  {old} -> {new}");
+                    }
+                }
+            }
+            None if json => {
+                println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "tld": config.tld }))?);
+            }
+            None => println!("{}", config.tld),
+        },
+        Command::LowResource => {
+            crate::low_resource::apply(&mut config);
+            save_config(&config, config_path)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&config)?);
+            } else {
+                println!("Applied the low-resource profile");
+            }
+        }
+        Command::Start { .. } => crate::daemon::start(&config, config_path)?,
+        Command::Stop => crate::daemon::stop(&config)?,
+        Command::Restart { service } => match service {
+            Some(service) => crate::daemon::restart_service(&config, config_path, service)?,
+            None => crate::daemon::restart(&config, config_path)?,
+        },
+        Command::Status => {
+            let status = crate::status::gather_status(&config);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&status)?);
+            } else {
+                status.print_human();
+            }
+        }
+        Command::Service { action } => {
+            if cfg!(target_os = "freebsd") {
+                crate::rcd::dispatch(action, &config)?
+            } else {
+                crate::systemd::dispatch(action, &config)?
+            }
+        }
+        Command::Services { action } => match action {
+            ServicesAction::Start { kind } => {
+                crate::services::start(kind)?;
+                println!("Started {}", kind.label());
+            }
+            ServicesAction::Stop { kind } => {
+                crate::services::stop(kind)?;
+                println!("Stopped {}", kind.label());
+            }
+            ServicesAction::List => {
+                let statuses = crate::services::list();
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&statuses)?);
+                } else {
+                    for status in &statuses {
+                        println!(
+                            "{:<10} {:<14} {:<6} {}",
+                            status.kind.label(),
+                            status.container_name,
+                            status.port,
+                            if status.running { "running" } else { "stopped" }
+                        );
+                    }
+                }
+            }
+        },
+        Command::SelfUpdate => crate::self_update::run(&config)?,
+        Command::RelayServer { .. } => {
+            // Always run inline (see `main.rs`'s `run_inline`) since, like
+            // `mini start` in the foreground, it needs to block forever on
+            // its own tokio runtime rather than report output and return.
+            unreachable!("RelayServer is handled before dispatch is called")
+        }
+        Command::Logs { domain, follow } => crate::logs::tail(&config, &domain, follow)?,
+        Command::Open { domain } => crate::open::open(&config, domain, &env::current_dir()?)?,
+        Command::Which { path } => {
+            let path = path.unwrap_or(env::current_dir()?);
+            let registry = DriverRegistry::with_known_drivers();
+            let report = registry.which(&path);
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "path": path.to_string_lossy(),
+                        "driver": report.driver.as_ref().map(|d| d.name()),
+                        "unmatched": report.unmatched,
+                    }))?
+                );
+            } else {
+                match report.driver {
+                    Some(driver) => println!("{} ({})", driver.name(), path.display()),
+                    None => {
+                        println!("No driver detected for {}", path.display());
+                        for (name, requirements) in report.unmatched {
+                            println!("  {name} requires {requirements}");
+                        }
+                    }
+                }
+            }
+        }
+        Command::Uninstall { keep_config } => {
+            let report = crate::uninstall::run(&config, config_path, keep_config)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!("Daemon stopped: {}", report.daemon_stopped);
+                println!("Service unit removed: {}", report.service_removed);
+                println!("Certs removed: {}", report.certs_removed);
+                println!("Config removed: {}", report.config_removed);
+            }
+        }
+        Command::Share { domain, provider, subdomain, basic_auth } => {
+            let domain = crate::open::resolve_domain(&config, domain, &env::current_dir()?)?;
+            let provider = tunnel_provider(provider.unwrap_or_default(), &config, subdomain, basic_auth);
+
+            let session = crate::share::start(&mut config, &domain, provider.as_ref())?;
+            save_config(&config, config_path)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&session)?);
+            } else {
+                println!("Sharing {domain} at {}", session.url);
+                match crate::share::terminal_qr(&session.url) {
+                    Ok(qr) => println!("{qr}"),
+                    Err(e) => eprintln!("Warning: failed to render QR code: {e}"),
+                }
+            }
+        }
+        Command::Unshare { domain } => {
+            let domain = crate::open::resolve_domain(&config, domain, &env::current_dir()?)?;
+            // Stop with whichever provider actually started this share,
+            // rather than assuming the default - `mini share --provider
+            // cloudflared` followed by a plain `mini unshare` should still
+            // tear the right tunnel down.
+            let kind = match config.shares.get(&domain).map(|s| s.provider.as_str()) {
+                Some("cloudflared") => TunnelProviderKind::Cloudflared,
+                Some("expose") => TunnelProviderKind::Expose,
+                _ => TunnelProviderKind::Ngrok,
+            };
+            let provider = tunnel_provider(kind, &config, None, None);
+
+            crate::share::stop(&mut config, &domain, provider.as_ref())?;
+            save_config(&config, config_path)?;
+            println!("Stopped sharing {domain}");
+        }
+        Command::FetchShareUrl { domain } => {
+            let domain = crate::open::resolve_domain(&config, domain, &env::current_dir()?)?;
+            let url = crate::share::fetch_url(&config, &domain)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "domain": domain, "url": url }))?);
+            } else {
+                println!("{url}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config_path(dir: &TempDir) -> PathBuf {
+        dir.path().join("config.yaml")
+    }
+
+    #[test]
+    fn test_link_and_list() {
+        let dir = TempDir::new().unwrap();
+        let path = config_path(&dir);
+
+        dispatch(
+            Command::Link {
+                domain: Some("example.test".to_string()),
+                path: Some(PathBuf::from("/sites/example")),
+            },
+            &path,
+            false,
+        )
+        .unwrap();
+
+        let config = load_config(&path).unwrap();
+        assert_eq!(config.sites.len(), 1);
+        assert_eq!(config.sites["example.test"].root_dir, "/sites/example");
+
+        dispatch(Command::Unlink { domain: "example.test".to_string() }, &path, false).unwrap();
+        let config = load_config(&path).unwrap();
+        assert!(config.sites.is_empty());
+    }
+
+    #[test]
+    fn test_secure_unsecure_requires_linked_site() {
+        let dir = TempDir::new().unwrap();
+        let path = config_path(&dir);
+
+        assert!(dispatch(Command::Secure { domain: "missing.test".to_string() }, &path, false).is_err());
+    }
+
+    #[test]
+    fn test_xdebug_toggle_requires_a_linked_site() {
+        let dir = TempDir::new().unwrap();
+        let path = config_path(&dir);
+
+        assert!(dispatch(Command::Xdebug { domain: "missing.test".to_string() }, &path, false).is_err());
+        assert!(dispatch(Command::Unxdebug { domain: "missing.test".to_string() }, &path, false).is_err());
+    }
+
+    #[test]
+    fn test_xdebug_toggle_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = config_path(&dir);
+
+        dispatch(
+            Command::Link { domain: Some("example.test".to_string()), path: Some(PathBuf::from("/sites/example")) },
+            &path,
+            false,
+        )
+        .unwrap();
+
+        dispatch(Command::Xdebug { domain: "example.test".to_string() }, &path, false).unwrap();
+        let config = load_config(&path).unwrap();
+        assert!(config.sites["example.test"].xdebug);
+
+        dispatch(Command::Unxdebug { domain: "example.test".to_string() }, &path, false).unwrap();
+        let config = load_config(&path).unwrap();
+        assert!(!config.sites["example.test"].xdebug);
+    }
+
+    #[test]
+    fn test_isolate_rejects_a_php_version_that_is_not_installed() {
+        let dir = TempDir::new().unwrap();
+        let path = config_path(&dir);
+
+        // The sandbox this test runs in has no real PHP installed, so any
+        // version should be rejected before even checking the site is linked.
+        assert!(dispatch(
+            Command::Isolate { domain: "missing.test".to_string(), php: "9.9".to_string() },
+            &path,
+            false,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_use_rejects_a_php_version_that_is_not_installed() {
+        let dir = TempDir::new().unwrap();
+        let path = config_path(&dir);
+
+        // The sandbox this test runs in has no real PHP installed.
+        assert!(dispatch(Command::Use { php: "php@9.9".to_string() }, &path, false).is_err());
+        let config = load_config(&path).unwrap();
+        assert_eq!(config.default_php_version, None);
+    }
+
+    #[test]
+    fn test_tld_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = config_path(&dir);
+
+        dispatch(Command::Tld { tld: Some(".localhost".to_string()) }, &path, false).unwrap();
+        let config = load_config(&path).unwrap();
+        assert_eq!(config.tld, ".localhost");
+    }
+
+    #[test]
+    fn test_low_resource_persists_the_profile() {
+        let dir = TempDir::new().unwrap();
+        let path = config_path(&dir);
+
+        dispatch(Command::LowResource, &path, false).unwrap();
+        let config = load_config(&path).unwrap();
+        assert_eq!(config.threads, 1);
+        assert!(!config.metrics_enabled);
+    }
+
+    #[test]
+    fn test_links_and_tld_accept_json_output() {
+        let dir = TempDir::new().unwrap();
+        let path = config_path(&dir);
+
+        dispatch(
+            Command::Link {
+                domain: Some("example.test".to_string()),
+                path: Some(PathBuf::from("/sites/example")),
+            },
+            &path,
+            false,
+        )
+        .unwrap();
+
+        assert!(dispatch(Command::Links, &path, true).is_ok());
+        assert!(dispatch(Command::Tld { tld: None }, &path, true).is_ok());
+    }
+
+    #[test]
+    fn test_status_succeeds_without_a_running_daemon() {
+        let dir = TempDir::new().unwrap();
+        let path = config_path(&dir);
+
+        assert!(dispatch(Command::Status, &path, false).is_ok());
+        assert!(dispatch(Command::Status, &path, true).is_ok());
+    }
+
+    #[test]
+    fn test_paths_lists_sites_found_in_each_parked_directory() {
+        let dir = TempDir::new().unwrap();
+        let path = config_path(&dir);
+        let parked = dir.path().join("sites");
+        std::fs::create_dir_all(parked.join("blog")).unwrap();
+
+        dispatch(Command::Park { path: Some(parked) }, &path, false).unwrap();
+
+        assert!(dispatch(Command::Paths, &path, false).is_ok());
+        assert!(dispatch(Command::Paths, &path, true).is_ok());
+    }
+
+    #[test]
+    fn test_which_detects_a_known_driver() {
+        let dir = TempDir::new().unwrap();
+        let path = config_path(&dir);
+
+        let site_path = dir.path().join("app");
+        std::fs::create_dir_all(site_path.join("public")).unwrap();
+        std::fs::write(site_path.join("artisan"), "").unwrap();
+        std::fs::write(site_path.join("public/index.php"), "").unwrap();
+
+        assert!(dispatch(Command::Which { path: Some(site_path.clone()) }, &path, false).is_ok());
+        assert!(dispatch(Command::Which { path: Some(site_path) }, &path, true).is_ok());
+    }
+
+    #[test]
+    fn test_which_explains_a_non_match() {
+        let dir = TempDir::new().unwrap();
+        let path = config_path(&dir);
+
+        assert!(dispatch(Command::Which { path: Some(dir.path().to_path_buf()) }, &path, false).is_ok());
+    }
+
+    #[test]
+    fn test_unshare_without_an_active_share_is_an_error() {
+        let dir = TempDir::new().unwrap();
+        let path = config_path(&dir);
+
+        assert!(dispatch(
+            Command::Unshare { domain: Some("app.test".to_string()) },
+            &path,
+            false,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_fetch_share_url_without_an_active_share_is_an_error() {
+        let dir = TempDir::new().unwrap();
+        let path = config_path(&dir);
+
+        assert!(dispatch(
+            Command::FetchShareUrl { domain: Some("app.test".to_string()) },
+            &path,
+            false,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_uninstall_removes_the_config_directory_unless_kept() {
+        let dir = TempDir::new().unwrap();
+        let path = config_path(&dir);
+        save_config(&ServerConfig::default(), &path).unwrap();
+
+        assert!(dispatch(Command::Uninstall { keep_config: true }, &path, false).is_ok());
+        assert!(path.exists());
+
+        assert!(dispatch(Command::Uninstall { keep_config: false }, &path, false).is_ok());
+        assert!(!path.exists());
+    }
+}
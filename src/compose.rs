@@ -0,0 +1,171 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+/// Candidate compose file names, checked in this order - `docker-compose.yml`
+/// is the name the standalone `docker-compose` tool looked for; `compose.yaml`
+/// is the shorter name the `docker compose` plugin also accepts.
+const COMPOSE_FILE_NAMES: &[&str] =
+    &["docker-compose.yml", "docker-compose.yaml", "compose.yml", "compose.yaml"];
+
+/// Find a compose file directly inside `dir`, if any.
+pub fn compose_file(dir: &Path) -> Option<PathBuf> {
+    COMPOSE_FILE_NAMES.iter().map(|name| dir.join(name)).find(|path| path.exists())
+}
+
+/// A compose service along with the host port its `ports:` mapping exposes -
+/// the one thing mini needs to proxy a site's domain to it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComposeService {
+    pub name: String,
+    pub host_port: u16,
+}
+
+/// Parse `file` looking for the first service with a published host port,
+/// e.g. `ports: ["8080:80"]`. A service published only as a single container
+/// port (docker assigns it a random host port) is skipped, since there's no
+/// deterministic port to proxy to.
+pub fn detect_web_service(file: &Path) -> Result<Option<ComposeService>> {
+    let contents =
+        std::fs::read_to_string(file).with_context(|| format!("failed to read {}", file.display()))?;
+    let document: serde_yaml::Value =
+        serde_yaml::from_str(&contents).with_context(|| format!("failed to parse {}", file.display()))?;
+
+    let Some(services) = document.get("services").and_then(|v| v.as_mapping()) else {
+        return Ok(None);
+    };
+
+    for (name, service) in services {
+        let Some(name) = name.as_str() else { continue };
+        let Some(ports) = service.get("ports").and_then(|v| v.as_sequence()) else { continue };
+        if let Some(host_port) = ports.iter().find_map(host_port_of) {
+            return Ok(Some(ComposeService { name: name.to_string(), host_port }));
+        }
+    }
+    Ok(None)
+}
+
+/// Pull a host port out of one entry of a `ports:` list - only the short
+/// string form (`"8080:80"`, `"127.0.0.1:8080:80"`, `"8080:80/tcp"`) is
+/// understood; the long mapping form and bare container-port entries are
+/// skipped.
+fn host_port_of(port: &serde_yaml::Value) -> Option<u16> {
+    let port = port.as_str()?;
+    let without_protocol = port.split('/').next().unwrap_or(port);
+    match without_protocol.split(':').collect::<Vec<&str>>().as_slice() {
+        [host_port, _container_port] => host_port.parse().ok(),
+        [_host_ip, host_port, _container_port] => host_port.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Bring `file`'s stack up in the background, pulling images on first run.
+pub fn up(file: &Path) -> Result<()> {
+    run_compose(file, &["up", "-d"])
+}
+
+/// Tear `file`'s stack down.
+pub fn down(file: &Path) -> Result<()> {
+    run_compose(file, &["down"])
+}
+
+fn run_compose(file: &Path, args: &[&str]) -> Result<()> {
+    let output = Command::new("docker")
+        .arg("compose")
+        .arg("-f")
+        .arg(file)
+        .args(args)
+        .output()
+        .context("failed to run docker compose - is docker installed?")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "docker compose {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_compose_file_prefers_docker_compose_yml() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("docker-compose.yml"), "").unwrap();
+        std::fs::write(dir.path().join("compose.yaml"), "").unwrap();
+
+        assert_eq!(compose_file(dir.path()), Some(dir.path().join("docker-compose.yml")));
+    }
+
+    #[test]
+    fn test_compose_file_returns_none_without_a_compose_file() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(compose_file(dir.path()), None);
+    }
+
+    #[test]
+    fn test_detect_web_service_finds_the_published_port() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("docker-compose.yml");
+        std::fs::write(
+            &file,
+            r#"
+services:
+  web:
+    image: nginx
+    ports:
+      - "8080:80"
+  db:
+    image: postgres
+    ports:
+      - "5432"
+"#,
+        )
+        .unwrap();
+
+        let service = detect_web_service(&file).unwrap();
+        assert_eq!(service, Some(ComposeService { name: "web".to_string(), host_port: 8080 }));
+    }
+
+    #[test]
+    fn test_detect_web_service_handles_a_bound_host_ip() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("compose.yaml");
+        std::fs::write(
+            &file,
+            r#"
+services:
+  web:
+    ports:
+      - "127.0.0.1:3000:3000/tcp"
+"#,
+        )
+        .unwrap();
+
+        let service = detect_web_service(&file).unwrap();
+        assert_eq!(service, Some(ComposeService { name: "web".to_string(), host_port: 3000 }));
+    }
+
+    #[test]
+    fn test_detect_web_service_returns_none_without_a_published_port() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("docker-compose.yml");
+        std::fs::write(
+            &file,
+            r#"
+services:
+  worker:
+    image: myapp
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(detect_web_service(&file).unwrap(), None);
+    }
+}
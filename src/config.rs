@@ -16,16 +16,81 @@ pub struct SiteConfig {
     pub env_vars: HashMap<String, String>,
     /// Custom driver for this site (if any)
     pub driver: Option<String>,
+    /// Free-form notes about this site (e.g. "client X, staging DB")
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Tags for filtering site listings
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// php.ini directive overrides for this site (e.g. `memory_limit`,
+    /// `upload_max_filesize`), applied per-request without touching the
+    /// shared pool's own php.ini.
+    #[serde(default)]
+    pub php_ini: HashMap<String, String>,
+    /// Whether this site's pool runs with `env[XDEBUG_CONFIG]` set for
+    /// step-debugging.
+    #[serde(default)]
+    pub xdebug: bool,
+    /// Where `mini proxy`/`mini compose up` point this domain at (e.g.
+    /// `http://127.0.0.1:8080`), set via [`crate::compose`] or the CLI's
+    /// `proxy` command. Not wired into request routing yet - `MyProxy`'s
+    /// `upstream_peer` doesn't consult `SiteManager` by host at all today
+    /// (see its doc comment), so this is recorded for when it does.
+    #[serde(default)]
+    pub proxy_target: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// A parked directory: every immediate subdirectory becomes a site.
+///
+/// `prefix`/`suffix` disambiguate folder names that collide across multiple
+/// parked paths, e.g. a `client-a` prefix turns `blog/` into `client-a-blog`.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct ParkedPath {
+    pub path: String,
+    #[serde(default)]
+    pub prefix: Option<String>,
+    #[serde(default)]
+    pub suffix: Option<String>,
+}
+
+impl ParkedPath {
+    pub fn new<S: Into<String>>(path: S) -> Self {
+        ParkedPath {
+            path: path.into(),
+            prefix: None,
+            suffix: None,
+        }
+    }
+
+    /// Derive the domain name (sans TLD) a subdirectory named `folder`
+    /// would be assigned under this parked path.
+    pub fn domain_for(&self, folder: &str) -> String {
+        format!(
+            "{}{}{}",
+            self.prefix.as_deref().unwrap_or(""),
+            folder,
+            self.suffix.as_deref().unwrap_or("")
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct ServerConfig {
     /// Version of the configuration format
     pub version: u32,
 
-    /// Number of worker threads (defaults to number of CPU cores)
+    /// Number of worker threads (defaults to number of CPU cores). Sizes
+    /// both the tokio runtime the proxy and its supporting services run on
+    /// and (via `ServerConf.threads`) each pingora service's own thread pool.
     pub threads: usize,
 
+    /// Worker threads for the DNS service's own dedicated tokio runtime,
+    /// sized separately from [`Self::threads`] since local DNS lookups are
+    /// lightweight enough that one thread is plenty even on a laptop
+    /// running on battery.
+    #[serde(default = "default_dns_threads")]
+    pub dns_threads: usize,
+
     /// HTTP listen address
     pub http_listen_addr: String,
 
@@ -53,8 +118,9 @@ pub struct ServerConfig {
     /// Group to run as after initialization
     pub group: Option<String>,
 
-    /// Parked directories (directories containing multiple sites)
-    pub parked_paths: Vec<String>,
+    /// Parked directories (directories containing multiple sites), in
+    /// precedence order: earlier entries win name conflicts.
+    pub parked_paths: Vec<ParkedPath>,
 
     /// Linked sites (individual site configurations)
     pub sites: HashMap<String, SiteConfig>,
@@ -65,11 +131,336 @@ pub struct ServerConfig {
     /// TLD to use for local development (e.g., ".test")
     pub tld: String,
 
-    /// Whether to allow network access from other devices
+    /// Whether to allow network access from other devices. Forces the
+    /// HTTP/HTTPS listeners onto `0.0.0.0` and requires each LAN device to
+    /// authorize itself with a one-time token before being proxied to - see
+    /// `network_access::NetworkAccessGuard`.
     pub network_access: bool,
 
+    /// Current one-time device-authorization token for `network_access`,
+    /// generated on first use and rotated every time a device spends it -
+    /// persisted the same way `admin_token` is, so a daemon restart doesn't
+    /// invalidate a link that hasn't been used yet.
+    #[serde(default)]
+    pub network_access_token: Option<String>,
+
     /// Port for sharing sites (e.g., via ngrok)
     pub share_port: u16,
+
+    /// Loopback address the local admin REST API listens on
+    #[serde(default = "default_admin_listen_addr")]
+    pub admin_listen_addr: String,
+
+    /// Bearer token required to call the admin API. Generated on first
+    /// daemon start if unset.
+    #[serde(default)]
+    pub admin_token: Option<String>,
+
+    /// Loopback address the gRPC control plane listens on
+    #[serde(default = "default_grpc_listen_addr")]
+    pub grpc_listen_addr: String,
+
+    /// Active tunnel sessions started by `mini share`, keyed by domain.
+    #[serde(default)]
+    pub shares: HashMap<String, crate::share::ShareSession>,
+
+    /// Auth token passed to `ngrok` so `mini share` can use a paid/registered
+    /// account (custom subdomains, longer-lived tunnels) instead of ngrok's
+    /// anonymous tier.
+    #[serde(default)]
+    pub ngrok_auth_token: Option<String>,
+
+    /// Token for a named Cloudflare Tunnel (created ahead of time with
+    /// `cloudflared tunnel create`), used by `mini share --provider
+    /// cloudflared`. Unset means `mini share` falls back to an anonymous
+    /// `*.trycloudflare.com` quick tunnel.
+    #[serde(default)]
+    pub cloudflared_tunnel_token: Option<String>,
+
+    /// Account token for Expose (<https://expose.dev>), used by `mini
+    /// share --provider expose`. Unset means sharing anonymously.
+    #[serde(default)]
+    pub expose_token: Option<String>,
+
+    /// Self-hosted Expose server host, if not using the hosted
+    /// `sharedwithexpose.com` service.
+    #[serde(default)]
+    pub expose_server: Option<String>,
+
+    /// How long a `mini share` session is considered valid before it's
+    /// reported as expired, in seconds. `None` means shares never expire -
+    /// none of the tunnel providers themselves report an expiry mini could
+    /// read back, so this is purely a locally-enforced TTL for whoever's
+    /// consuming [`crate::share::ShareSession::expires_at`].
+    #[serde(default)]
+    pub share_ttl_secs: Option<u64>,
+
+    /// PHP version served to sites that don't have their own isolated
+    /// version (`None` until `mini use` is run at least once).
+    #[serde(default)]
+    pub default_php_version: Option<String>,
+
+    /// Loopback address the `prometools` request-latency histograms are
+    /// served from, in OpenMetrics text format (separate from the
+    /// `prometheus` crate's own metrics endpoint bound in `main.rs` - the
+    /// two crates' registries aren't compatible).
+    #[serde(default = "default_latency_metrics_listen_addr")]
+    pub latency_metrics_listen_addr: String,
+
+    /// Bucket layout (seconds) for the plain HTTP listener's request
+    /// latency histogram.
+    #[serde(default = "crate::latency_metrics::default_buckets")]
+    pub http_latency_buckets: Vec<f64>,
+
+    /// Bucket layout (seconds) for the HTTPS listener's request latency
+    /// histogram, kept separate from [`ServerConfig::http_latency_buckets`]
+    /// since TLS-terminated traffic often has a different latency profile.
+    #[serde(default = "crate::latency_metrics::default_buckets")]
+    pub https_latency_buckets: Vec<f64>,
+
+    /// Bucket layout (seconds) for the shared upstream-latency histogram.
+    #[serde(default = "crate::latency_metrics::default_buckets")]
+    pub upstream_latency_buckets: Vec<f64>,
+
+    /// Whether to instrument proxied requests with distributed tracing
+    /// spans, reported to a local Jaeger agent.
+    #[serde(default)]
+    pub tracing_enabled: bool,
+
+    /// UDP address of the Jaeger agent's compact-thrift endpoint that spans
+    /// are reported to when `tracing_enabled` is set.
+    #[serde(default = "default_jaeger_agent_addr")]
+    pub jaeger_agent_addr: String,
+
+    /// Whether to bind the `prometheus` crate's metrics endpoint at all -
+    /// some users already run a real Prometheus on the default port and
+    /// don't want mini competing for it.
+    #[serde(default = "default_true")]
+    pub metrics_enabled: bool,
+
+    /// Listen address for the `prometheus` crate's metrics endpoint.
+    #[serde(default = "default_metrics_listen_addr")]
+    pub metrics_listen_addr: String,
+
+    /// Bearer token required to call the metrics endpoint. Unset means the
+    /// endpoint is open to anyone who can reach `metrics_listen_addr`.
+    #[serde(default)]
+    pub metrics_token: Option<String>,
+
+    /// Path to the TLS certificate serving the metrics endpoint. Unset
+    /// means it's served over plain HTTP.
+    #[serde(default)]
+    pub metrics_tls_cert_path: Option<String>,
+
+    /// Path to the TLS key serving the metrics endpoint.
+    #[serde(default)]
+    pub metrics_tls_key_path: Option<String>,
+
+    /// Tuning for pingora's upstream connection pool.
+    #[serde(default)]
+    pub upstream_pool: UpstreamPoolConfig,
+
+    /// How many of the most recent proxied requests to keep in memory for
+    /// the admin API's `/api/requests/recent`.
+    #[serde(default = "default_request_log_capacity")]
+    pub request_log_capacity: usize,
+
+    /// Whether `/api/requests/recent` entries also carry request/response
+    /// headers. Off by default since headers can carry cookies/auth tokens
+    /// that shouldn't sit in memory (and be returned over the admin API) for
+    /// every request by default.
+    #[serde(default)]
+    pub request_log_capture_headers: bool,
+
+    /// How many of the most recent 5xx responses and proxy/FastCGI failures
+    /// to keep in memory for the admin API's `/api/errors/recent`.
+    #[serde(default = "default_error_feed_capacity")]
+    pub error_feed_capacity: usize,
+
+    /// Load-shedding limits applied before a request ever reaches an
+    /// upstream.
+    #[serde(default)]
+    pub load_shedding: LoadSheddingConfig,
+
+    /// Self-hosted relay tunnel client, run inside the daemon as an
+    /// alternative to the third-party providers in `share.rs`. Disabled
+    /// (the default) unless `server_addr` is set.
+    #[serde(default)]
+    pub relay_client: RelayClientConfig,
+
+    /// Whether to run the built-in mail catcher: an SMTP listener that
+    /// accepts (but never forwards) outgoing mail from local apps, plus a
+    /// web UI to browse what was captured - a Mailpit/Mailhog stand-in that
+    /// doesn't need its own container.
+    #[serde(default)]
+    pub mail_catcher_enabled: bool,
+
+    /// Listen address for the mail catcher's SMTP listener. `1025` matches
+    /// Mailpit/Mailhog's own default, since that's the port most mail
+    /// libraries' local dev config already points at.
+    #[serde(default = "default_mail_catcher_smtp_listen_addr")]
+    pub mail_catcher_smtp_listen_addr: String,
+
+    /// Listen address for the mail catcher's web UI. Bound on its own port
+    /// rather than reachable at `mail.<tld>` through the main proxy, since
+    /// `upstream_peer` doesn't consult `SiteManager` to route by host yet
+    /// (see its doc comment).
+    #[serde(default = "default_mail_catcher_http_listen_addr")]
+    pub mail_catcher_http_listen_addr: String,
+
+    /// How many of the most recently captured messages the mail catcher
+    /// keeps in memory, same ring-buffer trade-off as
+    /// [`Self::error_feed_capacity`].
+    #[serde(default = "default_mail_catcher_capacity")]
+    pub mail_catcher_capacity: usize,
+}
+
+/// Tuning for pingora's upstream connection pool, broken out per class of
+/// upstream mini talks to - a php-fpm pool sees short, bursty connections
+/// while a proxied docker container or arbitrary port is closer to a long-
+/// lived passthrough, so one idle timeout for both either closes FPM
+/// sockets that were about to be reused or holds a docker connection open
+/// long after anything would reuse it.
+///
+/// Only [`Self::proxied_ports`] is wired into [`crate::MyProxy`] today,
+/// since that's the only upstream class the proxy can actually reach -
+/// `upstream_peer` always forwards to a hardcoded peer rather than
+/// consulting `SiteManager` to pick an FPM pool or docker container per
+/// site. `fpm`/`docker` exist so the config shape doesn't have to change
+/// again once that routing exists.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct UpstreamPoolConfig {
+    /// Keepalive connections pingora holds open across every upstream class
+    /// combined - a single process-wide setting, since `pingora_core`'s
+    /// connector pool isn't partitioned per upstream class today.
+    pub keepalive_pool_size: usize,
+    pub fpm: UpstreamClassConfig,
+    pub proxied_ports: UpstreamClassConfig,
+    pub docker: UpstreamClassConfig,
+}
+
+impl Default for UpstreamPoolConfig {
+    fn default() -> Self {
+        UpstreamPoolConfig {
+            keepalive_pool_size: 128,
+            fpm: UpstreamClassConfig { idle_timeout_secs: 60 },
+            proxied_ports: UpstreamClassConfig { idle_timeout_secs: 90 },
+            docker: UpstreamClassConfig { idle_timeout_secs: 120 },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct UpstreamClassConfig {
+    /// How long an idle keepalive connection to this upstream class is held
+    /// before pingora's connector pool evicts it.
+    pub idle_timeout_secs: u64,
+}
+
+impl Default for UpstreamClassConfig {
+    fn default() -> Self {
+        UpstreamClassConfig { idle_timeout_secs: 90 }
+    }
+}
+
+/// How many requests `MyProxy` will admit at once before shedding the rest
+/// with a `503` rather than letting them queue up and time out slowly - a
+/// runaway test script hammering a local site should fail fast instead of
+/// making every other request on the box wait behind it.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct LoadSheddingConfig {
+    /// Maximum number of requests being handled at once. `0` disables
+    /// shedding entirely, which is the default - this is an opt-in safety
+    /// valve, not a default-on limiter that could surprise someone with a
+    /// legitimately bursty local workload.
+    pub max_in_flight_requests: usize,
+    /// `Retry-After` value (seconds) sent with a shed request's `503`.
+    pub retry_after_secs: u64,
+}
+
+impl Default for LoadSheddingConfig {
+    fn default() -> Self {
+        LoadSheddingConfig {
+            max_in_flight_requests: 0,
+            retry_after_secs: 1,
+        }
+    }
+}
+
+/// Settings for `relay::run_client`, the daemon-resident half of mini's
+/// self-hosted tunnel mode. `server_addr` unset (the default) means the
+/// relay client never starts.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct RelayClientConfig {
+    /// `host:port` of the `mini relay-server` process to register with.
+    pub server_addr: Option<String>,
+    /// Domain to register and multiplex traffic for - must be one of
+    /// `sites`, the same as `mini share`.
+    pub domain: Option<String>,
+    /// Shared secret sent as `Hello.token`, if the relay server requires
+    /// one.
+    pub token: Option<String>,
+    /// PEM file for the CA (or self-signed cert) the relay server's TLS
+    /// certificate is validated against - there's no bundled set of public
+    /// root CAs to fall back to, so this is required once `server_addr` is
+    /// set.
+    pub ca_cert_path: Option<String>,
+    /// Public URL the relay server assigned on last successful
+    /// registration, persisted here the same way `admin_token` persists a
+    /// generated value - purely informational, read by `mini status`.
+    pub assigned_url: Option<String>,
+}
+
+fn default_admin_listen_addr() -> String {
+    "127.0.0.1:7472".to_string()
+}
+
+fn default_grpc_listen_addr() -> String {
+    "127.0.0.1:50051".to_string()
+}
+
+fn default_latency_metrics_listen_addr() -> String {
+    "127.0.0.1:9091".to_string()
+}
+
+fn default_jaeger_agent_addr() -> String {
+    "127.0.0.1:6831".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_metrics_listen_addr() -> String {
+    "127.0.0.1:9090".to_string()
+}
+
+fn default_request_log_capacity() -> usize {
+    200
+}
+
+fn default_error_feed_capacity() -> usize {
+    100
+}
+
+fn default_dns_threads() -> usize {
+    1
+}
+
+fn default_mail_catcher_smtp_listen_addr() -> String {
+    "127.0.0.1:1025".to_string()
+}
+
+fn default_mail_catcher_http_listen_addr() -> String {
+    "127.0.0.1:8025".to_string()
+}
+
+fn default_mail_catcher_capacity() -> usize {
+    100
 }
 
 impl Default for ServerConfig {
@@ -77,6 +468,7 @@ impl Default for ServerConfig {
         Self {
             version: 1,
             threads: num_cpus::get(),
+            dns_threads: default_dns_threads(),
             http_listen_addr: "127.0.0.1:80".to_string(),
             https_listen_addr: "127.0.0.1:443".to_string(),
             tls_cert_path: None,
@@ -91,7 +483,39 @@ impl Default for ServerConfig {
             default_site: None,
             tld: ".test".to_string(),
             network_access: false,
+            network_access_token: None,
             share_port: 8080,
+            admin_listen_addr: default_admin_listen_addr(),
+            admin_token: None,
+            grpc_listen_addr: default_grpc_listen_addr(),
+            shares: HashMap::new(),
+            ngrok_auth_token: None,
+            cloudflared_tunnel_token: None,
+            expose_token: None,
+            expose_server: None,
+            share_ttl_secs: None,
+            default_php_version: None,
+            latency_metrics_listen_addr: default_latency_metrics_listen_addr(),
+            http_latency_buckets: crate::latency_metrics::default_buckets(),
+            https_latency_buckets: crate::latency_metrics::default_buckets(),
+            upstream_latency_buckets: crate::latency_metrics::default_buckets(),
+            tracing_enabled: false,
+            jaeger_agent_addr: default_jaeger_agent_addr(),
+            metrics_enabled: true,
+            metrics_listen_addr: default_metrics_listen_addr(),
+            metrics_token: None,
+            metrics_tls_cert_path: None,
+            metrics_tls_key_path: None,
+            upstream_pool: UpstreamPoolConfig::default(),
+            request_log_capacity: default_request_log_capacity(),
+            request_log_capture_headers: false,
+            error_feed_capacity: default_error_feed_capacity(),
+            load_shedding: LoadSheddingConfig::default(),
+            relay_client: RelayClientConfig::default(),
+            mail_catcher_enabled: false,
+            mail_catcher_smtp_listen_addr: default_mail_catcher_smtp_listen_addr(),
+            mail_catcher_http_listen_addr: default_mail_catcher_http_listen_addr(),
+            mail_catcher_capacity: default_mail_catcher_capacity(),
         }
     }
 }
@@ -111,14 +535,15 @@ impl ServerConfig {
         Ok(())
     }
 
-    /// Add a parked directory
+    /// Add a parked directory (appended last, so existing paths keep
+    /// precedence over it on name conflicts).
     pub fn add_parked_path<S: Into<String>>(&mut self, path: S) {
-        self.parked_paths.push(path.into());
+        self.parked_paths.push(ParkedPath::new(path));
     }
 
     /// Remove a parked directory
     pub fn remove_parked_path<S: AsRef<str>>(&mut self, path: S) {
-        self.parked_paths.retain(|p| p != path.as_ref());
+        self.parked_paths.retain(|p| p.path != path.as_ref());
     }
 
     /// Add or update a site configuration
@@ -146,6 +571,7 @@ mod tests {
         let config = ServerConfig::default();
         assert_eq!(config.version, 1);
         assert_eq!(config.threads, num_cpus::get());
+        assert_eq!(config.dns_threads, 1);
         assert_eq!(config.http_listen_addr, "127.0.0.1:80");
         assert_eq!(config.https_listen_addr, "127.0.0.1:443");
         assert_eq!(config.tls_cert_path, None);
@@ -160,7 +586,44 @@ mod tests {
         assert_eq!(config.default_site, None);
         assert_eq!(config.tld, ".test");
         assert!(!config.network_access);
+        assert_eq!(config.network_access_token, None);
         assert_eq!(config.share_port, 8080);
+        assert_eq!(config.admin_listen_addr, "127.0.0.1:7472");
+        assert_eq!(config.admin_token, None);
+        assert_eq!(config.grpc_listen_addr, "127.0.0.1:50051");
+        assert!(config.shares.is_empty());
+        assert_eq!(config.ngrok_auth_token, None);
+        assert_eq!(config.cloudflared_tunnel_token, None);
+        assert_eq!(config.expose_token, None);
+        assert_eq!(config.expose_server, None);
+        assert_eq!(config.share_ttl_secs, None);
+        assert_eq!(config.default_php_version, None);
+        assert_eq!(config.latency_metrics_listen_addr, "127.0.0.1:9091");
+        assert_eq!(config.http_latency_buckets, crate::latency_metrics::default_buckets());
+        assert_eq!(config.https_latency_buckets, crate::latency_metrics::default_buckets());
+        assert_eq!(config.upstream_latency_buckets, crate::latency_metrics::default_buckets());
+        assert!(!config.tracing_enabled);
+        assert_eq!(config.jaeger_agent_addr, "127.0.0.1:6831");
+        assert!(config.metrics_enabled);
+        assert_eq!(config.metrics_listen_addr, "127.0.0.1:9090");
+        assert_eq!(config.metrics_token, None);
+        assert_eq!(config.metrics_tls_cert_path, None);
+        assert_eq!(config.metrics_tls_key_path, None);
+        assert_eq!(config.upstream_pool.keepalive_pool_size, 128);
+        assert_eq!(config.upstream_pool.fpm.idle_timeout_secs, 60);
+        assert_eq!(config.upstream_pool.proxied_ports.idle_timeout_secs, 90);
+        assert_eq!(config.upstream_pool.docker.idle_timeout_secs, 120);
+        assert_eq!(config.request_log_capacity, 200);
+        assert!(!config.request_log_capture_headers);
+        assert_eq!(config.error_feed_capacity, 100);
+        assert_eq!(config.load_shedding.max_in_flight_requests, 0);
+        assert_eq!(config.load_shedding.retry_after_secs, 1);
+        assert_eq!(config.relay_client, RelayClientConfig::default());
+        assert_eq!(config.relay_client.server_addr, None);
+        assert!(!config.mail_catcher_enabled);
+        assert_eq!(config.mail_catcher_smtp_listen_addr, "127.0.0.1:1025");
+        assert_eq!(config.mail_catcher_http_listen_addr, "127.0.0.1:8025");
+        assert_eq!(config.mail_catcher_capacity, 100);
     }
 
     #[test]
@@ -185,6 +648,11 @@ mod tests {
                 map
             },
             driver: Some("laravel".to_string()),
+            notes: None,
+            tags: Vec::new(),
+            php_ini: Default::default(),
+            xdebug: false,
+            proxy_target: None,
         };
 
         config.add_site("myapp.test".to_string(), site_config.clone());
@@ -226,6 +694,11 @@ mod tests {
                 map
             },
             driver: Some("laravel".to_string()),
+            notes: None,
+            tags: Vec::new(),
+            php_ini: Default::default(),
+            xdebug: false,
+            proxy_target: None,
         };
 
         config.add_site("myapp.test".to_string(), site_config);
@@ -253,6 +726,11 @@ mod tests {
                 map
             },
             driver: Some("laravel".to_string()),
+            notes: None,
+            tags: Vec::new(),
+            php_ini: Default::default(),
+            xdebug: false,
+            proxy_target: None,
         };
 
         config.add_site("myapp.test".to_string(), site_config);
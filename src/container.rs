@@ -0,0 +1,121 @@
+//! Container-friendly configuration: applying `MINI_*` environment
+//! variables on top of a loaded [`ServerConfig`], for a devcontainer or CI
+//! job that wants to configure mini entirely through its environment
+//! rather than mounting (or generating) a `config.yaml`.
+//!
+//! Only covers the fields a container workload actually needs to set -
+//! listen addresses, the TLD, worker threads, and whether metrics are on -
+//! not every [`ServerConfig`] field; anything else still needs the config
+//! file or `mini`'s own subcommands. Wired into `main.rs`'s one long-lived
+//! `mini start` load, not into `cli.rs`'s per-subcommand `load_config`
+//! calls, so these variables affect the running daemon but not, say, what
+//! `mini status` reads moments later in the same container.
+//!
+//! Skipping trust-store/resolver mutations - another part of running
+//! inside a container without host-level side effects - already happens
+//! for every install, not just this mode: see [`crate::init::run`]'s doc
+//! comment, since CA generation and resolver wiring aren't implemented at
+//! all yet. Binding only high ports without root falls out of
+//! `ports::choose_listen_addr`'s existing fallback, used unconditionally
+//! by every listener `main.rs` sets up.
+
+use crate::config::ServerConfig;
+
+/// Apply every set `MINI_*` override to `config` in place. Unset variables
+/// leave the corresponding field untouched; a variable that's set but
+/// doesn't parse (`MINI_THREADS=not-a-number`) is ignored the same way, so
+/// a typo falls back to the config file's value instead of failing the
+/// whole startup.
+pub fn apply_env_overrides(config: &mut ServerConfig) {
+    if let Ok(addr) = std::env::var("MINI_HTTP_LISTEN_ADDR") {
+        config.http_listen_addr = addr;
+    }
+    if let Ok(addr) = std::env::var("MINI_HTTPS_LISTEN_ADDR") {
+        config.https_listen_addr = addr;
+    }
+    if let Ok(addr) = std::env::var("MINI_ADMIN_LISTEN_ADDR") {
+        config.admin_listen_addr = addr;
+    }
+    if let Ok(tld) = std::env::var("MINI_TLD") {
+        config.tld = tld;
+    }
+    if let Some(threads) = std::env::var("MINI_THREADS").ok().and_then(|v| v.parse().ok()) {
+        config.threads = threads;
+    }
+    if let Some(enabled) = std::env::var("MINI_METRICS_ENABLED").ok().and_then(|v| v.parse().ok()) {
+        config.metrics_enabled = enabled;
+    }
+    if let Ok(token) = std::env::var("MINI_ADMIN_TOKEN") {
+        config.admin_token = Some(token);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // `std::env` is process-global; serialize this module's tests so they
+    // don't observe each other's variables.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn clear_env() {
+        for var in [
+            "MINI_HTTP_LISTEN_ADDR",
+            "MINI_HTTPS_LISTEN_ADDR",
+            "MINI_ADMIN_LISTEN_ADDR",
+            "MINI_TLD",
+            "MINI_THREADS",
+            "MINI_METRICS_ENABLED",
+            "MINI_ADMIN_TOKEN",
+        ] {
+            std::env::remove_var(var);
+        }
+    }
+
+    #[test]
+    fn test_unset_variables_leave_the_config_untouched() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        clear_env();
+        let mut config = ServerConfig::default();
+        let before = config.clone();
+
+        apply_env_overrides(&mut config);
+
+        assert_eq!(config, before);
+    }
+
+    #[test]
+    fn test_set_variables_override_the_matching_fields() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        clear_env();
+        std::env::set_var("MINI_HTTP_LISTEN_ADDR", "0.0.0.0:8080");
+        std::env::set_var("MINI_TLD", ".localhost");
+        std::env::set_var("MINI_THREADS", "4");
+        std::env::set_var("MINI_METRICS_ENABLED", "false");
+
+        let mut config = ServerConfig::default();
+        apply_env_overrides(&mut config);
+
+        assert_eq!(config.http_listen_addr, "0.0.0.0:8080");
+        assert_eq!(config.tld, ".localhost");
+        assert_eq!(config.threads, 4);
+        assert!(!config.metrics_enabled);
+
+        clear_env();
+    }
+
+    #[test]
+    fn test_unparseable_numeric_override_is_ignored() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        clear_env();
+        std::env::set_var("MINI_THREADS", "not-a-number");
+
+        let mut config = ServerConfig::default();
+        let default_threads = config.threads;
+        apply_env_overrides(&mut config);
+
+        assert_eq!(config.threads, default_threads);
+        clear_env();
+    }
+}
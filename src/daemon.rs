@@ -0,0 +1,217 @@
+use std::fs;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command as OsCommand, Stdio};
+
+use anyhow::{Context, Result};
+use tracing::{error, warn};
+use nix::sys::signal::{self, Signal};
+use nix::sys::wait::waitpid;
+use nix::unistd::{self, ForkResult, Pid};
+
+use crate::cli::ServiceTarget;
+use crate::config::ServerConfig;
+
+/// Where the daemon's pid is tracked, defaulting alongside the config file
+/// when the user hasn't set `pid_file` explicitly.
+fn pid_file_path(config: &ServerConfig) -> PathBuf {
+    match &config.pid_file {
+        Some(path) => PathBuf::from(path),
+        None => crate::cli::default_config_path()
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join("mini.pid"),
+    }
+}
+
+fn read_pid(path: &Path) -> Option<i32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Whether the pid file points at a process that's still alive.
+fn running_pid(path: &Path) -> Option<i32> {
+    let pid = read_pid(path)?;
+    signal::kill(Pid::from_raw(pid), None).ok().map(|_| pid)
+}
+
+/// Start the daemon, refusing if one is already running against this
+/// config's pid file. When `config.daemon` is set, this properly detaches
+/// (double fork + `setsid`, see [`daemonize`]) instead of just backgrounding
+/// a child that's still tied to the caller's session.
+pub fn start(config: &ServerConfig, _config_path: &Path) -> Result<()> {
+    let pid_file = pid_file_path(config);
+    if let Some(pid) = running_pid(&pid_file) {
+        anyhow::bail!("mini is already running (pid {pid})");
+    }
+
+    if let Some(parent) = pid_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if config.daemon {
+        daemonize(&pid_file)
+    } else {
+        spawn_foreground_child(&pid_file)
+    }
+}
+
+/// Background mini without detaching: spawn `start --foreground` and record
+/// its pid. Used when `config.daemon` is unset — the child still shares our
+/// session, so it's not a "real" daemon, just off the caller's terminal.
+fn spawn_foreground_child(pid_file: &Path) -> Result<()> {
+    let exe = std::env::current_exe().context("could not determine the mini executable path")?;
+    let child = OsCommand::new(exe)
+        .args(["start", "--foreground"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("failed to spawn the mini daemon")?;
+
+    fs::write(pid_file, child.id().to_string())?;
+    println!("mini started (pid {})", child.id());
+    Ok(())
+}
+
+/// Classic double-fork: fork, `setsid` in the first child to shed the
+/// controlling terminal, then fork again so the final process is never a
+/// session leader (and so can't reacquire a tty). The grandchild execs
+/// itself with `start --foreground`, replacing its own image so it ends up
+/// running the same server loop as the non-detaching path above.
+///
+/// Safety: `fork` is only safe to call here because nothing between it and
+/// the matching `exec`/`exit` in each branch allocates, locks, or touches
+/// anything beyond the handful of async-signal-safe calls below.
+fn daemonize(pid_file: &Path) -> Result<()> {
+    match unsafe { unistd::fork() }.context("first fork failed")? {
+        ForkResult::Parent { child } => {
+            waitpid(child, None).context("failed to wait for the intermediate daemon process")?;
+            let pid = read_pid(pid_file).ok_or_else(|| anyhow::anyhow!("daemon did not write its pid file"))?;
+            println!("mini started (pid {pid})");
+            Ok(())
+        }
+        ForkResult::Child => {
+            unistd::setsid().context("setsid failed")?;
+            match unsafe { unistd::fork() } {
+                Ok(ForkResult::Parent { .. }) => std::process::exit(0),
+                Ok(ForkResult::Child) => {
+                    if let Err(e) = run_detached(pid_file) {
+                        error!("failed to start the detached daemon: {e}");
+                        std::process::exit(1);
+                    }
+                    unreachable!("run_detached either execs or exits");
+                }
+                Err(e) => {
+                    error!("second fork failed: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+/// The grandchild of [`daemonize`]: record our own pid, then exec ourselves
+/// with `start --foreground` in place so the running server is a normal,
+/// unforked process from that point on.
+fn run_detached(pid_file: &Path) -> Result<()> {
+    fs::write(pid_file, unistd::getpid().to_string())?;
+    let exe = std::env::current_exe().context("could not determine the mini executable path")?;
+    let err = OsCommand::new(exe)
+        .args(["start", "--foreground"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .exec();
+    Err(anyhow::anyhow!("failed to exec the mini daemon: {err}"))
+}
+
+/// Stop the running daemon by sending it `SIGTERM`.
+pub fn stop(config: &ServerConfig) -> Result<()> {
+    let pid_file = pid_file_path(config);
+    let pid = running_pid(&pid_file)
+        .ok_or_else(|| anyhow::anyhow!("mini is not running"))?;
+
+    signal::kill(Pid::from_raw(pid), Signal::SIGTERM)
+        .with_context(|| format!("failed to signal mini daemon (pid {pid})"))?;
+    let _ = fs::remove_file(&pid_file);
+    println!("mini stopped (pid {pid})");
+    Ok(())
+}
+
+/// Restart the daemon.
+///
+/// This stops the old process and starts a new one sequentially, so there's
+/// a brief gap where the listener sockets are unbound. Handing sockets off
+/// between processes without a gap (as pingora's own `--upgrade` flow does
+/// via `SIGQUIT` and fd transfer) isn't wired up yet, so we fall back to
+/// this simpler restart.
+pub fn restart(config: &ServerConfig, config_path: &Path) -> Result<()> {
+    let pid_file = pid_file_path(config);
+    if running_pid(&pid_file).is_some() {
+        stop(config)?;
+    }
+    start(config, config_path)
+}
+
+/// Restart just one subsystem (DNS, HTTP, or TLS) instead of the whole
+/// daemon.
+///
+/// mini runs the proxy, DNS server, admin API, and gRPC control plane as
+/// tasks of a single process (see `main.rs`'s `tokio::select!`), with no
+/// supervisor able to tear down and rebuild one listener in place. Until
+/// that's split out, a scoped restart still has to bounce the whole
+/// process — the only thing this buys over `restart` is a clear record of
+/// *why*, so `mini restart --service dns` stays a distinct, honest command
+/// rather than a silent alias once real per-service supervision exists.
+pub fn restart_service(config: &ServerConfig, config_path: &Path, service: ServiceTarget) -> Result<()> {
+    warn!(
+        "restarting the whole mini daemon to recover the {} service; \
+         zero-downtime per-service restarts aren't implemented yet",
+        service.label()
+    );
+    restart(config, config_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_stop_without_a_pid_file_is_an_error() {
+        let dir = TempDir::new().unwrap();
+        let mut config = ServerConfig::default();
+        config.pid_file = Some(dir.path().join("mini.pid").to_string_lossy().to_string());
+
+        assert!(stop(&config).is_err());
+    }
+
+    #[test]
+    fn test_stop_ignores_a_stale_pid_file() {
+        let dir = TempDir::new().unwrap();
+        let pid_path = dir.path().join("mini.pid");
+        // A pid that's extremely unlikely to be alive in the test environment.
+        fs::write(&pid_path, "999999").unwrap();
+
+        let mut config = ServerConfig::default();
+        config.pid_file = Some(pid_path.to_string_lossy().to_string());
+
+        assert!(stop(&config).is_err());
+    }
+
+    #[test]
+    fn test_start_refuses_a_second_instance_whether_or_not_daemon_is_set() {
+        let dir = TempDir::new().unwrap();
+        let pid_path = dir.path().join("mini.pid");
+        fs::write(&pid_path, unistd::getpid().to_string()).unwrap();
+
+        for daemon in [false, true] {
+            let mut config = ServerConfig::default();
+            config.pid_file = Some(pid_path.to_string_lossy().to_string());
+            config.daemon = daemon;
+
+            let err = start(&config, Path::new("unused")).unwrap_err();
+            assert!(err.to_string().contains("already running"));
+        }
+    }
+}
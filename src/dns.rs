@@ -1,33 +1,154 @@
-use std::net::Ipv4Addr;
+use std::net::{Ipv4Addr, SocketAddr};
 use std::iter;
-use hickory_proto::op::{MessageType, ResponseCode};
+use std::time::Duration;
+use hickory_proto::op::{Message, MessageType, OpCode, ResponseCode};
 use hickory_proto::rr::{DNSClass, Name, RData, Record, RecordType};
 use hickory_proto::rr::rdata::A;
+use hickory_proto::serialize::binary::{BinDecodable, BinEncodable};
 use hickory_server::authority::MessageResponseBuilder;
 use hickory_server::server::{Request, RequestHandler, ResponseHandler, ResponseInfo};
+use tokio::net::UdpSocket;
 
 const DEFAULT_TLDS: &[&str] = &["test", "localhost"];
 
+/// How long [`DnsHandler::forward`] waits on one forwarder before trying
+/// the next.
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(2);
+
 pub struct DnsHandler {
     address: Ipv4Addr,
-    tlds: Vec<String>,
+    /// TLDs mini resolves, pre-normalized to raw label bytes at construction
+    /// time so every query can compare against them directly instead of
+    /// stringifying (and UTF-8-validating) its own TLD label first.
+    tlds: Vec<Vec<u8>>,
+    /// Upstream resolvers a query outside `tlds` is forwarded to when
+    /// `strict` is `false`, tried in order until one answers.
+    forwarders: Vec<SocketAddr>,
+    /// When `true` (the default), a query outside `tlds` always gets
+    /// `NXDOMAIN` - mini never forwards. When `false`, it's tried against
+    /// `forwarders` instead, letting this resolver stand in as the
+    /// machine's only one rather than just mini's own TLDs.
+    strict: bool,
+}
+
+impl Default for DnsHandler {
+    fn default() -> Self {
+        DnsHandlerBuilder::default().build()
+    }
 }
 
 impl DnsHandler {
     pub fn new() -> Self {
-        DnsHandler {
+        Self::default()
+    }
+
+    /// Start building a [`DnsHandler`] with non-default address/TLDs/
+    /// forwarders/strict settings, for an embedder that doesn't want to
+    /// edit this module's `DEFAULT_TLDS` to change them.
+    pub fn builder() -> DnsHandlerBuilder {
+        DnsHandlerBuilder::default()
+    }
+
+    /// Whether `name`'s TLD is one of [`Self::tlds`] - compares label bytes
+    /// directly, with no per-query allocation, since this runs on every
+    /// lookup a parked site's HTTP requests trigger.
+    fn is_supported_domain(&self, name: &Name) -> bool {
+        match name.iter().last() {
+            Some(tld) => self.tlds.iter().any(|t| t.eq_ignore_ascii_case(tld)),
+            None => false,
+        }
+    }
+
+    /// Forward `request`'s query to each of `forwarders` in turn until one
+    /// replies within [`FORWARD_TIMEOUT`] with a message that parses -
+    /// `None` if `forwarders` is empty, every forwarder times out, or every
+    /// reply fails to decode.
+    async fn forward(&self, request: &Request) -> Option<Message> {
+        if self.forwarders.is_empty() {
+            return None;
+        }
+
+        let mut query = Message::new();
+        query.set_id(request.header().id());
+        query.set_message_type(MessageType::Query);
+        query.set_op_code(OpCode::Query);
+        query.set_recursion_desired(true);
+        query.add_query(request.query().original().clone());
+        let query_bytes = query.to_bytes().ok()?;
+
+        let socket = UdpSocket::bind("0.0.0.0:0").await.ok()?;
+        let mut buf = [0u8; 512];
+        for forwarder in &self.forwarders {
+            if socket.send_to(&query_bytes, forwarder).await.is_err() {
+                continue;
+            }
+            if let Ok(Ok(len)) = tokio::time::timeout(FORWARD_TIMEOUT, socket.recv(&mut buf)).await {
+                if let Ok(reply) = Message::from_bytes(&buf[..len]) {
+                    return Some(reply);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Builds a [`DnsHandler`] with non-default address/TLDs/forwarders/strict
+/// settings, via [`DnsHandler::builder`].
+pub struct DnsHandlerBuilder {
+    address: Ipv4Addr,
+    tlds: Vec<String>,
+    forwarders: Vec<SocketAddr>,
+    strict: bool,
+}
+
+impl Default for DnsHandlerBuilder {
+    fn default() -> Self {
+        DnsHandlerBuilder {
             address: Ipv4Addr::new(127, 0, 0, 1),
             tlds: DEFAULT_TLDS.iter().map(|s| s.to_string()).collect(),
+            forwarders: Vec::new(),
+            strict: true,
         }
     }
+}
 
-    fn is_supported_domain(&self, name: &Name) -> bool {
-        if let Some(tld) = name.iter().last() {
-            if let Ok(tld_str) = std::str::from_utf8(tld) {
-                return self.tlds.iter().any(|t| t == tld_str);
-            }
+impl DnsHandlerBuilder {
+    /// The IP address every resolved query's `A` record points at. Defaults
+    /// to loopback.
+    pub fn address(mut self, address: Ipv4Addr) -> Self {
+        self.address = address;
+        self
+    }
+
+    /// The TLDs this handler resolves, replacing the default `test`/
+    /// `localhost`.
+    pub fn tlds(mut self, tlds: Vec<String>) -> Self {
+        self.tlds = tlds;
+        self
+    }
+
+    /// Upstream resolvers to forward non-`tlds` queries to when `strict` is
+    /// off (see [`Self::strict`]).
+    pub fn forwarders(mut self, forwarders: Vec<SocketAddr>) -> Self {
+        self.forwarders = forwarders;
+        self
+    }
+
+    /// If `false`, a query outside `tlds` is forwarded to [`Self::forwarders`]
+    /// instead of always getting `NXDOMAIN`. Defaults to `true`, matching
+    /// this handler's original TLDs-only behavior.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn build(self) -> DnsHandler {
+        DnsHandler {
+            address: self.address,
+            tlds: self.tlds.iter().map(|s| s.as_bytes().to_vec()).collect(),
+            forwarders: self.forwarders,
+            strict: self.strict,
         }
-        false
     }
 }
 
@@ -37,23 +158,37 @@ impl RequestHandler for DnsHandler {
         let mut header = request.header().clone();
         header.set_message_type(MessageType::Response);
 
-        if !self.is_supported_domain(&request.query().name().into()) {
+        let name: Name = request.query().name().into();
+        if !self.is_supported_domain(&name) {
+            if !self.strict {
+                if let Some(reply) = self.forward(request).await {
+                    header.set_response_code(reply.response_code());
+                    let response = MessageResponseBuilder::from_message_request(request).build(
+                        header,
+                        reply.answers().iter(),
+                        reply.name_servers().iter(),
+                        iter::empty(),
+                        reply.additionals().iter(),
+                    );
+                    return response_handle.send_response(response).await.expect("failed to send response");
+                }
+            }
+
             header.set_response_code(ResponseCode::NXDomain);
             let response = MessageResponseBuilder::from_message_request(request)
-                .build(header.clone(), iter::empty(), iter::empty(), iter::empty(), iter::empty());
+                .build(header, iter::empty(), iter::empty(), iter::empty(), iter::empty());
             return response_handle.send_response(response).await.expect("failed to send response");
         }
 
         let mut record = Record::new();
-        record.set_name(request.query().name().clone().into());
+        record.set_name(name);
         record.set_record_type(RecordType::A);
         record.set_dns_class(DNSClass::IN);
         record.set_ttl(300);
         record.set_data(Some(RData::A(A(self.address))));
 
-        let answers = vec![record];
         let response = MessageResponseBuilder::from_message_request(request)
-            .build(header.clone(), answers.iter(), iter::empty(), iter::empty(), iter::empty());
+            .build(header, iter::once(&record), iter::empty(), iter::empty(), iter::empty());
         response_handle.send_response(response).await.expect("failed to send response")
     }
 }
@@ -193,4 +328,46 @@ mod tests {
         assert_eq!(response.response_code(), ResponseCode::NXDomain);
         assert_eq!(response.answer_count(), 0);
     }
+
+    #[test]
+    fn test_builder_defaults_match_new() {
+        let built = DnsHandler::builder().build();
+        let default = DnsHandler::new();
+        assert_eq!(built.address, default.address);
+        assert_eq!(built.tlds, default.tlds);
+        assert_eq!(built.strict, default.strict);
+        assert!(built.forwarders.is_empty());
+    }
+
+    #[test]
+    fn test_builder_overrides_address_tlds_and_strict() {
+        let handler = DnsHandler::builder()
+            .address(Ipv4Addr::new(10, 0, 0, 1))
+            .tlds(vec!["dev".to_string()])
+            .strict(false)
+            .build();
+
+        assert_eq!(handler.address, Ipv4Addr::new(10, 0, 0, 1));
+        assert!(handler.is_supported_domain(&Name::parse("app.dev.", None).unwrap()));
+        assert!(!handler.is_supported_domain(&Name::parse("app.test.", None).unwrap()));
+        assert!(!handler.strict);
+    }
+
+    #[tokio::test]
+    async fn test_forward_returns_none_with_no_forwarders() {
+        let handler = DnsHandler::builder().strict(false).build();
+        let addr: SocketAddr = "127.0.0.1:53".parse().unwrap();
+        let name = Name::parse("example.com.", None).unwrap();
+        let query = Query::query(name, RecordType::A);
+        let mut message = Message::new();
+        message.set_id(1);
+        message.set_message_type(MessageType::Query);
+        message.set_op_code(OpCode::Query);
+        message.add_query(query);
+
+        let message_bytes = message.to_bytes().unwrap();
+        let message_req = MessageRequest::from_bytes(&message_bytes).unwrap();
+        let request = Request::new(message_req, addr, Protocol::Udp);
+        assert!(handler.forward(&request).await.is_none());
+    }
 }
@@ -1,22 +1,233 @@
-use std::path::PathBuf;
-use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
+use tokio::process::Command;
+
+use crate::error_page::{self, ErrorPageContext};
+use crate::fastcgi::{self, FastCgiAddr, FastCgiRequest, FastCgiResponse};
+use crate::init::confirm;
+use crate::php_builtin;
+use crate::php_fpm::{Backend, PoolManager};
+
+/// What [`LaravelDriver::setup`] did to get a freshly-cloned site ready
+/// to serve: whether composer dependencies and/or `.env` were missing,
+/// and whether fixing each was run (vs. skipped or declined).
+#[derive(Debug, Default, PartialEq)]
+pub struct SetupReport {
+    pub vendor_installed: bool,
+    pub env_bootstrapped: bool,
+}
+
+/// The incoming request's method/URI/headers, for a [`Driver`] method
+/// called while actually serving a request rather than during
+/// `link`/`start`/`stop`.
+#[derive(Debug, Clone, Default)]
+pub struct DriverRequestInfo {
+    pub method: String,
+    pub uri: String,
+    pub headers: Vec<(String, String)>,
+}
+
+/// Everything a [`Driver`] method might need beyond a bare site path: the
+/// site's configured PHP version and env vars, the shared [`PoolManager`]
+/// a driver would otherwise have to reach for through its own constructor
+/// args, and - when available - the request being served.
+///
+/// `detect`/`which` only ever have a site's root path to work with (driver
+/// resolution happens before a site's PHP version or env vars are known -
+/// see [`crate::site::SiteManager::link_site`]), so `php_version`/`env_vars`
+/// are `None`/empty there; `start_site` builds a fuller context once a
+/// [`crate::site::Site`] exists. `request` is always `None` today - the
+/// live proxy path doesn't call into `Driver` per-request at all (see
+/// [`crate::MyProxy::upstream_peer`][peer]'s doc comment); `execute` is
+/// still `LaravelDriver`'s own inherent method, not part of this trait. It's
+/// here so a future FastCGI-path wiring, or a richer driver, has somewhere
+/// to put request details without another trait-breaking change.
+///
+/// [peer]: crate::MyProxy::upstream_peer
+#[derive(Debug, Clone, Default)]
+pub struct DriverContext {
+    pub path: PathBuf,
+    pub php_version: Option<String>,
+    pub env_vars: HashMap<String, String>,
+    pub pool_manager: Option<Arc<PoolManager>>,
+    pub request: Option<DriverRequestInfo>,
+}
+
+impl DriverContext {
+    /// A context with nothing but a site's root path set - what `detect`/
+    /// `which` build before a driver is even chosen.
+    pub fn from_path(path: &Path) -> Self {
+        DriverContext {
+            path: path.to_path_buf(),
+            ..Default::default()
+        }
+    }
+}
 
 #[async_trait]
 pub trait Driver: Send + Sync {
     fn name(&self) -> &'static str;
-    fn supports(&self, path: &PathBuf) -> bool;
-    async fn start(&self) -> Result<()>;
-    async fn stop(&self) -> Result<()>;
+
+    /// Simple, path-only site detection - implement this for a driver that
+    /// doesn't need anything from [`DriverContext`] beyond the site's root
+    /// path, which covers every driver mini ships with today.
+    fn supports_path(&self, path: &Path) -> bool;
+
+    /// Whether this driver can serve the site `ctx` describes. Defaults to
+    /// [`Self::supports_path`] applied to `ctx.path` - a compatibility shim
+    /// for drivers written before `DriverContext` existed; override this
+    /// directly for a driver that wants to match on `ctx.php_version` or
+    /// `ctx.env_vars` too.
+    fn supports(&self, ctx: &DriverContext) -> bool {
+        self.supports_path(&ctx.path)
+    }
+
+    /// Human-readable description of what `supports` looks for, shown by
+    /// `mini which` when this driver didn't match.
+    fn requirements(&self) -> &'static str;
+    async fn start(&self, ctx: &DriverContext) -> Result<()>;
+    async fn stop(&self, ctx: &DriverContext) -> Result<()>;
 }
 
 pub struct LaravelDriver {
-    // TODO: Add fields when needed
+    path: PathBuf,
+    php_version: String,
+    ini_overrides: HashMap<String, String>,
+    xdebug: bool,
+    pool_manager: Arc<PoolManager>,
 }
 
 impl LaravelDriver {
-    pub fn new(_path: PathBuf, _php_version: String) -> Self {
-        LaravelDriver {}
+    pub fn new(
+        path: PathBuf,
+        php_version: String,
+        ini_overrides: HashMap<String, String>,
+        xdebug: bool,
+        pool_manager: Arc<PoolManager>,
+    ) -> Self {
+        LaravelDriver {
+            path,
+            php_version,
+            ini_overrides,
+            xdebug,
+            pool_manager,
+        }
+    }
+
+    /// Run `public/index.php` against this site's isolated PHP-FPM pool,
+    /// starting it under `pool_manager` if it isn't already running.
+    /// `request` carries everything about the incoming HTTP request;
+    /// `script_filename`/`document_root`/`php_admin_values` are filled in
+    /// here from the site's own config rather than the caller's. Falls
+    /// back to reverse-proxying a `php -S` built-in server when no
+    /// php-fpm binary is installed for this site's PHP version.
+    ///
+    /// Never returns `Err` for a backend problem - a pool that won't
+    /// start, a send that can't connect, or a response that comes back a
+    /// 5xx (or with stderr output) is rendered as a styled error page
+    /// instead, mirroring Valet's dump of fatal errors rather than
+    /// leaving the browser with a blank 502.
+    pub async fn execute(&self, mut request: FastCgiRequest) -> Result<FastCgiResponse> {
+        let document_root = self.path.join("public");
+        request.script_filename = document_root.join("index.php").to_string_lossy().into_owned();
+        request.script_name = "/index.php".to_string();
+        request.document_root = document_root.to_string_lossy().into_owned();
+        request.php_admin_values = self
+            .ini_overrides
+            .iter()
+            .map(|(directive, value)| (directive.clone(), value.clone()))
+            .collect();
+
+        let backend = match self
+            .pool_manager
+            .ensure_running(&self.php_version, self.xdebug, &document_root)
+            .await
+        {
+            Ok(backend) => backend,
+            Err(error) => return Ok(self.error_page("no backend available", None, &[], Some(&error.to_string()))),
+        };
+        let backend_label = self.describe_backend(&backend);
+
+        let result = match &backend {
+            Backend::FastCgi(addr) => fastcgi::send(addr, &mut request).await,
+            Backend::Http(addr) => php_builtin::send(*addr, &mut request).await,
+        };
+        let response = match result {
+            Ok(response) => response,
+            Err(error) => return Ok(self.error_page(&backend_label, None, &[], Some(&error.to_string()))),
+        };
+
+        if response.status >= 500 || !response.stderr.is_empty() {
+            return Ok(self.error_page(&backend_label, Some(response.status), &response.stderr, None));
+        }
+        Ok(response)
+    }
+
+    fn describe_backend(&self, backend: &Backend) -> String {
+        match backend {
+            Backend::FastCgi(FastCgiAddr::Unix(path)) => format!("php-fpm via {}", path.display()),
+            Backend::FastCgi(FastCgiAddr::Tcp(addr)) => format!("php-fpm via {addr}"),
+            Backend::Http(addr) => format!("PHP built-in server via {addr}"),
+        }
+    }
+
+    fn error_page(
+        &self,
+        backend: &str,
+        status: Option<u16>,
+        stderr: &[u8],
+        connection_error: Option<&str>,
+    ) -> FastCgiResponse {
+        error_page::render(&ErrorPageContext {
+            php_version: &self.php_version,
+            backend,
+            status,
+            stderr,
+            connection_error,
+        })
+    }
+
+    /// Get a freshly-cloned Laravel site ready to serve: if `vendor/` is
+    /// missing, offer to run `composer install` as a managed child with
+    /// its output streamed straight to mini's own stdout/stderr; if
+    /// `.env` is missing but `.env.example` exists, offer to bootstrap
+    /// one from it. `yes` skips both confirmations, matching `mini
+    /// init`'s `--yes` for scripted setups.
+    pub async fn setup(&self, yes: bool) -> Result<SetupReport> {
+        let mut report = SetupReport::default();
+
+        if !self.path.join("vendor").exists() && confirm("Run `composer install` for this site?", yes) {
+            self.run_composer_install().await?;
+            report.vendor_installed = true;
+        }
+
+        let env_path = self.path.join(".env");
+        let env_example_path = self.path.join(".env.example");
+        if !env_path.exists()
+            && env_example_path.exists()
+            && confirm("Create .env from .env.example?", yes)
+        {
+            tokio::fs::copy(&env_example_path, &env_path).await?;
+            report.env_bootstrapped = true;
+        }
+
+        Ok(report)
+    }
+
+    async fn run_composer_install(&self) -> Result<()> {
+        let status = Command::new("composer")
+            .arg("install")
+            .current_dir(&self.path)
+            .status()
+            .await
+            .context("failed to launch composer - is it installed and on PATH?")?;
+        if !status.success() {
+            anyhow::bail!("composer install exited with {status}");
+        }
+        Ok(())
     }
 }
 
@@ -26,7 +237,7 @@ impl Driver for LaravelDriver {
         "Laravel"
     }
 
-    fn supports(&self, path: &PathBuf) -> bool {
+    fn supports_path(&self, path: &Path) -> bool {
         let artisan_path = path.join("artisan");
         let public_path = path.join("public");
         let index_php = public_path.join("index.php");
@@ -34,23 +245,674 @@ impl Driver for LaravelDriver {
         artisan_path.exists() && public_path.exists() && index_php.exists()
     }
 
-    async fn start(&self) -> Result<()> {
+    fn requirements(&self) -> &'static str {
+        "an `artisan` file and a `public/index.php` entry point"
+    }
+
+    async fn start(&self, _ctx: &DriverContext) -> Result<()> {
         // TODO: Implement Laravel site startup
         Ok(())
     }
 
-    async fn stop(&self) -> Result<()> {
+    async fn stop(&self, _ctx: &DriverContext) -> Result<()> {
         // TODO: Implement Laravel site shutdown
         Ok(())
     }
 }
 
+/// Turns a backend dispatch result into either the response to return
+/// as-is, or a styled error page - the shared tail of `execute` once a
+/// backend was found to send the request to, pulled out on its own so
+/// the 5xx/stderr check can be exercised in tests without a running
+/// PHP-FPM. Shared by every driver that dispatches through a plain
+/// php-fpm pool with no backend-label variation of its own
+/// ([`WordPressDriver`], [`SymfonyDriver`], [`CraftDriver`],
+/// [`BedrockDriver`], [`KirbyDriver`]); [`LaravelDriver::execute`] has its
+/// own version of this check since it also needs to label non-php-fpm
+/// backends and connection failures before ever reaching a response.
+fn finish_php_fpm_response(php_version: &str, result: Result<FastCgiResponse>) -> FastCgiResponse {
+    let response = match result {
+        Ok(response) => response,
+        Err(error) => {
+            return error_page::render(&ErrorPageContext {
+                php_version,
+                backend: "php-fpm",
+                status: None,
+                stderr: &[],
+                connection_error: Some(&error.to_string()),
+            })
+        }
+    };
+
+    if response.status >= 500 || !response.stderr.is_empty() {
+        return error_page::render(&ErrorPageContext {
+            php_version,
+            backend: "php-fpm",
+            status: Some(response.status),
+            stderr: &response.stderr,
+            connection_error: None,
+        });
+    }
+    response
+}
+
+/// A WordPress site, served straight out of its root directory (unlike
+/// [`LaravelDriver`], WordPress has no `public/` split) with `index.php`
+/// as the front controller for anything that isn't a static file.
+pub struct WordPressDriver {
+    path: PathBuf,
+    php_version: String,
+    ini_overrides: HashMap<String, String>,
+    xdebug: bool,
+    pool_manager: Arc<PoolManager>,
+}
+
+impl WordPressDriver {
+    pub fn new(
+        path: PathBuf,
+        php_version: String,
+        ini_overrides: HashMap<String, String>,
+        xdebug: bool,
+        pool_manager: Arc<PoolManager>,
+    ) -> Self {
+        WordPressDriver {
+            path,
+            php_version,
+            ini_overrides,
+            xdebug,
+            pool_manager,
+        }
+    }
+
+    /// Whether `request_path` (relative to the site root) should be served
+    /// directly rather than routed to `index.php` - anything that exists on
+    /// disk under the site root, same rule a plain static file server would
+    /// use, since WordPress itself is what decides how to handle everything
+    /// else (pretty permalinks included).
+    pub fn is_static_file(&self, request_path: &Path) -> bool {
+        self.path.join(request_path).is_file()
+    }
+
+    /// Run `index.php` at the site root against this site's PHP-FPM pool -
+    /// the WordPress counterpart to [`LaravelDriver::execute`]; see that
+    /// method's doc comment for the fallback and error-page behavior, which
+    /// is identical here.
+    pub async fn execute(&self, mut request: FastCgiRequest) -> Result<FastCgiResponse> {
+        let document_root = self.path.clone();
+        request.script_filename = document_root.join("index.php").to_string_lossy().into_owned();
+        request.script_name = "/index.php".to_string();
+        request.document_root = document_root.to_string_lossy().into_owned();
+        request.php_admin_values = self
+            .ini_overrides
+            .iter()
+            .map(|(directive, value)| (directive.clone(), value.clone()))
+            .collect();
+
+        let backend = match self
+            .pool_manager
+            .ensure_running(&self.php_version, self.xdebug, &document_root)
+            .await
+        {
+            Ok(backend) => backend,
+            Err(error) => {
+                return Ok(error_page::render(&ErrorPageContext {
+                    php_version: &self.php_version,
+                    backend: "no backend available",
+                    status: None,
+                    stderr: &[],
+                    connection_error: Some(&error.to_string()),
+                }))
+            }
+        };
+
+        let result = match &backend {
+            Backend::FastCgi(addr) => fastcgi::send(addr, &mut request).await,
+            Backend::Http(addr) => php_builtin::send(*addr, &mut request).await,
+        };
+        Ok(finish_php_fpm_response(&self.php_version, result))
+    }
+}
+
+#[async_trait]
+impl Driver for WordPressDriver {
+    fn name(&self) -> &'static str {
+        "WordPress"
+    }
+
+    fn supports_path(&self, path: &Path) -> bool {
+        path.join("wp-config.php").exists() || path.join("wp-load.php").exists()
+    }
+
+    fn requirements(&self) -> &'static str {
+        "a `wp-config.php` or `wp-load.php` at the site root"
+    }
+
+    async fn start(&self, _ctx: &DriverContext) -> Result<()> {
+        // TODO: Implement WordPress site startup
+        Ok(())
+    }
+
+    async fn stop(&self, _ctx: &DriverContext) -> Result<()> {
+        // TODO: Implement WordPress site shutdown
+        Ok(())
+    }
+}
+
+/// A Symfony site, served out of `public/` (or the legacy `web/` layout
+/// Symfony used before 4.0) with that directory's front controller
+/// (`index.php`, or `app.php` under `web/`) handling anything that isn't a
+/// static asset.
+pub struct SymfonyDriver {
+    path: PathBuf,
+    php_version: String,
+    ini_overrides: HashMap<String, String>,
+    xdebug: bool,
+    pool_manager: Arc<PoolManager>,
+}
+
+impl SymfonyDriver {
+    pub fn new(
+        path: PathBuf,
+        php_version: String,
+        ini_overrides: HashMap<String, String>,
+        xdebug: bool,
+        pool_manager: Arc<PoolManager>,
+    ) -> Self {
+        SymfonyDriver {
+            path,
+            php_version,
+            ini_overrides,
+            xdebug,
+            pool_manager,
+        }
+    }
+
+    /// The docroot to serve this site out of, and its front controller
+    /// within it - `public/index.php` for a modern Symfony app, falling
+    /// back to the legacy `web/app.php` layout when `public/` doesn't
+    /// exist.
+    fn document_root_and_front_controller(&self) -> (PathBuf, &'static str) {
+        let public_path = self.path.join("public");
+        if public_path.join("index.php").exists() {
+            (public_path, "index.php")
+        } else {
+            (self.path.join("web"), "app.php")
+        }
+    }
+
+    /// Whether `request_path` (relative to the docroot) should be served
+    /// directly rather than routed to the front controller.
+    pub fn is_static_file(&self, request_path: &Path) -> bool {
+        let (document_root, _) = self.document_root_and_front_controller();
+        document_root.join(request_path).is_file()
+    }
+
+    /// Run this site's front controller against its PHP-FPM pool - the
+    /// Symfony counterpart to [`LaravelDriver::execute`]; see that method's
+    /// doc comment for the fallback and error-page behavior, which is
+    /// identical here.
+    pub async fn execute(&self, mut request: FastCgiRequest) -> Result<FastCgiResponse> {
+        let (document_root, front_controller) = self.document_root_and_front_controller();
+        request.script_filename = document_root.join(front_controller).to_string_lossy().into_owned();
+        request.script_name = format!("/{front_controller}");
+        request.document_root = document_root.to_string_lossy().into_owned();
+        request.php_admin_values = self
+            .ini_overrides
+            .iter()
+            .map(|(directive, value)| (directive.clone(), value.clone()))
+            .collect();
+
+        let backend = match self
+            .pool_manager
+            .ensure_running(&self.php_version, self.xdebug, &document_root)
+            .await
+        {
+            Ok(backend) => backend,
+            Err(error) => {
+                return Ok(error_page::render(&ErrorPageContext {
+                    php_version: &self.php_version,
+                    backend: "no backend available",
+                    status: None,
+                    stderr: &[],
+                    connection_error: Some(&error.to_string()),
+                }))
+            }
+        };
+
+        let result = match &backend {
+            Backend::FastCgi(addr) => fastcgi::send(addr, &mut request).await,
+            Backend::Http(addr) => php_builtin::send(*addr, &mut request).await,
+        };
+        Ok(finish_php_fpm_response(&self.php_version, result))
+    }
+}
+
+#[async_trait]
+impl Driver for SymfonyDriver {
+    fn name(&self) -> &'static str {
+        "Symfony"
+    }
+
+    fn supports_path(&self, path: &Path) -> bool {
+        if !path.join("bin/console").exists() {
+            return false;
+        }
+        path.join("public/index.php").exists() || path.join("web/app.php").exists()
+    }
+
+    fn requirements(&self) -> &'static str {
+        "a `bin/console` plus `public/index.php` (or the legacy `web/app.php`)"
+    }
+
+    async fn start(&self, _ctx: &DriverContext) -> Result<()> {
+        // TODO: Implement Symfony site startup
+        Ok(())
+    }
+
+    async fn stop(&self, _ctx: &DriverContext) -> Result<()> {
+        // TODO: Implement Symfony site shutdown
+        Ok(())
+    }
+}
+
+/// A Craft CMS site, served out of `web/` with `web/index.php` as the
+/// front controller.
+///
+/// [`StaticOutputDriver`] is the generic static driver registered alongside
+/// this one, and `DriverRegistry`'s match order between drivers is
+/// unspecified (its `drivers` map is a `HashMap` - see
+/// `DriverRegistry::detect`/`which`), so this driver has no way to demand
+/// priority over it even if the two did overlap. They don't in practice -
+/// `StaticOutputDriver` matches on an `index.html` under `dist/`, `build/`,
+/// `public/`, or `_site/`, none of which a Craft site's `web/index.php` +
+/// `craft` executable requirement can satisfy - but that's `supports_path`
+/// staying narrow, not the registry enforcing any ordering.
+pub struct CraftDriver {
+    path: PathBuf,
+    php_version: String,
+    ini_overrides: HashMap<String, String>,
+    xdebug: bool,
+    pool_manager: Arc<PoolManager>,
+}
+
+impl CraftDriver {
+    pub fn new(
+        path: PathBuf,
+        php_version: String,
+        ini_overrides: HashMap<String, String>,
+        xdebug: bool,
+        pool_manager: Arc<PoolManager>,
+    ) -> Self {
+        CraftDriver {
+            path,
+            php_version,
+            ini_overrides,
+            xdebug,
+            pool_manager,
+        }
+    }
+
+    fn document_root(&self) -> PathBuf {
+        self.path.join("web")
+    }
+
+    /// Whether `request_path` (relative to `web/`) should be served
+    /// directly rather than routed to `index.php`.
+    pub fn is_static_file(&self, request_path: &Path) -> bool {
+        self.document_root().join(request_path).is_file()
+    }
+
+    /// Run `web/index.php` against this site's PHP-FPM pool - the Craft
+    /// counterpart to [`LaravelDriver::execute`]; see that method's doc
+    /// comment for the fallback and error-page behavior, which is
+    /// identical here.
+    pub async fn execute(&self, mut request: FastCgiRequest) -> Result<FastCgiResponse> {
+        let document_root = self.document_root();
+        request.script_filename = document_root.join("index.php").to_string_lossy().into_owned();
+        request.script_name = "/index.php".to_string();
+        request.document_root = document_root.to_string_lossy().into_owned();
+        request.php_admin_values = self
+            .ini_overrides
+            .iter()
+            .map(|(directive, value)| (directive.clone(), value.clone()))
+            .collect();
+
+        let backend = match self
+            .pool_manager
+            .ensure_running(&self.php_version, self.xdebug, &document_root)
+            .await
+        {
+            Ok(backend) => backend,
+            Err(error) => {
+                return Ok(error_page::render(&ErrorPageContext {
+                    php_version: &self.php_version,
+                    backend: "no backend available",
+                    status: None,
+                    stderr: &[],
+                    connection_error: Some(&error.to_string()),
+                }))
+            }
+        };
+
+        let result = match &backend {
+            Backend::FastCgi(addr) => fastcgi::send(addr, &mut request).await,
+            Backend::Http(addr) => php_builtin::send(*addr, &mut request).await,
+        };
+        Ok(finish_php_fpm_response(&self.php_version, result))
+    }
+}
+
+#[async_trait]
+impl Driver for CraftDriver {
+    fn name(&self) -> &'static str {
+        "Craft"
+    }
+
+    fn supports_path(&self, path: &Path) -> bool {
+        path.join("craft").exists() && path.join("web/index.php").exists()
+    }
+
+    fn requirements(&self) -> &'static str {
+        "a `craft` executable and a `web/index.php` entry point"
+    }
+
+    async fn start(&self, _ctx: &DriverContext) -> Result<()> {
+        // TODO: Implement Craft site startup
+        Ok(())
+    }
+
+    async fn stop(&self, _ctx: &DriverContext) -> Result<()> {
+        // TODO: Implement Craft site shutdown
+        Ok(())
+    }
+}
+
+/// A Bedrock (roots.io) WordPress site: WordPress core lives in `web/wp`
+/// rather than the site root, with `web/index.php` as the front
+/// controller - [`WordPressDriver::supports_path`]'s root-level
+/// `wp-config.php`/`wp-load.php` check never matches one of these, which is
+/// what left Bedrock sites mis-detected as a plain static or unservable
+/// site before this driver existed.
+pub struct BedrockDriver {
+    path: PathBuf,
+    php_version: String,
+    ini_overrides: HashMap<String, String>,
+    xdebug: bool,
+    pool_manager: Arc<PoolManager>,
+}
+
+impl BedrockDriver {
+    pub fn new(
+        path: PathBuf,
+        php_version: String,
+        ini_overrides: HashMap<String, String>,
+        xdebug: bool,
+        pool_manager: Arc<PoolManager>,
+    ) -> Self {
+        BedrockDriver {
+            path,
+            php_version,
+            ini_overrides,
+            xdebug,
+            pool_manager,
+        }
+    }
+
+    fn document_root(&self) -> PathBuf {
+        self.path.join("web")
+    }
+
+    /// Whether `request_path` (relative to `web/`) should be served
+    /// directly rather than routed to `index.php`.
+    pub fn is_static_file(&self, request_path: &Path) -> bool {
+        self.document_root().join(request_path).is_file()
+    }
+
+    /// Run `web/index.php` against this site's PHP-FPM pool - the Bedrock
+    /// counterpart to [`LaravelDriver::execute`]; see that method's doc
+    /// comment for the fallback and error-page behavior, which is
+    /// identical here.
+    pub async fn execute(&self, mut request: FastCgiRequest) -> Result<FastCgiResponse> {
+        let document_root = self.document_root();
+        request.script_filename = document_root.join("index.php").to_string_lossy().into_owned();
+        request.script_name = "/index.php".to_string();
+        request.document_root = document_root.to_string_lossy().into_owned();
+        request.php_admin_values = self
+            .ini_overrides
+            .iter()
+            .map(|(directive, value)| (directive.clone(), value.clone()))
+            .collect();
+
+        let backend = match self
+            .pool_manager
+            .ensure_running(&self.php_version, self.xdebug, &document_root)
+            .await
+        {
+            Ok(backend) => backend,
+            Err(error) => {
+                return Ok(error_page::render(&ErrorPageContext {
+                    php_version: &self.php_version,
+                    backend: "no backend available",
+                    status: None,
+                    stderr: &[],
+                    connection_error: Some(&error.to_string()),
+                }))
+            }
+        };
+
+        let result = match &backend {
+            Backend::FastCgi(addr) => fastcgi::send(addr, &mut request).await,
+            Backend::Http(addr) => php_builtin::send(*addr, &mut request).await,
+        };
+        Ok(finish_php_fpm_response(&self.php_version, result))
+    }
+}
+
+#[async_trait]
+impl Driver for BedrockDriver {
+    fn name(&self) -> &'static str {
+        "Bedrock"
+    }
+
+    fn supports_path(&self, path: &Path) -> bool {
+        path.join("web/wp").exists() && path.join("web/index.php").exists()
+    }
+
+    fn requirements(&self) -> &'static str {
+        "a `web/wp` directory and a `web/index.php` entry point"
+    }
+
+    async fn start(&self, _ctx: &DriverContext) -> Result<()> {
+        // TODO: Implement Bedrock site startup
+        Ok(())
+    }
+
+    async fn stop(&self, _ctx: &DriverContext) -> Result<()> {
+        // TODO: Implement Bedrock site shutdown
+        Ok(())
+    }
+}
+
+/// A Kirby CMS site, served out of the project root with `index.php` as
+/// the front controller - unlike [`WordPressDriver`], Kirby's own `content/`
+/// and `site/` directories hold page content and PHP templates rather than
+/// public assets, so `is_static_file` has to exclude them even though
+/// they're real files directly under the site root.
+pub struct KirbyDriver {
+    path: PathBuf,
+    php_version: String,
+    ini_overrides: HashMap<String, String>,
+    xdebug: bool,
+    pool_manager: Arc<PoolManager>,
+}
+
+impl KirbyDriver {
+    pub fn new(
+        path: PathBuf,
+        php_version: String,
+        ini_overrides: HashMap<String, String>,
+        xdebug: bool,
+        pool_manager: Arc<PoolManager>,
+    ) -> Self {
+        KirbyDriver {
+            path,
+            php_version,
+            ini_overrides,
+            xdebug,
+            pool_manager,
+        }
+    }
+
+    /// Whether `request_path` (relative to the site root) should be served
+    /// directly rather than routed to `index.php` - a file that exists on
+    /// disk, as long as it isn't under `content/` or `site/`, which Kirby
+    /// needs to keep handling itself.
+    pub fn is_static_file(&self, request_path: &Path) -> bool {
+        if request_path.starts_with("content") || request_path.starts_with("site") {
+            return false;
+        }
+        self.path.join(request_path).is_file()
+    }
+
+    /// Run `index.php` at the site root against this site's PHP-FPM pool -
+    /// the Kirby counterpart to [`LaravelDriver::execute`]; see that
+    /// method's doc comment for the fallback and error-page behavior, which
+    /// is identical here.
+    pub async fn execute(&self, mut request: FastCgiRequest) -> Result<FastCgiResponse> {
+        let document_root = self.path.clone();
+        request.script_filename = document_root.join("index.php").to_string_lossy().into_owned();
+        request.script_name = "/index.php".to_string();
+        request.document_root = document_root.to_string_lossy().into_owned();
+        request.php_admin_values = self
+            .ini_overrides
+            .iter()
+            .map(|(directive, value)| (directive.clone(), value.clone()))
+            .collect();
+
+        let backend = match self
+            .pool_manager
+            .ensure_running(&self.php_version, self.xdebug, &document_root)
+            .await
+        {
+            Ok(backend) => backend,
+            Err(error) => {
+                return Ok(error_page::render(&ErrorPageContext {
+                    php_version: &self.php_version,
+                    backend: "no backend available",
+                    status: None,
+                    stderr: &[],
+                    connection_error: Some(&error.to_string()),
+                }))
+            }
+        };
+
+        let result = match &backend {
+            Backend::FastCgi(addr) => fastcgi::send(addr, &mut request).await,
+            Backend::Http(addr) => php_builtin::send(*addr, &mut request).await,
+        };
+        Ok(finish_php_fpm_response(&self.php_version, result))
+    }
+}
+
+#[async_trait]
+impl Driver for KirbyDriver {
+    fn name(&self) -> &'static str {
+        "Kirby"
+    }
+
+    fn supports_path(&self, path: &Path) -> bool {
+        path.join("kirby/bootstrap.php").exists()
+    }
+
+    fn requirements(&self) -> &'static str {
+        "a `kirby/bootstrap.php` file"
+    }
+
+    async fn start(&self, _ctx: &DriverContext) -> Result<()> {
+        // TODO: Implement Kirby site startup
+        Ok(())
+    }
+
+    async fn stop(&self, _ctx: &DriverContext) -> Result<()> {
+        // TODO: Implement Kirby site shutdown
+        Ok(())
+    }
+}
+
+/// Directory names checked, in order, for a static-site-generator's build
+/// output - whichever one exists (and has an `index.html`) first wins, same
+/// "first match is good enough" spirit as
+/// [`crate::registry::DriverRegistry::detect`]'s own unspecified-order
+/// matching between drivers.
+const STATIC_OUTPUT_DIRS: &[&str] = &["dist", "build", "public", "_site"];
+
+/// A site that's just the build output of a static-site generator (Hugo,
+/// Jekyll, Astro, and similar) rather than something mini runs itself -
+/// there's no PHP here to execute, so unlike every other [`Driver`] in this
+/// file there's no `execute`/`pool_manager`, only the output directory to
+/// serve out of instead of the project root. Before this driver existed,
+/// these projects only worked if the output directory was linked directly
+/// instead of the project root.
+pub struct StaticOutputDriver {
+    path: PathBuf,
+}
+
+impl StaticOutputDriver {
+    pub fn new(path: PathBuf) -> Self {
+        StaticOutputDriver { path }
+    }
+
+    /// The build output directory to serve this site out of - the first of
+    /// [`STATIC_OUTPUT_DIRS`] that exists and has an `index.html`, or the
+    /// project root if `supports_path` wasn't checked first and none do.
+    pub fn document_root(&self) -> PathBuf {
+        for dir in STATIC_OUTPUT_DIRS {
+            let candidate = self.path.join(dir);
+            if candidate.join("index.html").exists() {
+                return candidate;
+            }
+        }
+        self.path.clone()
+    }
+}
+
+#[async_trait]
+impl Driver for StaticOutputDriver {
+    fn name(&self) -> &'static str {
+        "Static Output"
+    }
+
+    fn supports_path(&self, path: &Path) -> bool {
+        STATIC_OUTPUT_DIRS
+            .iter()
+            .any(|dir| path.join(dir).join("index.html").exists())
+    }
+
+    fn requirements(&self) -> &'static str {
+        "a generated output directory (`dist/`, `build/`, `public/`, or `_site/`) with an `index.html`"
+    }
+
+    async fn start(&self, _ctx: &DriverContext) -> Result<()> {
+        // TODO: Implement static output site startup
+        Ok(())
+    }
+
+    async fn stop(&self, _ctx: &DriverContext) -> Result<()> {
+        // TODO: Implement static output site shutdown
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use tempfile::TempDir;
     use tokio::fs;
 
+    fn test_pool_manager() -> Arc<PoolManager> {
+        Arc::new(PoolManager::new(PathBuf::from("/tmp/mini-test-php-fpm")))
+    }
+
     #[tokio::test]
     async fn test_laravel_driver() {
         let temp_dir = TempDir::new().unwrap();
@@ -70,17 +932,393 @@ mod tests {
         let driver = LaravelDriver::new(
             laravel_site.clone(),
             "8.2".to_string(),
+            HashMap::new(),
+            false,
+            test_pool_manager(),
         );
 
         // Test Laravel site detection
-        assert!(driver.supports(&laravel_site));
-        assert!(!driver.supports(&static_site));
+        assert!(driver.supports_path(&laravel_site));
+        assert!(!driver.supports_path(&static_site));
+        assert!(driver.supports(&DriverContext::from_path(&laravel_site)));
+        assert!(!driver.supports(&DriverContext::from_path(&static_site)));
 
         // Test driver name
         assert_eq!(driver.name(), "Laravel");
 
         // Test start and stop
-        driver.start().await.unwrap();
-        driver.stop().await.unwrap();
+        let ctx = DriverContext::from_path(&laravel_site);
+        driver.start(&ctx).await.unwrap();
+        driver.stop(&ctx).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_execute_renders_an_error_page_without_a_php_fpm_binary() {
+        let temp_dir = TempDir::new().unwrap();
+        let laravel_site = temp_dir.path().join("laravel-site");
+        fs::create_dir_all(laravel_site.join("public")).await.unwrap();
+
+        // No real PHP-FPM install exists in tests, so there's no binary
+        // for any version to find - this should render a styled error
+        // page rather than hang trying to spawn one, or bubble up a bare
+        // `Err` a browser would just see as a blank 502.
+        let driver = LaravelDriver::new(
+            laravel_site,
+            "99.99".to_string(),
+            HashMap::new(),
+            false,
+            test_pool_manager(),
+        );
+        let response = driver.execute(FastCgiRequest::default()).await.unwrap();
+
+        assert_eq!(response.status, 502);
+        let body = String::from_utf8(response.body).unwrap();
+        assert!(body.contains("99.99"));
+    }
+
+    #[tokio::test]
+    async fn test_setup_does_nothing_when_vendor_and_env_already_exist() {
+        let temp_dir = TempDir::new().unwrap();
+        let laravel_site = temp_dir.path().join("laravel-site");
+        fs::create_dir_all(laravel_site.join("vendor")).await.unwrap();
+        fs::write(laravel_site.join(".env"), "").await.unwrap();
+
+        let driver = LaravelDriver::new(laravel_site, "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+        let report = driver.setup(true).await.unwrap();
+
+        assert_eq!(report, SetupReport { vendor_installed: false, env_bootstrapped: false });
+    }
+
+    #[tokio::test]
+    async fn test_setup_bootstraps_env_from_example_when_vendor_is_already_installed() {
+        let temp_dir = TempDir::new().unwrap();
+        let laravel_site = temp_dir.path().join("laravel-site");
+        fs::create_dir_all(laravel_site.join("vendor")).await.unwrap();
+        fs::write(laravel_site.join(".env.example"), "APP_ENV=local").await.unwrap();
+
+        let driver = LaravelDriver::new(laravel_site.clone(), "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+        let report = driver.setup(true).await.unwrap();
+
+        assert!(report.env_bootstrapped);
+        assert!(!report.vendor_installed);
+        assert_eq!(fs::read_to_string(laravel_site.join(".env")).await.unwrap(), "APP_ENV=local");
+    }
+
+    #[tokio::test]
+    async fn test_setup_errors_when_vendor_is_missing_and_composer_is_not_installed() {
+        let temp_dir = TempDir::new().unwrap();
+        let laravel_site = temp_dir.path().join("laravel-site");
+        fs::create_dir_all(&laravel_site).await.unwrap();
+
+        // No real composer install exists in tests, so this should fail
+        // cleanly rather than hang trying to run one.
+        let driver = LaravelDriver::new(laravel_site, "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+        assert!(driver.setup(true).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_wordpress_driver_detection() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let wp_site = root.join("wp-site");
+        fs::create_dir_all(&wp_site).await.unwrap();
+        fs::write(wp_site.join("wp-config.php"), "").await.unwrap();
+
+        let laravel_site = root.join("laravel-site");
+        fs::create_dir_all(laravel_site.join("public")).await.unwrap();
+        fs::write(laravel_site.join("artisan"), "").await.unwrap();
+        fs::write(laravel_site.join("public/index.php"), "").await.unwrap();
+
+        let driver = WordPressDriver::new(wp_site.clone(), "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+
+        assert!(driver.supports_path(&wp_site));
+        assert!(!driver.supports_path(&laravel_site));
+        assert_eq!(driver.name(), "WordPress");
+    }
+
+    #[tokio::test]
+    async fn test_wordpress_driver_is_static_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let wp_site = temp_dir.path().join("wp-site");
+        fs::create_dir_all(&wp_site).await.unwrap();
+        fs::write(wp_site.join("style.css"), "").await.unwrap();
+
+        let driver = WordPressDriver::new(wp_site, "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+
+        assert!(driver.is_static_file(Path::new("style.css")));
+        assert!(!driver.is_static_file(Path::new("/about-us/")));
+    }
+
+    #[tokio::test]
+    async fn test_wordpress_driver_finish_renders_an_error_page_for_a_500_with_stderr() {
+        let temp_dir = TempDir::new().unwrap();
+        let wp_site = temp_dir.path().join("wp-site");
+        fs::create_dir_all(&wp_site).await.unwrap();
+
+        // A PHP fatal error comes back as a successful dispatch carrying a
+        // 500 and stderr output, not as an `Err` - `finish_php_fpm_response`
+        // should still turn that into the styled error page rather than
+        // passing it through to the visitor as-is.
+        let driver = WordPressDriver::new(wp_site, "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+        let response = finish_php_fpm_response(&driver.php_version, Ok(FastCgiResponse {
+            status: 500,
+            stderr: b"PHP Fatal error: something broke".to_vec(),
+            ..Default::default()
+        }));
+
+        assert_eq!(response.status, 500);
+        let body = String::from_utf8(response.body).unwrap();
+        assert!(body.contains("PHP Fatal error"));
+    }
+
+    #[tokio::test]
+    async fn test_symfony_driver_detection() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let modern_site = root.join("modern-site");
+        fs::create_dir_all(modern_site.join("bin")).await.unwrap();
+        fs::create_dir_all(modern_site.join("public")).await.unwrap();
+        fs::write(modern_site.join("bin/console"), "").await.unwrap();
+        fs::write(modern_site.join("public/index.php"), "").await.unwrap();
+
+        let legacy_site = root.join("legacy-site");
+        fs::create_dir_all(legacy_site.join("bin")).await.unwrap();
+        fs::create_dir_all(legacy_site.join("web")).await.unwrap();
+        fs::write(legacy_site.join("bin/console"), "").await.unwrap();
+        fs::write(legacy_site.join("web/app.php"), "").await.unwrap();
+
+        let no_console_site = root.join("no-console-site");
+        fs::create_dir_all(no_console_site.join("public")).await.unwrap();
+        fs::write(no_console_site.join("public/index.php"), "").await.unwrap();
+
+        let driver = SymfonyDriver::new(modern_site.clone(), "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+
+        assert!(driver.supports_path(&modern_site));
+        assert!(driver.supports_path(&legacy_site));
+        assert!(!driver.supports_path(&no_console_site));
+        assert_eq!(driver.name(), "Symfony");
+    }
+
+    #[tokio::test]
+    async fn test_symfony_driver_is_static_file_against_public() {
+        let temp_dir = TempDir::new().unwrap();
+        let site = temp_dir.path().join("modern-site");
+        fs::create_dir_all(site.join("public")).await.unwrap();
+        fs::write(site.join("public/style.css"), "").await.unwrap();
+
+        let driver = SymfonyDriver::new(site, "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+
+        assert!(driver.is_static_file(Path::new("style.css")));
+        assert!(!driver.is_static_file(Path::new("/about-us/")));
+    }
+
+    #[tokio::test]
+    async fn test_symfony_driver_finish_renders_an_error_page_for_a_500_with_stderr() {
+        let temp_dir = TempDir::new().unwrap();
+        let site = temp_dir.path().join("modern-site");
+        fs::create_dir_all(site.join("public")).await.unwrap();
+
+        let driver = SymfonyDriver::new(site, "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+        let response = finish_php_fpm_response(&driver.php_version, Ok(FastCgiResponse {
+            status: 500,
+            stderr: b"PHP Fatal error: something broke".to_vec(),
+            ..Default::default()
+        }));
+
+        assert_eq!(response.status, 500);
+        let body = String::from_utf8(response.body).unwrap();
+        assert!(body.contains("PHP Fatal error"));
+    }
+
+    #[tokio::test]
+    async fn test_craft_driver_detection() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let craft_site = root.join("craft-site");
+        fs::create_dir_all(craft_site.join("web")).await.unwrap();
+        fs::write(craft_site.join("craft"), "").await.unwrap();
+        fs::write(craft_site.join("web/index.php"), "").await.unwrap();
+
+        let missing_executable_site = root.join("missing-executable-site");
+        fs::create_dir_all(missing_executable_site.join("web")).await.unwrap();
+        fs::write(missing_executable_site.join("web/index.php"), "").await.unwrap();
+
+        let driver = CraftDriver::new(craft_site.clone(), "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+
+        assert!(driver.supports_path(&craft_site));
+        assert!(!driver.supports_path(&missing_executable_site));
+        assert_eq!(driver.name(), "Craft");
+    }
+
+    #[tokio::test]
+    async fn test_craft_driver_is_static_file_against_web() {
+        let temp_dir = TempDir::new().unwrap();
+        let site = temp_dir.path().join("craft-site");
+        fs::create_dir_all(site.join("web")).await.unwrap();
+        fs::write(site.join("web/style.css"), "").await.unwrap();
+
+        let driver = CraftDriver::new(site, "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+
+        assert!(driver.is_static_file(Path::new("style.css")));
+        assert!(!driver.is_static_file(Path::new("/about-us/")));
+    }
+
+    #[tokio::test]
+    async fn test_craft_driver_finish_renders_an_error_page_for_a_500_with_stderr() {
+        let temp_dir = TempDir::new().unwrap();
+        let site = temp_dir.path().join("craft-site");
+        fs::create_dir_all(site.join("web")).await.unwrap();
+
+        let driver = CraftDriver::new(site, "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+        let response = finish_php_fpm_response(&driver.php_version, Ok(FastCgiResponse {
+            status: 500,
+            stderr: b"PHP Fatal error: something broke".to_vec(),
+            ..Default::default()
+        }));
+
+        assert_eq!(response.status, 500);
+        let body = String::from_utf8(response.body).unwrap();
+        assert!(body.contains("PHP Fatal error"));
+    }
+
+    #[tokio::test]
+    async fn test_bedrock_driver_detection() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let bedrock_site = root.join("bedrock-site");
+        fs::create_dir_all(bedrock_site.join("web/wp")).await.unwrap();
+        fs::write(bedrock_site.join("web/index.php"), "").await.unwrap();
+
+        let plain_wp_site = root.join("wp-site");
+        fs::create_dir_all(&plain_wp_site).await.unwrap();
+        fs::write(plain_wp_site.join("wp-config.php"), "").await.unwrap();
+
+        let driver = BedrockDriver::new(bedrock_site.clone(), "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+
+        assert!(driver.supports_path(&bedrock_site));
+        assert!(!driver.supports_path(&plain_wp_site));
+        assert_eq!(driver.name(), "Bedrock");
+    }
+
+    #[tokio::test]
+    async fn test_bedrock_driver_is_static_file_against_web() {
+        let temp_dir = TempDir::new().unwrap();
+        let site = temp_dir.path().join("bedrock-site");
+        fs::create_dir_all(site.join("web")).await.unwrap();
+        fs::write(site.join("web/style.css"), "").await.unwrap();
+
+        let driver = BedrockDriver::new(site, "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+
+        assert!(driver.is_static_file(Path::new("style.css")));
+        assert!(!driver.is_static_file(Path::new("/about-us/")));
+    }
+
+    #[tokio::test]
+    async fn test_bedrock_driver_finish_renders_an_error_page_for_a_500_with_stderr() {
+        let temp_dir = TempDir::new().unwrap();
+        let site = temp_dir.path().join("bedrock-site");
+        fs::create_dir_all(site.join("web")).await.unwrap();
+
+        let driver = BedrockDriver::new(site, "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+        let response = finish_php_fpm_response(&driver.php_version, Ok(FastCgiResponse {
+            status: 500,
+            stderr: b"PHP Fatal error: something broke".to_vec(),
+            ..Default::default()
+        }));
+
+        assert_eq!(response.status, 500);
+        let body = String::from_utf8(response.body).unwrap();
+        assert!(body.contains("PHP Fatal error"));
+    }
+
+    #[tokio::test]
+    async fn test_kirby_driver_detection() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let kirby_site = root.join("kirby-site");
+        fs::create_dir_all(kirby_site.join("kirby")).await.unwrap();
+        fs::write(kirby_site.join("kirby/bootstrap.php"), "").await.unwrap();
+
+        let static_site = root.join("static-site");
+        fs::create_dir_all(&static_site).await.unwrap();
+
+        let driver = KirbyDriver::new(kirby_site.clone(), "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+
+        assert!(driver.supports_path(&kirby_site));
+        assert!(!driver.supports_path(&static_site));
+        assert_eq!(driver.name(), "Kirby");
+    }
+
+    #[tokio::test]
+    async fn test_kirby_driver_is_static_file_excludes_content_and_site() {
+        let temp_dir = TempDir::new().unwrap();
+        let site = temp_dir.path().join("kirby-site");
+        fs::create_dir_all(site.join("content")).await.unwrap();
+        fs::create_dir_all(site.join("site")).await.unwrap();
+        fs::write(site.join("style.css"), "").await.unwrap();
+        fs::write(site.join("content/home.txt"), "").await.unwrap();
+        fs::write(site.join("site/config.php"), "").await.unwrap();
+
+        let driver = KirbyDriver::new(site, "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+
+        assert!(driver.is_static_file(Path::new("style.css")));
+        assert!(!driver.is_static_file(Path::new("content/home.txt")));
+        assert!(!driver.is_static_file(Path::new("site/config.php")));
+    }
+
+    #[tokio::test]
+    async fn test_kirby_driver_finish_renders_an_error_page_for_a_500_with_stderr() {
+        let temp_dir = TempDir::new().unwrap();
+        let site = temp_dir.path().join("kirby-site");
+        fs::create_dir_all(&site).await.unwrap();
+
+        let driver = KirbyDriver::new(site, "8.2".to_string(), HashMap::new(), false, test_pool_manager());
+        let response = finish_php_fpm_response(&driver.php_version, Ok(FastCgiResponse {
+            status: 500,
+            stderr: b"PHP Fatal error: something broke".to_vec(),
+            ..Default::default()
+        }));
+
+        assert_eq!(response.status, 500);
+        let body = String::from_utf8(response.body).unwrap();
+        assert!(body.contains("PHP Fatal error"));
+    }
+
+    #[tokio::test]
+    async fn test_static_output_driver_detection() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let hugo_site = root.join("hugo-site");
+        fs::create_dir_all(hugo_site.join("public")).await.unwrap();
+        fs::write(hugo_site.join("public/index.html"), "").await.unwrap();
+
+        let source_only_site = root.join("source-only-site");
+        fs::create_dir_all(&source_only_site).await.unwrap();
+
+        let driver = StaticOutputDriver::new(hugo_site.clone());
+
+        assert!(driver.supports_path(&hugo_site));
+        assert!(!driver.supports_path(&source_only_site));
+        assert_eq!(driver.name(), "Static Output");
+    }
+
+    #[tokio::test]
+    async fn test_static_output_driver_document_root_prefers_dist_over_public() {
+        let temp_dir = TempDir::new().unwrap();
+        let site = temp_dir.path().join("astro-site");
+        fs::create_dir_all(site.join("dist")).await.unwrap();
+        fs::create_dir_all(site.join("public")).await.unwrap();
+        fs::write(site.join("dist/index.html"), "").await.unwrap();
+        fs::write(site.join("public/index.html"), "").await.unwrap();
+
+        let driver = StaticOutputDriver::new(site.clone());
+
+        assert_eq!(driver.document_root(), site.join("dist"));
     }
 }
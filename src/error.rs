@@ -0,0 +1,56 @@
+//! A typed error for mini's public, embedder-facing APIs - [`crate::site::SiteManager`]
+//! today - so a consumer like an editor plugin or GUI can branch on
+//! `NotFound` vs `AlreadyExists` instead of string-matching an error message.
+//!
+//! Most of mini's internals still return `anyhow::Result`, and that's fine:
+//! this type is for call sites meant to be driven programmatically, not for
+//! the CLI's own top-level error handling (anyhow already serves that well -
+//! one `bail!` with context, printed to stderr, process exits non-zero).
+
+use thiserror::Error;
+
+/// A typed failure from one of mini's public, embedder-facing APIs.
+#[derive(Debug, Error)]
+pub enum MiniError {
+    /// No site/resource exists under the given name.
+    #[error("{0} not found")]
+    NotFound(String),
+    /// A site/resource under the given name already exists.
+    #[error("{0} already exists")]
+    AlreadyExists(String),
+    /// A filesystem operation failed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// The on-disk config is missing or malformed.
+    #[error("config error: {0}")]
+    Config(String),
+    /// A TLS/certificate operation failed.
+    #[error("TLS error: {0}")]
+    Tls(String),
+    /// A DNS server operation failed.
+    #[error("DNS error: {0}")]
+    Dns(String),
+    /// A site driver (Laravel, static, ...) failed to detect or start.
+    #[error("driver error: {0}")]
+    Driver(String),
+}
+
+pub type Result<T> = std::result::Result<T, MiniError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_not_found_formats_with_the_name() {
+        let err = MiniError::NotFound("blog.test".to_string());
+        assert_eq!(err.to_string(), "blog.test not found");
+    }
+
+    #[test]
+    fn test_io_error_is_transparent() {
+        let io_err = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+        let err: MiniError = io_err.into();
+        assert_eq!(err.to_string(), "no such file");
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Assigns each [`ErrorFeedEntry`] a process-lifetime-unique id, so two
+/// failures in the same second (or even the same millisecond) are still
+/// distinguishable when someone's asking "which one of these was my 502?".
+/// Resets on restart - nothing here needs to survive one.
+static NEXT_ERROR_ID: AtomicU64 = AtomicU64::new(1);
+
+/// One 5xx response or proxy/FastCGI failure, as recorded by
+/// [`crate::MyProxy::logging`] - the admin API's `/api/errors/recent` serves
+/// these back so "it just 502'd once" is diagnosable after the fact instead
+/// of only visible in the scrollback of `error_log`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorFeedEntry {
+    pub id: u64,
+    pub timestamp_unix: u64,
+    pub host: String,
+    pub path: String,
+    pub status: u16,
+    /// The upstream/proxy error pingora reported, if this failure surfaced
+    /// one - a 5xx with no upstream error just means the far end chose to
+    /// answer that way, not that pingora gave up on it.
+    pub upstream_error: Option<String>,
+    /// The site this request was routed to, once routing actually consults
+    /// `SiteManager` by host - `None` until then (see
+    /// [`crate::MyProxy::upstream_peer`]).
+    pub matched_site: Option<String>,
+}
+
+/// A fixed-capacity ring buffer of recent request failures. Old entries are
+/// evicted as new ones arrive rather than this growing forever, for the same
+/// reason as [`crate::request_log::RequestLog`] - it exists to answer "what
+/// just broke", not as a durable log.
+pub struct ErrorFeed {
+    capacity: usize,
+    entries: Mutex<VecDeque<ErrorFeedEntry>>,
+}
+
+impl ErrorFeed {
+    pub fn new(capacity: usize) -> Self {
+        ErrorFeed {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn record(&self, host: String, path: String, status: u16, upstream_error: Option<String>) {
+        let entry = ErrorFeedEntry {
+            id: NEXT_ERROR_ID.fetch_add(1, Ordering::Relaxed),
+            timestamp_unix: now_unix(),
+            host,
+            path,
+            status,
+            upstream_error,
+            matched_site: None,
+        };
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// The most recent failures, newest first, capped at `limit` (or
+    /// everything held, if `limit` is `None` or larger than that).
+    pub fn recent(&self, limit: Option<usize>) -> Vec<ErrorFeedEntry> {
+        let entries = self.entries.lock().unwrap();
+        let limit = limit.unwrap_or(entries.len()).min(entries.len());
+        entries.iter().rev().take(limit).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_assigns_increasing_ids() {
+        let feed = ErrorFeed::new(10);
+        feed.record("a.test".to_string(), "/one".to_string(), 502, None);
+        feed.record("a.test".to_string(), "/two".to_string(), 503, None);
+
+        let recent = feed.recent(None);
+        assert_eq!(recent.len(), 2);
+        assert!(recent[0].id > recent[1].id);
+    }
+
+    #[test]
+    fn test_recent_returns_newest_first_and_honors_limit() {
+        let feed = ErrorFeed::new(10);
+        feed.record("a.test".to_string(), "/one".to_string(), 500, None);
+        feed.record("a.test".to_string(), "/two".to_string(), 502, Some("connect timed out".to_string()));
+        feed.record("a.test".to_string(), "/three".to_string(), 504, None);
+
+        let recent = feed.recent(Some(2));
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].path, "/three");
+        assert_eq!(recent[1].path, "/two");
+        assert_eq!(recent[1].upstream_error, Some("connect timed out".to_string()));
+    }
+
+    #[test]
+    fn test_record_evicts_oldest_past_capacity() {
+        let feed = ErrorFeed::new(2);
+        feed.record("a.test".to_string(), "/one".to_string(), 500, None);
+        feed.record("a.test".to_string(), "/two".to_string(), 500, None);
+        feed.record("a.test".to_string(), "/three".to_string(), 500, None);
+
+        let recent = feed.recent(None);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].path, "/three");
+        assert_eq!(recent[1].path, "/two");
+    }
+}
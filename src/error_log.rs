@@ -0,0 +1,188 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use nix::sys::signal::{self, SaFlags, SigAction, SigHandler, SigSet, Signal};
+use tracing_subscriber::filter::EnvFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, Registry};
+
+use crate::config::ServerConfig;
+
+/// Log files larger than this are rotated (renamed to `<path>.1`, replacing
+/// any previous backup) before the next write.
+const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Set by the `SIGUSR1` handler below; checked before every write so a
+/// `logrotate postrotate` hook (or an operator's own `kill -USR1`) can tell
+/// us to reopen the file at its configured path without restarting mini.
+static REOPEN_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn request_reopen(_signal: std::ffi::c_int) {
+    REOPEN_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+lazy_static! {
+    /// The live filter's reload handle, set once [`init`] runs. [`set_filter`]
+    /// (wired up to the admin API's `/api/log-level`) uses it to change
+    /// per-module level filters, e.g. `info,mini::php_fpm=debug`, without
+    /// restarting the process.
+    static ref FILTER_HANDLE: Mutex<Option<reload::Handle<EnvFilter, Registry>>> = Mutex::new(None);
+}
+
+/// Initialize structured logging: events are pretty-printed to stderr, or
+/// written as JSON lines to `config.error_log` if set (with size-based
+/// rotation and `SIGUSR1` reopen support), so daemonized runs don't
+/// silently lose diagnostics. The level filter honors `RUST_LOG` on
+/// startup and can be changed later via [`set_filter`]. Also bridges the
+/// handful of dependencies (hyper, pingora, prometheus) that still log
+/// through the plain `log` facade into the same output.
+pub fn init(config: &ServerConfig) -> Result<()> {
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let (filter, handle) = reload::Layer::new(env_filter);
+    *FILTER_HANDLE.lock().unwrap() = Some(handle);
+    let registry = Registry::default().with(filter);
+
+    if let Some(error_log) = &config.error_log {
+        let writer = ReopenableFile::open(error_log)?;
+        install_reopen_handler()?;
+        registry
+            .with(fmt::layer().json().with_ansi(false).with_writer(Mutex::new(writer)))
+            .try_init()
+            .context("failed to install the tracing subscriber")?;
+    } else {
+        registry
+            .with(fmt::layer().pretty())
+            .try_init()
+            .context("failed to install the tracing subscriber")?;
+    }
+
+    tracing_log::LogTracer::init().context("failed to bridge `log`-based output into `tracing`")?;
+    Ok(())
+}
+
+/// Change the live level filter (e.g. `"info,mini::php_fpm=debug"`) without
+/// restarting the process. Errors if [`init`] hasn't run yet, or if
+/// `directives` doesn't parse.
+pub fn set_filter(directives: &str) -> Result<()> {
+    let handle = FILTER_HANDLE.lock().unwrap();
+    let handle = handle.as_ref().context("logging isn't initialized yet")?;
+    let filter = EnvFilter::try_new(directives).context("invalid log filter directives")?;
+    handle.reload(filter).context("failed to reload the log filter")
+}
+
+fn install_reopen_handler() -> Result<()> {
+    let action = SigAction::new(SigHandler::Handler(request_reopen), SaFlags::empty(), SigSet::empty());
+    unsafe { signal::sigaction(Signal::SIGUSR1, &action) }
+        .context("failed to install the SIGUSR1 reopen handler")?;
+    Ok(())
+}
+
+/// A log file handle that reopens itself at `path` when asked (via
+/// [`REOPEN_REQUESTED`]) and rotates itself once it grows past
+/// [`MAX_LOG_BYTES`], both checked lazily on write rather than from a
+/// background thread.
+struct ReopenableFile {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl ReopenableFile {
+    fn open(path: &str) -> Result<Self> {
+        let path = PathBuf::from(path);
+        let file = open_append(&path)?;
+        Ok(ReopenableFile {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    fn rotate_if_needed(&self, file: &mut File) -> io::Result<()> {
+        if file.metadata()?.len() < MAX_LOG_BYTES {
+            return Ok(());
+        }
+        let backup = rotated_path(&self.path);
+        fs::rename(&self.path, &backup)?;
+        *file = open_append(&self.path)?;
+        Ok(())
+    }
+}
+
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(".1");
+    PathBuf::from(rotated)
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+impl Write for ReopenableFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut file = self.file.lock().unwrap();
+        if REOPEN_REQUESTED.swap(false, Ordering::SeqCst) {
+            *file = open_append(&self.path)?;
+        }
+        self.rotate_if_needed(&mut file)?;
+        file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.lock().unwrap().flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rotate_if_needed_leaves_a_small_file_alone() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("mini.log");
+        fs::write(&path, "short").unwrap();
+
+        let writer = ReopenableFile::open(path.to_str().unwrap()).unwrap();
+        let mut file = writer.file.lock().unwrap();
+        writer.rotate_if_needed(&mut file).unwrap();
+
+        assert!(!rotated_path(&path).exists());
+    }
+
+    #[test]
+    fn test_rotate_if_needed_renames_a_large_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("mini.log");
+        fs::write(&path, vec![b'x'; MAX_LOG_BYTES as usize + 1]).unwrap();
+
+        let writer = ReopenableFile::open(path.to_str().unwrap()).unwrap();
+        let mut file = writer.file.lock().unwrap();
+        writer.rotate_if_needed(&mut file).unwrap();
+
+        assert!(rotated_path(&path).exists());
+    }
+
+    #[test]
+    fn test_write_reopens_the_file_after_a_requested_reopen() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("mini.log");
+
+        let mut writer = ReopenableFile::open(path.to_str().unwrap()).unwrap();
+        writer.write_all(b"before\n").unwrap();
+
+        // Simulate a log rotation swapping the file out from under us, then
+        // a SIGUSR1 telling us to pick up the new one at the same path.
+        fs::rename(&path, dir.path().join("mini.log.1")).unwrap();
+        REOPEN_REQUESTED.store(true, Ordering::SeqCst);
+        writer.write_all(b"after\n").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "after\n");
+    }
+}
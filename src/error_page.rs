@@ -0,0 +1,142 @@
+use crate::fastcgi::FastCgiResponse;
+
+/// What went wrong, for [`render`] to describe: either php-fpm (or the
+/// built-in server) was unreachable at all, or it answered with a 5xx
+/// status and/or wrote to stderr.
+pub struct ErrorPageContext<'a> {
+    pub php_version: &'a str,
+    pub backend: &'a str,
+    pub status: Option<u16>,
+    pub stderr: &'a [u8],
+    pub connection_error: Option<&'a str>,
+}
+
+/// Render a styled HTML error page in place of a blank 502/500, mirroring
+/// Valet's dump of fatal errors: the PHP version and backend a request was
+/// routed to, the connection error if php-fpm couldn't be reached at all,
+/// and a tail of anything it wrote to stderr otherwise.
+pub fn render(context: &ErrorPageContext) -> FastCgiResponse {
+    let status = context.status.unwrap_or(502);
+    let heading = match context.connection_error {
+        Some(_) => "Could not reach the PHP backend".to_string(),
+        None => format!("PHP returned a {status} response"),
+    };
+
+    let mut details = format!(
+        "<dt>PHP version</dt><dd>{}</dd><dt>Backend</dt><dd>{}</dd>",
+        html_escape(context.php_version),
+        html_escape(context.backend),
+    );
+    if let Some(error) = context.connection_error {
+        details.push_str(&format!("<dt>Error</dt><dd>{}</dd>", html_escape(error)));
+    }
+
+    let stderr_tail = tail_lines(context.stderr, STDERR_TAIL_LINES);
+    let stderr_block = if stderr_tail.is_empty() {
+        String::new()
+    } else {
+        format!("<h2>Recent stderr</h2><pre>{}</pre>", html_escape(&stderr_tail))
+    };
+
+    let body = format!(
+        "<!doctype html><html><head><meta charset=\"utf-8\"><title>{heading}</title>\
+         <style>{STYLE}</style></head><body><h1>{heading}</h1><dl>{details}</dl>{stderr_block}</body></html>",
+        heading = html_escape(&heading),
+    );
+
+    FastCgiResponse {
+        status,
+        headers: vec![("Content-Type".to_string(), "text/html; charset=utf-8".to_string())],
+        body: body.into_bytes(),
+        stderr: context.stderr.to_vec(),
+    }
+}
+
+/// How many trailing stderr lines to show - enough to see the fatal error
+/// and its stack trace without dumping an entire noisy log.
+const STDERR_TAIL_LINES: usize = 20;
+
+const STYLE: &str = "body{font-family:-apple-system,BlinkMacSystemFont,sans-serif;margin:2rem;color:#1a1a1a}\
+h1{color:#c0392b}dt{font-weight:bold;margin-top:.5rem}dd{margin:0}\
+pre{background:#1a1a1a;color:#eee;padding:1rem;overflow:auto;border-radius:4px}";
+
+fn tail_lines(stderr: &[u8], max_lines: usize) -> String {
+    let text = String::from_utf8_lossy(stderr);
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines.len().saturating_sub(max_lines);
+    lines[start..].join("\n")
+}
+
+fn html_escape(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_describes_a_connection_failure() {
+        let response = render(&ErrorPageContext {
+            php_version: "8.2",
+            backend: "no backend available",
+            status: None,
+            stderr: &[],
+            connection_error: Some("no php-fpm binary found for PHP 8.2"),
+        });
+
+        assert_eq!(response.status, 502);
+        let body = String::from_utf8(response.body).unwrap();
+        assert!(body.contains("Could not reach the PHP backend"));
+        assert!(body.contains("no php-fpm binary found for PHP 8.2"));
+        assert!(body.contains("8.2"));
+    }
+
+    #[test]
+    fn test_render_includes_a_tail_of_stderr_for_a_5xx_response() {
+        let stderr = b"PHP Warning: first\nPHP Fatal error: it broke\n".to_vec();
+        let response = render(&ErrorPageContext {
+            php_version: "8.3",
+            backend: "php-fpm via /tmp/mini-8.3.sock",
+            status: Some(500),
+            stderr: &stderr,
+            connection_error: None,
+        });
+
+        assert_eq!(response.status, 500);
+        let body = String::from_utf8(response.body).unwrap();
+        assert!(body.contains("PHP returned a 500 response"));
+        assert!(body.contains("PHP Fatal error: it broke"));
+        assert_eq!(response.stderr, stderr);
+    }
+
+    #[test]
+    fn test_render_escapes_html_in_stderr() {
+        let stderr = b"<script>alert(1)</script>".to_vec();
+        let response = render(&ErrorPageContext {
+            php_version: "8.2",
+            backend: "php-fpm via /tmp/mini-8.2.sock",
+            status: Some(500),
+            stderr: &stderr,
+            connection_error: None,
+        });
+
+        let body = String::from_utf8(response.body).unwrap();
+        assert!(!body.contains("<script>"));
+        assert!(body.contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn test_render_omits_the_stderr_block_when_there_is_none() {
+        let response = render(&ErrorPageContext {
+            php_version: "8.2",
+            backend: "php-fpm via /tmp/mini-8.2.sock",
+            status: Some(500),
+            stderr: &[],
+            connection_error: None,
+        });
+
+        let body = String::from_utf8(response.body).unwrap();
+        assert!(!body.contains("Recent stderr"));
+    }
+}
@@ -0,0 +1,31 @@
+//! Events a [`crate::instance::Mini`] instance can emit, for a host (menu-bar
+//! app, editor extension) to stay in sync without polling `mini status`.
+//!
+//! Nothing inside mini calls [`crate::instance::Mini::emit`] yet - site
+//! lifecycle changes go through [`crate::site::SiteManager`], which doesn't
+//! hold a reference to the [`Mini`][crate::instance::Mini] that owns it, and
+//! certificate/scan/service-health events don't have a natural call site
+//! until `tld.rs`/`parking.rs`/`services.rs` are threaded through `Mini` too.
+//! [`crate::instance::Mini::subscribe`] is built and tested standalone, ready
+//! to wire each of those into as that happens.
+
+use serde::Serialize;
+
+use crate::services::ManagedServiceKind;
+
+/// Something a [`crate::instance::Mini`] instance wants subscribers to know
+/// about, without them having to poll for it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum MiniEvent {
+    SiteLinked { domain: String },
+    SiteUnlinked { domain: String },
+    SiteSecured { domain: String },
+    SiteDisabled { domain: String },
+    SiteEnabled { domain: String },
+    /// A TLS certificate was issued or renewed for `domain`.
+    CertificateIssued { domain: String },
+    /// A parked-directory rescan finished; counts mirror
+    /// [`crate::site::RescanDiff`].
+    ScanCompleted { added: usize, removed: usize },
+    ServiceHealthChanged { kind: ManagedServiceKind, running: bool },
+}
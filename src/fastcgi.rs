@@ -0,0 +1,827 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use lazy_static::lazy_static;
+use prometheus::{register_int_counter_vec, register_int_gauge_vec, IntCounterVec, IntGaugeVec};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpStream, UnixStream};
+use tokio::sync::Mutex;
+
+const FCGI_VERSION_1: u8 = 1;
+const FCGI_BEGIN_REQUEST: u8 = 1;
+const FCGI_END_REQUEST: u8 = 3;
+const FCGI_PARAMS: u8 = 4;
+const FCGI_STDIN: u8 = 5;
+const FCGI_STDOUT: u8 = 6;
+const FCGI_STDERR: u8 = 7;
+
+const FCGI_RESPONDER: u16 = 1;
+
+/// Tells php-fpm to keep the connection open after answering instead of
+/// closing it, so [`CONNECTION_POOL`] can hand it to the next request
+/// against the same socket instead of reconnecting from scratch.
+const FCGI_KEEP_CONN: u8 = 1;
+
+/// Idle keep-alive connections kept per FastCGI socket. Bounds the file
+/// descriptors mini holds open once a site goes quiet, not the number of
+/// requests that can be in flight at once - a request that finds the pool
+/// empty just opens its own connection, same as before pooling existed.
+const MAX_IDLE_CONNECTIONS_PER_POOL: usize = 8;
+
+lazy_static! {
+    static ref CONNECTION_POOL: ConnectionPool = ConnectionPool::default();
+    static ref CONNECTION_POOL_HITS: IntCounterVec = register_int_counter_vec!(
+        "mini_fastcgi_connection_pool_hits_total",
+        "FastCGI requests that reused a pooled keep-alive connection instead of opening a new one",
+        &["pool"]
+    )
+    .unwrap();
+    static ref CONNECTION_POOL_MISSES: IntCounterVec = register_int_counter_vec!(
+        "mini_fastcgi_connection_pool_misses_total",
+        "FastCGI requests that had to open a fresh connection because none were idle in the pool",
+        &["pool"]
+    )
+    .unwrap();
+    static ref CONNECTION_POOL_IDLE: IntGaugeVec = register_int_gauge_vec!(
+        "mini_fastcgi_connection_pool_idle_connections",
+        "Idle keep-alive connections currently held open per FastCGI pool",
+        &["pool"]
+    )
+    .unwrap();
+}
+
+/// Every FastCGI record is capped at this content length; a payload bigger
+/// than this (params or a request body) is split across several records.
+const MAX_RECORD_CONTENT: usize = 0xFFFF;
+
+/// There's only ever one request in flight per connection, so the
+/// request id FastCGI associates records with is fixed.
+const REQUEST_ID: u16 = 1;
+
+/// How long a normal request gets to run before mini gives up on php-fpm.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An Xdebug step-debugging session can sit at a breakpoint indefinitely,
+/// so a request carrying a trigger gets minutes instead of seconds.
+const XDEBUG_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Where php-fpm is listening.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FastCgiAddr {
+    Unix(PathBuf),
+    Tcp(String),
+}
+
+/// A request body to stream into `FCGI_STDIN`: either bytes already held
+/// in memory (the common case for anything but a large upload), or an
+/// open [`AsyncRead`] source read from directly as it's written out, so a
+/// multi-gigabyte upload never has to be buffered in full before it
+/// reaches php-fpm.
+pub enum RequestBody {
+    Bytes(Vec<u8>),
+    Stream {
+        reader: Pin<Box<dyn AsyncRead + Send>>,
+        content_length: u64,
+    },
+}
+
+impl RequestBody {
+    pub fn from_stream(reader: impl AsyncRead + Send + 'static, content_length: u64) -> Self {
+        RequestBody::Stream { reader: Box::pin(reader), content_length }
+    }
+
+    fn content_length(&self) -> u64 {
+        match self {
+            RequestBody::Bytes(bytes) => bytes.len() as u64,
+            RequestBody::Stream { content_length, .. } => *content_length,
+        }
+    }
+}
+
+impl Default for RequestBody {
+    fn default() -> Self {
+        RequestBody::Bytes(Vec::new())
+    }
+}
+
+impl std::fmt::Debug for RequestBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RequestBody::Bytes(bytes) => f.debug_tuple("Bytes").field(&bytes.len()).finish(),
+            RequestBody::Stream { content_length, .. } => {
+                f.debug_struct("Stream").field("content_length", content_length).finish()
+            }
+        }
+    }
+}
+
+/// Everything php-fpm needs to run a script: the CGI params (built from
+/// this into SCRIPT_FILENAME, QUERY_STRING, HTTP_* headers, etc) and the
+/// request body, streamed over STDIN.
+#[derive(Debug, Default)]
+pub struct FastCgiRequest {
+    pub method: String,
+    pub script_filename: String,
+    /// The request path php-fpm matches `ping.path`/`pm.status_path`
+    /// against - distinct from `script_filename`, which points at a real
+    /// file on disk and is left empty for those built-in endpoints.
+    pub script_name: String,
+    /// The full path of the incoming request (e.g. `/index.php/foo`),
+    /// used to derive `PATH_INFO`/`PATH_TRANSLATED` below - distinct from
+    /// `script_name`, since a request can carry extra segments past the
+    /// front controller that the front controller still needs to see.
+    pub path: String,
+    pub document_root: String,
+    pub query_string: String,
+    pub server_name: String,
+    pub server_addr: String,
+    pub server_port: u16,
+    pub remote_addr: String,
+    pub remote_port: u16,
+    /// Whether the request arrived over a TLS-terminated listener; sets
+    /// `HTTPS=on` so frameworks that check it don't think they're being
+    /// accessed over plain HTTP.
+    pub https: bool,
+    /// Request headers, excluding Content-Type/Content-Length which get
+    /// their own CGI variables.
+    pub headers: Vec<(String, String)>,
+    pub content_type: Option<String>,
+    pub body: RequestBody,
+    /// php.ini directive overrides for this site, sent as `PHP_ADMIN_VALUE`
+    /// so they apply even to directives the script itself can't change.
+    pub php_admin_values: Vec<(String, String)>,
+}
+
+/// php-fpm's response: the parsed status line and headers it sent back
+/// over STDOUT, the body that followed them, and anything it wrote to
+/// STDERR (for the caller to fold into mini's own logs).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FastCgiResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+/// Run `request` against the php-fpm pool listening at `addr` and return
+/// its response, reusing an idle keep-alive connection from
+/// [`CONNECTION_POOL`] when one is available rather than reconnecting from
+/// scratch - a page with dozens of asset-triggered PHP requests otherwise
+/// pays a fresh connect for every one of them. A request carrying an
+/// Xdebug trigger gets [`XDEBUG_TIMEOUT`] instead of [`DEFAULT_TIMEOUT`],
+/// since it may be sitting at a breakpoint.
+pub async fn send(addr: &FastCgiAddr, request: &mut FastCgiRequest) -> Result<FastCgiResponse> {
+    let timeout = response_timeout(request);
+    match tokio::time::timeout(timeout, send_with_pool(addr, request)).await {
+        Ok(result) => result,
+        Err(_) => bail!("php-fpm did not respond within {timeout:?}"),
+    }
+}
+
+async fn send_with_pool(addr: &FastCgiAddr, request: &mut FastCgiRequest) -> Result<FastCgiResponse> {
+    let (mut stream, reused) = CONNECTION_POOL.acquire(addr).await?;
+
+    if let Err(error) = write_begin_request(&mut stream).await {
+        if !reused {
+            return Err(error);
+        }
+        // A pooled connection php-fpm had already closed on its end
+        // (e.g. it hit `pm.max_requests`) - nothing was sent yet, so it's
+        // safe to retry once against a brand-new connection rather than
+        // failing the whole request over a connection we should never
+        // have reused.
+        stream = connect(addr).await?;
+        write_begin_request(&mut stream).await?;
+    }
+
+    match send_body_and_read(&mut stream, request).await {
+        Ok(response) => {
+            CONNECTION_POOL.release(addr, stream).await;
+            Ok(response)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// A connection to php-fpm, type-erased so [`ConnectionPool`] can hold
+/// both [`UnixStream`] and [`TcpStream`] connections in the same bucket.
+trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+async fn connect(addr: &FastCgiAddr) -> Result<Box<dyn Stream>> {
+    match addr {
+        FastCgiAddr::Unix(path) => Ok(Box::new(UnixStream::connect(path).await?)),
+        FastCgiAddr::Tcp(host_port) => Ok(Box::new(TcpStream::connect(host_port).await?)),
+    }
+}
+
+fn pool_key(addr: &FastCgiAddr) -> String {
+    match addr {
+        FastCgiAddr::Unix(path) => format!("unix:{}", path.display()),
+        FastCgiAddr::Tcp(host_port) => format!("tcp:{host_port}"),
+    }
+}
+
+/// Idle keep-alive connections, bucketed per FastCGI socket so pools for
+/// different PHP versions (or Xdebug's separate pool) never hand out a
+/// connection meant for another one.
+#[derive(Default)]
+struct ConnectionPool {
+    idle: Mutex<HashMap<String, Vec<Box<dyn Stream>>>>,
+}
+
+impl ConnectionPool {
+    /// Take an idle connection for `addr` if one is available, or open a
+    /// fresh one otherwise. The returned `bool` is whether the connection
+    /// was reused, which [`send_with_pool`] needs to decide whether a
+    /// failure on it is safe to retry.
+    async fn acquire(&self, addr: &FastCgiAddr) -> Result<(Box<dyn Stream>, bool)> {
+        let key = pool_key(addr);
+        let pooled = self.idle.lock().await.get_mut(&key).and_then(Vec::pop);
+        if let Some(stream) = pooled {
+            CONNECTION_POOL_HITS.with_label_values(&[&key]).inc();
+            CONNECTION_POOL_IDLE.with_label_values(&[&key]).dec();
+            return Ok((stream, true));
+        }
+        CONNECTION_POOL_MISSES.with_label_values(&[&key]).inc();
+        Ok((connect(addr).await?, false))
+    }
+
+    /// Return a still-good connection to the pool for reuse, unless its
+    /// bucket is already at [`MAX_IDLE_CONNECTIONS_PER_POOL`] - in which
+    /// case it's simply dropped, closing it.
+    async fn release(&self, addr: &FastCgiAddr, stream: Box<dyn Stream>) {
+        let key = pool_key(addr);
+        let mut idle = self.idle.lock().await;
+        let bucket = idle.entry(key.clone()).or_default();
+        if bucket.len() < MAX_IDLE_CONNECTIONS_PER_POOL {
+            bucket.push(stream);
+            CONNECTION_POOL_IDLE.with_label_values(&[&key]).inc();
+        }
+    }
+}
+
+/// How long a backend gets to answer `request` before the caller gives
+/// up: [`XDEBUG_TIMEOUT`] if it carries an Xdebug trigger, otherwise
+/// [`DEFAULT_TIMEOUT`]. Shared with [`crate::php_builtin::send`] so both
+/// backends a site can run against wait the same amount of time.
+pub(crate) fn response_timeout(request: &FastCgiRequest) -> Duration {
+    if wants_xdebug(request) {
+        XDEBUG_TIMEOUT
+    } else {
+        DEFAULT_TIMEOUT
+    }
+}
+
+/// True if `request` carries an Xdebug trigger: an `XDEBUG_SESSION` cookie
+/// or an `XDEBUG_SESSION`/`XDEBUG_TRIGGER` query parameter. The trigger
+/// itself is already passed through untouched as part of the cookie/query
+/// CGI params - this only decides how long to wait for a response.
+fn wants_xdebug(request: &FastCgiRequest) -> bool {
+    let has_cookie_session = request.headers.iter().any(|(name, value)| {
+        name.eq_ignore_ascii_case("Cookie") && value.contains("XDEBUG_SESSION=")
+    });
+    let has_query_trigger =
+        request.query_string.contains("XDEBUG_SESSION") || request.query_string.contains("XDEBUG_TRIGGER");
+    has_cookie_session || has_query_trigger
+}
+
+async fn send_over<S>(stream: &mut S, request: &mut FastCgiRequest) -> Result<FastCgiResponse>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    write_begin_request(stream).await?;
+    send_body_and_read(stream, request).await
+}
+
+async fn send_body_and_read<S>(stream: &mut S, request: &mut FastCgiRequest) -> Result<FastCgiResponse>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    write_params(stream, &encode_name_value_pairs(&build_params(request))).await?;
+    write_stdin(stream, &mut request.body).await?;
+    read_response(stream).await
+}
+
+/// Write `content` as a run of same-typed records, splitting it into
+/// `MAX_RECORD_CONTENT`-sized chunks. Callers are responsible for sending
+/// the zero-length record that terminates the stream afterwards.
+async fn write_record<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    record_type: u8,
+    content: &[u8],
+) -> Result<()> {
+    if content.is_empty() {
+        return Ok(());
+    }
+    for chunk in content.chunks(MAX_RECORD_CONTENT) {
+        write_one_record(stream, record_type, chunk).await?;
+    }
+    Ok(())
+}
+
+async fn write_one_record<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    record_type: u8,
+    content: &[u8],
+) -> Result<()> {
+    let len = content.len() as u16;
+    let header = [
+        FCGI_VERSION_1,
+        record_type,
+        (REQUEST_ID >> 8) as u8,
+        (REQUEST_ID & 0xFF) as u8,
+        (len >> 8) as u8,
+        (len & 0xFF) as u8,
+        0, // padding length
+        0, // reserved
+    ];
+    stream.write_all(&header).await?;
+    stream.write_all(content).await?;
+    Ok(())
+}
+
+async fn write_begin_request<S: AsyncWrite + Unpin>(stream: &mut S) -> Result<()> {
+    let body = [
+        (FCGI_RESPONDER >> 8) as u8,
+        (FCGI_RESPONDER & 0xFF) as u8,
+        FCGI_KEEP_CONN,
+        0,
+        0,
+        0,
+        0,
+        0,
+    ];
+    write_one_record(stream, FCGI_BEGIN_REQUEST, &body).await
+}
+
+async fn write_params<S: AsyncWrite + Unpin>(stream: &mut S, encoded: &[u8]) -> Result<()> {
+    write_record(stream, FCGI_PARAMS, encoded).await?;
+    write_one_record(stream, FCGI_PARAMS, &[]).await
+}
+
+/// Write `body` out as `FCGI_STDIN` records. A [`RequestBody::Bytes`] body
+/// is chunked the same way any other record content is; a
+/// [`RequestBody::Stream`] body is read in `MAX_RECORD_CONTENT`-sized
+/// pieces and each piece is written as soon as it's read, so the whole
+/// upload never needs to sit resident in memory at once - `write_all`
+/// (and the socket it's ultimately backed by) applies backpressure the
+/// same way it would for any other write.
+async fn write_stdin<S: AsyncWrite + Unpin>(stream: &mut S, body: &mut RequestBody) -> Result<()> {
+    match body {
+        RequestBody::Bytes(bytes) => write_record(stream, FCGI_STDIN, bytes).await?,
+        RequestBody::Stream { reader, .. } => {
+            let mut buffer = vec![0u8; MAX_RECORD_CONTENT];
+            loop {
+                let read = reader.read(&mut buffer).await?;
+                if read == 0 {
+                    break;
+                }
+                write_one_record(stream, FCGI_STDIN, &buffer[..read]).await?;
+            }
+        }
+    }
+    write_one_record(stream, FCGI_STDIN, &[]).await
+}
+
+/// Build the CGI param set php-fpm expects, translating request headers
+/// into their `HTTP_*` form (dashes become underscores, uppercased). This is
+/// also how `request_tracing`'s `traceparent`/`uber-trace-id` headers reach
+/// PHP once a `FastCgiRequest` carries them: no special-casing needed here,
+/// they fall out as `HTTP_TRACEPARENT`/`HTTP_UBER_TRACE_ID` like any other
+/// header.
+fn build_params(request: &FastCgiRequest) -> Vec<(String, String)> {
+    let mut params = vec![
+        ("GATEWAY_INTERFACE".to_string(), "CGI/1.1".to_string()),
+        ("SERVER_SOFTWARE".to_string(), "mini".to_string()),
+        ("SERVER_PROTOCOL".to_string(), "HTTP/1.1".to_string()),
+        ("REQUEST_METHOD".to_string(), request.method.clone()),
+        ("SCRIPT_FILENAME".to_string(), request.script_filename.clone()),
+        ("SCRIPT_NAME".to_string(), request.script_name.clone()),
+        ("DOCUMENT_ROOT".to_string(), request.document_root.clone()),
+        ("QUERY_STRING".to_string(), request.query_string.clone()),
+        ("SERVER_NAME".to_string(), request.server_name.clone()),
+        ("SERVER_ADDR".to_string(), request.server_addr.clone()),
+        ("SERVER_PORT".to_string(), request.server_port.to_string()),
+        ("REMOTE_ADDR".to_string(), request.remote_addr.clone()),
+        ("REMOTE_PORT".to_string(), request.remote_port.to_string()),
+        ("CONTENT_LENGTH".to_string(), request.body.content_length().to_string()),
+    ];
+
+    if request.https {
+        params.push(("HTTPS".to_string(), "on".to_string()));
+    }
+
+    if let Some(content_type) = &request.content_type {
+        params.push(("CONTENT_TYPE".to_string(), content_type.clone()));
+    }
+
+    if let Some(info) = path_info(&request.path, &request.script_name) {
+        params.push(("PATH_INFO".to_string(), info.to_string()));
+        params.push(("PATH_TRANSLATED".to_string(), format!("{}{info}", request.document_root)));
+    }
+
+    for (name, value) in &request.headers {
+        let cgi_name = format!("HTTP_{}", name.to_uppercase().replace('-', "_"));
+        params.push((cgi_name, value.clone()));
+    }
+
+    if !request.php_admin_values.is_empty() {
+        let directives = request
+            .php_admin_values
+            .iter()
+            .map(|(directive, value)| format!("{directive} {value}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        params.push(("PHP_ADMIN_VALUE".to_string(), directives));
+    }
+
+    params
+}
+
+/// The part of `path` left over past `script_name`, for frameworks (older
+/// Symfony, CodeIgniter) that route on `PATH_INFO` rather than rewriting
+/// everything to the front controller - e.g. `/index.php/foo` with a
+/// `script_name` of `/index.php` yields `/foo`. `None` when `path` doesn't
+/// start with `script_name`, or has nothing left over once it does.
+fn path_info<'a>(path: &'a str, script_name: &str) -> Option<&'a str> {
+    if script_name.is_empty() {
+        return None;
+    }
+    let remainder = path.strip_prefix(script_name)?;
+    if remainder.is_empty() || !remainder.starts_with('/') {
+        return None;
+    }
+    Some(remainder)
+}
+
+/// FastCGI's variable-length name-value encoding: lengths under 128 fit in
+/// a single byte, anything bigger uses 4 bytes with the high bit set.
+fn encode_name_value_pairs(pairs: &[(String, String)]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    for (name, value) in pairs {
+        encode_length(&mut encoded, name.len());
+        encode_length(&mut encoded, value.len());
+        encoded.extend_from_slice(name.as_bytes());
+        encoded.extend_from_slice(value.as_bytes());
+    }
+    encoded
+}
+
+fn encode_length(out: &mut Vec<u8>, len: usize) {
+    if len < 128 {
+        out.push(len as u8);
+    } else {
+        let len = len as u32;
+        out.push((len >> 24) as u8 | 0x80);
+        out.push((len >> 16) as u8);
+        out.push((len >> 8) as u8);
+        out.push(len as u8);
+    }
+}
+
+/// Read records until php-fpm sends FCGI_END_REQUEST, splitting STDOUT
+/// into the CGI-style status line/headers/body and collecting STDERR
+/// separately.
+async fn read_response<S: AsyncRead + Unpin>(stream: &mut S) -> Result<FastCgiResponse> {
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+
+    loop {
+        let mut header = [0u8; 8];
+        stream.read_exact(&mut header).await?;
+        let record_type = header[1];
+        let content_length = u16::from_be_bytes([header[4], header[5]]) as usize;
+        let padding_length = header[6] as usize;
+
+        let mut content = vec![0u8; content_length];
+        stream.read_exact(&mut content).await?;
+        if padding_length > 0 {
+            let mut padding = vec![0u8; padding_length];
+            stream.read_exact(&mut padding).await?;
+        }
+
+        match record_type {
+            FCGI_STDOUT => stdout.extend_from_slice(&content),
+            FCGI_STDERR => stderr.extend_from_slice(&content),
+            FCGI_END_REQUEST => break,
+            _ => {}
+        }
+    }
+
+    let (status, headers, body) = parse_cgi_output(&stdout)?;
+    Ok(FastCgiResponse { status, headers, body, stderr })
+}
+
+/// Split php-fpm's CGI-style STDOUT into its status code, headers, and
+/// body: headers are `Name: value` lines up to the first blank line, and a
+/// `Status:` header (if present) sets the status code instead of becoming
+/// a response header.
+fn parse_cgi_output(output: &[u8]) -> Result<(u16, Vec<(String, String)>, Vec<u8>)> {
+    let separator = output
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .map(|pos| (pos, 4))
+        .or_else(|| output.windows(2).position(|window| window == b"\n\n").map(|pos| (pos, 2)));
+
+    let Some((header_end, separator_len)) = separator else {
+        bail!("php-fpm response had no header/body separator");
+    };
+
+    let header_block = std::str::from_utf8(&output[..header_end])?;
+    let body = output[header_end + separator_len..].to_vec();
+
+    let mut status = 200;
+    let mut headers = Vec::new();
+    for line in header_block.split("\r\n").flat_map(|line| line.split('\n')) {
+        if line.is_empty() {
+            continue;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("Status") {
+            if let Some(code) = value.split_whitespace().next() {
+                status = code.parse().unwrap_or(200);
+            }
+        } else {
+            headers.push((name.to_string(), value.to_string()));
+        }
+    }
+
+    Ok((status, headers, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::duplex;
+
+    #[test]
+    fn test_encode_name_value_pairs_uses_single_byte_lengths_for_short_values() {
+        let encoded = encode_name_value_pairs(&[("FOO".to_string(), "bar".to_string())]);
+        assert_eq!(encoded, vec![3, 3, b'F', b'O', b'O', b'b', b'a', b'r']);
+    }
+
+    #[test]
+    fn test_encode_length_uses_four_bytes_past_127() {
+        let mut out = Vec::new();
+        encode_length(&mut out, 200);
+        assert_eq!(out, vec![0x80, 0, 0, 200]);
+    }
+
+    #[test]
+    fn test_build_params_translates_headers_to_http_prefixed_names() {
+        let request = FastCgiRequest {
+            method: "GET".to_string(),
+            headers: vec![("X-Forwarded-For".to_string(), "1.2.3.4".to_string())],
+            ..Default::default()
+        };
+        let params = build_params(&request);
+        assert!(params.contains(&("HTTP_X_FORWARDED_FOR".to_string(), "1.2.3.4".to_string())));
+    }
+
+    #[test]
+    fn test_build_params_joins_php_admin_values_with_newlines() {
+        let request = FastCgiRequest {
+            method: "GET".to_string(),
+            php_admin_values: vec![
+                ("memory_limit".to_string(), "512M".to_string()),
+                ("upload_max_filesize".to_string(), "100M".to_string()),
+            ],
+            ..Default::default()
+        };
+        let params = build_params(&request);
+        assert!(params.contains(&(
+            "PHP_ADMIN_VALUE".to_string(),
+            "memory_limit 512M\nupload_max_filesize 100M".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_build_params_omits_php_admin_value_when_there_are_no_overrides() {
+        let params = build_params(&FastCgiRequest::default());
+        assert!(!params.iter().any(|(name, _)| name == "PHP_ADMIN_VALUE"));
+    }
+
+    #[test]
+    fn test_build_params_includes_script_name_for_ping_style_requests() {
+        let request = FastCgiRequest {
+            script_name: "/mini-ping".to_string(),
+            ..Default::default()
+        };
+        let params = build_params(&request);
+        assert!(params.contains(&("SCRIPT_NAME".to_string(), "/mini-ping".to_string())));
+    }
+
+    #[test]
+    fn test_build_params_includes_path_info_and_path_translated_for_extra_segments() {
+        let request = FastCgiRequest {
+            path: "/index.php/foo/bar".to_string(),
+            script_name: "/index.php".to_string(),
+            document_root: "/var/www/public".to_string(),
+            ..Default::default()
+        };
+        let params = build_params(&request);
+        assert!(params.contains(&("PATH_INFO".to_string(), "/foo/bar".to_string())));
+        assert!(params.contains(&(
+            "PATH_TRANSLATED".to_string(),
+            "/var/www/public/foo/bar".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_build_params_omits_path_info_when_the_path_matches_the_script_name_exactly() {
+        let request = FastCgiRequest {
+            path: "/index.php".to_string(),
+            script_name: "/index.php".to_string(),
+            ..Default::default()
+        };
+        let params = build_params(&request);
+        assert!(!params.iter().any(|(name, _)| name == "PATH_INFO"));
+        assert!(!params.iter().any(|(name, _)| name == "PATH_TRANSLATED"));
+    }
+
+    #[test]
+    fn test_build_params_sets_https_on_for_a_tls_terminated_request() {
+        let request = FastCgiRequest { https: true, ..Default::default() };
+        let params = build_params(&request);
+        assert!(params.contains(&("HTTPS".to_string(), "on".to_string())));
+    }
+
+    #[test]
+    fn test_build_params_omits_https_for_a_plain_http_request() {
+        let params = build_params(&FastCgiRequest::default());
+        assert!(!params.iter().any(|(name, _)| name == "HTTPS"));
+    }
+
+    #[test]
+    fn test_wants_xdebug_detects_a_session_cookie() {
+        let request = FastCgiRequest {
+            headers: vec![("Cookie".to_string(), "XDEBUG_SESSION=mini".to_string())],
+            ..Default::default()
+        };
+        assert!(wants_xdebug(&request));
+    }
+
+    #[test]
+    fn test_wants_xdebug_detects_a_query_trigger() {
+        let request = FastCgiRequest {
+            query_string: "XDEBUG_TRIGGER=1".to_string(),
+            ..Default::default()
+        };
+        assert!(wants_xdebug(&request));
+    }
+
+    #[test]
+    fn test_wants_xdebug_is_false_for_an_ordinary_request() {
+        let request = FastCgiRequest {
+            headers: vec![("Cookie".to_string(), "session_id=abc".to_string())],
+            query_string: "page=2".to_string(),
+            ..Default::default()
+        };
+        assert!(!wants_xdebug(&request));
+    }
+
+    #[test]
+    fn test_parse_cgi_output_extracts_status_headers_and_body() {
+        let output = b"Status: 404 Not Found\r\nContent-Type: text/html\r\n\r\n<h1>missing</h1>";
+        let (status, headers, body) = parse_cgi_output(output).unwrap();
+
+        assert_eq!(status, 404);
+        assert_eq!(headers, vec![("Content-Type".to_string(), "text/html".to_string())]);
+        assert_eq!(body, b"<h1>missing</h1>");
+    }
+
+    #[test]
+    fn test_parse_cgi_output_defaults_to_200_without_a_status_header() {
+        let output = b"Content-Type: text/plain\r\n\r\nhello";
+        let (status, _, body) = parse_cgi_output(output).unwrap();
+
+        assert_eq!(status, 200);
+        assert_eq!(body, b"hello");
+    }
+
+    /// A minimal FastCGI responder that reads params/stdin then sends back
+    /// a fixed body, standing in for php-fpm in tests.
+    async fn respond_with<S>(mut stream: S, body: &'static str)
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        // Drain records until the empty STDIN record that ends the request.
+        loop {
+            let mut header = [0u8; 8];
+            stream.read_exact(&mut header).await.unwrap();
+            let record_type = header[1];
+            let content_length = u16::from_be_bytes([header[4], header[5]]) as usize;
+            let mut content = vec![0u8; content_length];
+            stream.read_exact(&mut content).await.unwrap();
+            if record_type == FCGI_STDIN && content.is_empty() {
+                break;
+            }
+        }
+
+        let payload = format!("Content-Type: text/plain\r\n\r\n{body}");
+        write_one_record(&mut stream, FCGI_STDOUT, payload.as_bytes()).await.unwrap();
+        write_one_record(&mut stream, FCGI_STDOUT, &[]).await.unwrap();
+        let end_request = [0, 0, 0, 0, 0, 0, 0, 0];
+        write_one_record(&mut stream, FCGI_END_REQUEST, &end_request).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_send_over_a_duplex_stream_round_trips_a_response() {
+        let (mut client, server) = duplex(8192);
+
+        let server_task = tokio::spawn(respond_with(server, "hello from fpm"));
+
+        let mut request = FastCgiRequest {
+            method: "GET".to_string(),
+            script_filename: "/var/www/public/index.php".to_string(),
+            ..Default::default()
+        };
+        let response = send_over(&mut client, &mut request).await.unwrap();
+        server_task.await.unwrap();
+
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, b"hello from fpm");
+        assert_eq!(response.headers, vec![("Content-Type".to_string(), "text/plain".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_write_begin_request_sets_the_keep_alive_flag() {
+        let mut buffer = Vec::new();
+        write_begin_request(&mut buffer).await.unwrap();
+        // buffer[..8] is the record header; body[2] (buffer[10]) is the
+        // flags byte BEGIN_REQUEST carries its role in.
+        assert_eq!(buffer[10], FCGI_KEEP_CONN);
+    }
+
+    #[tokio::test]
+    async fn test_send_reuses_a_pooled_connection_across_requests() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let socket_path = dir.path().join("php.sock");
+        let listener = tokio::net::UnixListener::bind(&socket_path).unwrap();
+
+        // The server task accepts exactly once, then answers two requests
+        // in turn over that single connection - if `send` didn't reuse
+        // the pooled connection for the second call, it would open a
+        // second one nobody here ever accepts, and this would time out
+        // instead of completing cleanly.
+        let server_task = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            respond_with(&mut stream, "first").await;
+            respond_with(&mut stream, "second").await;
+        });
+
+        let addr = FastCgiAddr::Unix(socket_path);
+        let mut request = FastCgiRequest { method: "GET".to_string(), ..Default::default() };
+
+        let first = tokio::time::timeout(Duration::from_secs(5), send(&addr, &mut request)).await.unwrap().unwrap();
+        let second = tokio::time::timeout(Duration::from_secs(5), send(&addr, &mut request)).await.unwrap().unwrap();
+        tokio::time::timeout(Duration::from_secs(5), server_task).await.unwrap().unwrap();
+
+        assert_eq!(first.body, b"first");
+        assert_eq!(second.body, b"second");
+    }
+
+    #[tokio::test]
+    async fn test_write_stdin_streams_a_body_across_multiple_records() {
+        let body_bytes = vec![b'x'; MAX_RECORD_CONTENT + 10];
+        let mut body = RequestBody::from_stream(std::io::Cursor::new(body_bytes.clone()), body_bytes.len() as u64);
+
+        let mut buffer = Vec::new();
+        write_stdin(&mut buffer, &mut body).await.unwrap();
+
+        // Walk the records back out of the raw bytes `write_stdin` wrote:
+        // one full-sized record, one with the 10 leftover bytes, then the
+        // empty record that ends the stream - proof the body was written
+        // piece by piece rather than serialized into one oversized record.
+        let mut offset = 0;
+        let mut record_lengths = Vec::new();
+        while offset < buffer.len() {
+            let content_length = u16::from_be_bytes([buffer[offset + 4], buffer[offset + 5]]) as usize;
+            record_lengths.push(content_length);
+            offset += 8 + content_length;
+        }
+        assert_eq!(record_lengths, vec![MAX_RECORD_CONTENT, 10, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_connection_pool_release_drops_connections_past_the_idle_cap() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let addr = FastCgiAddr::Unix(dir.path().join("php.sock"));
+        let pool = ConnectionPool::default();
+
+        for _ in 0..MAX_IDLE_CONNECTIONS_PER_POOL + 1 {
+            let (client, _server) = duplex(64);
+            pool.release(&addr, Box::new(client)).await;
+        }
+
+        let key = pool_key(&addr);
+        assert_eq!(pool.idle.lock().await.get(&key).unwrap().len(), MAX_IDLE_CONNECTIONS_PER_POOL);
+    }
+}
@@ -0,0 +1,299 @@
+//! A small C ABI over [`crate::instance::Mini`], for hosts that want to
+//! drive mini without shelling out to the `mini` binary - the PHP/JS
+//! ecosystem around Valet (Ray, Takeout, editor extensions) being the
+//! motivating case. Gated behind the `ffi` feature since most consumers of
+//! this crate only need the plain Rust API `instance.rs` already provides;
+//! this module is a thin, `unsafe`-heavy wrapper around it, not a second
+//! implementation.
+//!
+//! Every function takes/returns raw pointers and must be called the way a
+//! C header would document: `mini_open` before anything else, every other
+//! call with the handle it returned, `mini_close` exactly once at the end,
+//! and every non-null `*mut c_char` this module hands back freed with
+//! [`mini_free_string`] rather than the host's own `free`.
+//!
+//! No napi-rs bindings yet - `Mini`'s API is still async, and wrapping that
+//! for Node without pulling tokio into a native addon's own event loop is a
+//! bigger design question than this commit answers; the C ABI below is
+//! usable from N-API today via a thin hand-written JS shim that `dlopen`s
+//! this library, the same way any other native Node module wraps a C ABI.
+
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::path::PathBuf;
+use std::ptr;
+
+use tokio::runtime::Runtime;
+
+use crate::cli::{load_config, save_config};
+use crate::instance::Mini;
+use crate::status;
+
+/// An opened mini instance plus a dedicated Tokio runtime to drive its
+/// async API from synchronous C code. Opaque to callers - always accessed
+/// through a pointer this module handed out.
+pub struct MiniHandle {
+    mini: Mini,
+    runtime: Runtime,
+}
+
+/// Read a non-null, UTF-8 `*const c_char` into an owned `String`, or `None`
+/// for a null pointer or invalid UTF-8.
+unsafe fn cstr_to_string(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    CStr::from_ptr(ptr).to_str().ok().map(|s| s.to_string())
+}
+
+/// Hand a Rust `String` to the caller as a heap-allocated, null-terminated
+/// C string they must release with [`mini_free_string`]. Null on a string
+/// containing an interior `\0` (JSON output never does).
+fn string_to_cstring(s: String) -> *mut c_char {
+    match CString::new(s) {
+        Ok(cstring) => cstring.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Open a mini instance rooted at `config_path` (a null-terminated UTF-8
+/// path) - creating a fresh runtime to drive it and loading `config.yaml`
+/// from that path the same way [`Mini::builder`] always does. Returns null
+/// on a null/invalid path or a config file that exists but won't parse.
+/// Pair with [`mini_close`].
+///
+/// # Safety
+/// `config_path` must be null or point to a valid null-terminated C string.
+#[no_mangle]
+pub unsafe extern "C" fn mini_open(config_path: *const c_char) -> *mut MiniHandle {
+    let Some(path) = cstr_to_string(config_path) else {
+        return ptr::null_mut();
+    };
+    let Ok(runtime) = Runtime::new() else {
+        return ptr::null_mut();
+    };
+    let Ok(mini) = Mini::builder().config(PathBuf::from(path)).build() else {
+        return ptr::null_mut();
+    };
+    Box::into_raw(Box::new(MiniHandle { mini, runtime }))
+}
+
+/// Release a handle opened with [`mini_open`]. `handle` must not be used
+/// again afterward. A no-op on null.
+///
+/// # Safety
+/// `handle` must be null or a pointer [`mini_open`] returned that hasn't
+/// already been passed to `mini_close`.
+#[no_mangle]
+pub unsafe extern "C" fn mini_close(handle: *mut MiniHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Release a string returned by [`mini_list`] or [`mini_status`]. A no-op
+/// on null.
+///
+/// # Safety
+/// `s` must be null or a pointer one of this module's functions returned,
+/// not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn mini_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Park a directory (null-terminated UTF-8 path) of sites, the same as
+/// `mini park <path>`. `0` on success, `-1` on a null/invalid argument or
+/// if the config couldn't be reloaded/saved.
+///
+/// # Safety
+/// `handle` must be a live pointer from [`mini_open`]; `path` must be null
+/// or a valid null-terminated C string.
+#[no_mangle]
+pub unsafe extern "C" fn mini_park(handle: *mut MiniHandle, path: *const c_char) -> c_int {
+    let Some(handle) = handle.as_mut() else {
+        return -1;
+    };
+    let Some(path) = cstr_to_string(path) else {
+        return -1;
+    };
+
+    let Ok(mut config) = load_config(handle.mini.config_path()) else {
+        return -1;
+    };
+    config.add_parked_path(path);
+    match save_config(&config, handle.mini.config_path()) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Link `path` (null-terminated UTF-8) as `domain` (null-terminated UTF-8),
+/// the same as `mini link <domain> <path>`. `0` on success, `-1` on a
+/// null/invalid argument or a [`crate::error::MiniError`] from
+/// [`crate::site::SiteManager::add_site`].
+///
+/// # Safety
+/// `handle` must be a live pointer from [`mini_open`]; `domain`/`path` must
+/// each be null or a valid null-terminated C string.
+#[no_mangle]
+pub unsafe extern "C" fn mini_link(
+    handle: *mut MiniHandle,
+    domain: *const c_char,
+    path: *const c_char,
+) -> c_int {
+    let Some(handle) = handle.as_mut() else {
+        return -1;
+    };
+    let (Some(domain), Some(path)) = (cstr_to_string(domain), cstr_to_string(path)) else {
+        return -1;
+    };
+
+    let sites = handle.mini.sites().clone();
+    match handle.runtime.block_on(sites.add_site(&domain, PathBuf::from(path))) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Secure `domain` (null-terminated UTF-8) with a locally-trusted TLS
+/// certificate, the same as `mini secure <domain>`. `0` on success, `-1` on
+/// a null/invalid argument or a [`crate::error::MiniError`] from
+/// [`crate::site::SiteManager::secure_site`].
+///
+/// # Safety
+/// `handle` must be a live pointer from [`mini_open`]; `domain` must be
+/// null or a valid null-terminated C string.
+#[no_mangle]
+pub unsafe extern "C" fn mini_secure(handle: *mut MiniHandle, domain: *const c_char) -> c_int {
+    let Some(handle) = handle.as_mut() else {
+        return -1;
+    };
+    let Some(domain) = cstr_to_string(domain) else {
+        return -1;
+    };
+
+    let sites = handle.mini.sites().clone();
+    match handle.runtime.block_on(sites.secure_site(&domain)) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Every site mini currently knows about, as a JSON array of
+/// [`crate::site::SiteStatus`] - the same shape the admin API's
+/// `/api/sites/status` returns. Null on a null handle; caller owns the
+/// returned string and must release it with [`mini_free_string`].
+///
+/// # Safety
+/// `handle` must be a live pointer from [`mini_open`].
+#[no_mangle]
+pub unsafe extern "C" fn mini_list(handle: *mut MiniHandle) -> *mut c_char {
+    let Some(handle) = handle.as_mut() else {
+        return ptr::null_mut();
+    };
+
+    let sites = handle.mini.sites().clone();
+    let statuses: Vec<_> = handle
+        .runtime
+        .block_on(sites.list_sites(None))
+        .iter()
+        .map(|site| site.status())
+        .collect();
+    match serde_json::to_string(&statuses) {
+        Ok(json) => string_to_cstring(json),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// This instance's [`crate::status::StatusReport`] as JSON, the same shape
+/// `mini status --json` prints. Null on a null handle; caller owns the
+/// returned string and must release it with [`mini_free_string`].
+///
+/// # Safety
+/// `handle` must be a live pointer from [`mini_open`].
+#[no_mangle]
+pub unsafe extern "C" fn mini_status(handle: *mut MiniHandle) -> *mut c_char {
+    let Some(handle) = handle.as_mut() else {
+        return ptr::null_mut();
+    };
+
+    let report = status::gather_status(handle.mini.config());
+    match serde_json::to_string(&report) {
+        Ok(json) => string_to_cstring(json),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn open_with_config(dir: &TempDir) -> *mut MiniHandle {
+        let config_path = dir.path().join("config.yaml");
+        let c_path = CString::new(config_path.to_str().unwrap()).unwrap();
+        unsafe { mini_open(c_path.as_ptr()) }
+    }
+
+    #[test]
+    fn test_open_close_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let handle = open_with_config(&dir);
+        assert!(!handle.is_null());
+        unsafe { mini_close(handle) };
+    }
+
+    #[test]
+    fn test_open_with_null_path_returns_null() {
+        assert!(unsafe { mini_open(ptr::null()) }.is_null());
+    }
+
+    #[test]
+    fn test_link_list_and_secure_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let handle = open_with_config(&dir);
+
+        let domain = CString::new("app.test").unwrap();
+        let path = CString::new(dir.path().to_str().unwrap()).unwrap();
+        assert_eq!(unsafe { mini_link(handle, domain.as_ptr(), path.as_ptr()) }, 0);
+        assert_eq!(unsafe { mini_secure(handle, domain.as_ptr()) }, 0);
+
+        let list_json = unsafe { mini_list(handle) };
+        assert!(!list_json.is_null());
+        let json = unsafe { CStr::from_ptr(list_json) }.to_str().unwrap().to_string();
+        assert!(json.contains("app.test"));
+        unsafe { mini_free_string(list_json) };
+
+        unsafe { mini_close(handle) };
+    }
+
+    #[test]
+    fn test_status_returns_valid_json() {
+        let dir = TempDir::new().unwrap();
+        let handle = open_with_config(&dir);
+
+        let status_json = unsafe { mini_status(handle) };
+        assert!(!status_json.is_null());
+        let json = unsafe { CStr::from_ptr(status_json) }.to_str().unwrap().to_string();
+        assert!(serde_json::from_str::<serde_json::Value>(&json).is_ok());
+        unsafe { mini_free_string(status_json) };
+
+        unsafe { mini_close(handle) };
+    }
+
+    #[test]
+    fn test_park_persists_to_config() {
+        let dir = TempDir::new().unwrap();
+        let handle = open_with_config(&dir);
+
+        let path = CString::new("/Users/test/Sites").unwrap();
+        assert_eq!(unsafe { mini_park(handle, path.as_ptr()) }, 0);
+
+        let config = load_config(unsafe { (*handle).mini.config_path() }).unwrap();
+        assert_eq!(config.parked_paths.len(), 1);
+        unsafe { mini_close(handle) };
+    }
+}
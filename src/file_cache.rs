@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+use std::fs::{File, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+use prometheus::{register_int_counter, IntCounter};
+
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+
+lazy_static! {
+    static ref CACHE_HITS: IntCounter = register_int_counter!(
+        "mini_file_cache_hits_total",
+        "Lookups served from a still-fresh open_file_cache entry without touching the filesystem"
+    )
+    .unwrap();
+    static ref CACHE_MISSES: IntCounter = register_int_counter!(
+        "mini_file_cache_misses_total",
+        "Lookups that had to open/stat the file because there was no entry, or it had expired"
+    )
+    .unwrap();
+}
+
+/// An open file descriptor plus the `stat` metadata it was opened with,
+/// held past the request that first looked it up so the next request for
+/// the same path - common on a parked monorepo, where one file might back
+/// hundreds of sibling sites - can skip the open/stat syscalls entirely.
+struct Entry {
+    file: Arc<File>,
+    metadata: Metadata,
+    cached_at: Instant,
+}
+
+/// A file-descriptor and stat-metadata cache in the spirit of nginx's
+/// `open_file_cache`: entries are trusted for `ttl` before the next lookup
+/// re-`stat`s the path to make sure nothing changed underneath, rather than
+/// paying the open/stat cost on every single request.
+///
+/// Nothing in mini's live request path calls into this yet - the pingora
+/// [`MyProxy`](crate::MyProxy) proxy always forwards to an upstream peer and
+/// never reads a file off disk itself, so there's no static-file-serving
+/// code path to plug a cache in front of. `src/server.rs` has the shape of
+/// one (a `MiniServer` that would serve parked sites' files directly) but it
+/// predates the pingora-based proxy, isn't wired into `main.rs`, and refers
+/// to driver APIs that no longer exist. This module is the cache half of
+/// that feature, built and tested standalone so it's ready once a real
+/// static-serving path exists to use it.
+pub struct OpenFileCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<PathBuf, Entry>>,
+    /// Files at or above this size are read back via [`MappedFile`] instead
+    /// of a buffered [`std::io::Read`] - `None` (the default) never mmaps.
+    /// See [`Self::read`]'s doc comment for why that's opt-in.
+    mmap_threshold_bytes: Option<u64>,
+}
+
+/// What [`OpenFileCache::read`] handed back - a buffered copy for small
+/// files, or a read-only mapping for anything at or above
+/// `mmap_threshold_bytes`. Both deref to `&[u8]`, so most callers can treat
+/// the two identically.
+pub enum FileContents {
+    Buffered(Vec<u8>),
+    #[cfg(unix)]
+    Mapped(MappedFile),
+}
+
+impl std::ops::Deref for FileContents {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            FileContents::Buffered(bytes) => bytes,
+            #[cfg(unix)]
+            FileContents::Mapped(mapped) => mapped,
+        }
+    }
+}
+
+impl OpenFileCache {
+    pub fn new(ttl: Duration) -> Self {
+        OpenFileCache {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+            mmap_threshold_bytes: None,
+        }
+    }
+
+    /// Like [`Self::new`], but files at or above `mmap_threshold_bytes` are
+    /// served back from [`Self::read`] as a memory mapping rather than a
+    /// buffered copy - see [`Self::read`] for why that's worth doing only
+    /// above some size, and only where the platform supports it.
+    pub fn new_with_mmap_threshold(ttl: Duration, mmap_threshold_bytes: u64) -> Self {
+        OpenFileCache {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+            mmap_threshold_bytes: Some(mmap_threshold_bytes),
+        }
+    }
+
+    /// Read `path`'s full contents through the same cached file handle
+    /// [`Self::get`] would return. Below `mmap_threshold_bytes` (or on a
+    /// platform without an `mmap` path, or when no threshold was
+    /// configured) this is a plain buffered read - `mmap`'s win comes from
+    /// skipping the page-cache-to-userspace copy a buffered read pays on
+    /// every call, which only outweighs the cost of the `mmap`/`munmap`
+    /// syscalls themselves once a file is large enough to be read more than
+    /// once or twice, e.g. scrubbing through a large video clip in a
+    /// browser during development.
+    pub fn read(&self, path: &Path) -> io::Result<FileContents> {
+        let (file, metadata) = self.get(path)?;
+
+        #[cfg(unix)]
+        if let Some(threshold) = self.mmap_threshold_bytes {
+            if metadata.len() >= threshold {
+                return Ok(FileContents::Mapped(MappedFile::new(&file, metadata.len() as usize)?));
+            }
+        }
+
+        let mut buf = Vec::with_capacity(metadata.len() as usize);
+        io::Read::read_to_end(&mut &*file, &mut buf)?;
+        Ok(FileContents::Buffered(buf))
+    }
+
+    /// Look up `path`, reusing a cached file handle and metadata if one
+    /// exists and is still within `ttl`. Revalidates (and replaces, on a
+    /// mismatch) by comparing modification time and length against a fresh
+    /// `stat`, the same signal nginx's `open_file_cache_valid` checks.
+    pub fn get(&self, path: &Path) -> io::Result<(Arc<File>, Metadata)> {
+        if let Some(entry) = self.entries.read().unwrap().get(path) {
+            if entry.cached_at.elapsed() < self.ttl {
+                CACHE_HITS.inc();
+                return Ok((entry.file.clone(), entry.metadata.clone()));
+            }
+        }
+
+        CACHE_MISSES.inc();
+        let file = File::open(path)?;
+        let metadata = file.metadata()?;
+        let file = Arc::new(file);
+
+        self.entries.write().unwrap().insert(
+            path.to_path_buf(),
+            Entry {
+                file: file.clone(),
+                metadata: metadata.clone(),
+                cached_at: Instant::now(),
+            },
+        );
+
+        Ok((file, metadata))
+    }
+
+    /// Drop any cached entry for `path`, so the next [`get`](Self::get)
+    /// re-opens it regardless of `ttl` - for callers that know a file
+    /// changed (e.g. a watcher) rather than waiting for the next
+    /// revalidation window.
+    pub fn invalidate(&self, path: &Path) {
+        self.entries.write().unwrap().remove(path);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+}
+
+/// A read-only `mmap` of a file's full contents, used by
+/// [`OpenFileCache::read`] once a file is at or above `mmap_threshold_bytes`.
+/// Dereferences to `&[u8]` and `munmap`s itself on drop, the same
+/// safety-wrapper shape as crates like `memmap2` - kept in-house here since
+/// adding that dependency isn't necessary for the handful of calls this
+/// needs.
+#[cfg(unix)]
+pub struct MappedFile {
+    ptr: *mut nix::libc::c_void,
+    len: usize,
+}
+
+#[cfg(unix)]
+impl MappedFile {
+    fn new(file: &File, len: usize) -> io::Result<Self> {
+        if len == 0 {
+            return Ok(MappedFile { ptr: std::ptr::null_mut(), len: 0 });
+        }
+
+        let ptr = unsafe {
+            nix::sys::mman::mmap(
+                std::ptr::null_mut(),
+                len,
+                nix::sys::mman::ProtFlags::PROT_READ,
+                nix::sys::mman::MapFlags::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+            .map_err(io::Error::from)?
+        };
+
+        Ok(MappedFile { ptr, len })
+    }
+}
+
+#[cfg(unix)]
+impl std::ops::Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        if self.len == 0 {
+            return;
+        }
+        unsafe {
+            let _ = nix::sys::mman::munmap(self.ptr, self.len);
+        }
+    }
+}
+
+// Safety: the mapping is read-only (`PROT_READ`) and never mutated through
+// this wrapper, so sharing `&MappedFile`/moving `MappedFile` across threads
+// carries the same guarantees as the `&[u8]` it derefs to.
+#[cfg(unix)]
+unsafe impl Send for MappedFile {}
+#[cfg(unix)]
+unsafe impl Sync for MappedFile {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::thread;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_get_caches_across_lookups() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index.html");
+        fs::write(&path, "hello").unwrap();
+
+        let cache = OpenFileCache::new(Duration::from_secs(60));
+        cache.get(&path).unwrap();
+        cache.get(&path).unwrap();
+
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_get_revalidates_after_ttl_expires() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index.html");
+        fs::write(&path, "hello").unwrap();
+
+        let cache = OpenFileCache::new(Duration::from_millis(10));
+        let (_, first) = cache.get(&path).unwrap();
+
+        thread::sleep(Duration::from_millis(20));
+        fs::write(&path, "goodbye, but longer").unwrap();
+        let (_, second) = cache.get(&path).unwrap();
+
+        assert_ne!(first.len(), second.len());
+    }
+
+    #[test]
+    fn test_invalidate_forces_a_fresh_open() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index.html");
+        fs::write(&path, "hello").unwrap();
+
+        let cache = OpenFileCache::new(Duration::from_secs(60));
+        cache.get(&path).unwrap();
+        cache.invalidate(&path);
+
+        fs::write(&path, "goodbye, but longer").unwrap();
+        let (_, metadata) = cache.get(&path).unwrap();
+
+        assert_eq!(metadata.len(), "goodbye, but longer".len() as u64);
+    }
+
+    #[test]
+    fn test_get_errors_on_a_missing_file() {
+        let dir = TempDir::new().unwrap();
+        let cache = OpenFileCache::new(Duration::from_secs(60));
+
+        assert!(cache.get(&dir.path().join("missing.html")).is_err());
+    }
+
+    #[test]
+    fn test_read_below_threshold_is_buffered() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("small.html");
+        fs::write(&path, "hello").unwrap();
+
+        let cache = OpenFileCache::new_with_mmap_threshold(Duration::from_secs(60), 1024);
+        let contents = cache.read(&path).unwrap();
+
+        assert!(matches!(contents, FileContents::Buffered(_)));
+        assert_eq!(&*contents, b"hello");
+    }
+
+    #[test]
+    fn test_read_at_or_above_threshold_is_mapped() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("big.bin");
+        fs::write(&path, vec![b'x'; 4096]).unwrap();
+
+        let cache = OpenFileCache::new_with_mmap_threshold(Duration::from_secs(60), 4096);
+        let contents = cache.read(&path).unwrap();
+
+        assert!(matches!(contents, FileContents::Mapped(_)));
+        assert_eq!(contents.len(), 4096);
+        assert!(contents.iter().all(|&b| b == b'x'));
+    }
+
+    #[test]
+    fn test_read_without_a_threshold_never_maps() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("big.bin");
+        fs::write(&path, vec![b'x'; 4096]).unwrap();
+
+        let cache = OpenFileCache::new(Duration::from_secs(60));
+        let contents = cache.read(&path).unwrap();
+
+        assert!(matches!(contents, FileContents::Buffered(_)));
+    }
+}
@@ -0,0 +1,221 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::pin::Pin;
+
+use anyhow::Result;
+use futures::Stream;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{service::Interceptor, transport::Server, Request, Response, Status};
+
+use crate::cli::{load_config, save_config};
+use crate::config::{ServerConfig, SiteConfig};
+use crate::status;
+
+pub mod proto {
+    tonic::include_proto!("mini");
+}
+
+use proto::mini_server::{Mini, MiniServer};
+use proto::{
+    GetShareRequest, LinkSiteRequest, ListSitesRequest, ListSitesResponse, SecureSiteRequest, ShareInfo, Site,
+    StatusReport as ProtoStatusReport, UnlinkSiteRequest, UnlinkSiteResponse, UnsecureSiteRequest,
+    WatchStatusRequest,
+};
+
+/// How often `WatchStatus` polls for a fresh status report to push.
+const WATCH_STATUS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+pub struct MiniControlPlane {
+    config_path: PathBuf,
+}
+
+impl MiniControlPlane {
+    pub fn new(config_path: PathBuf) -> Self {
+        Self { config_path }
+    }
+
+    fn load(&self) -> Result<ServerConfig, Status> {
+        load_config(&self.config_path).map_err(|e| Status::internal(e.to_string()))
+    }
+
+    fn save(&self, config: &ServerConfig) -> Result<(), Status> {
+        save_config(config, &self.config_path).map_err(|e| Status::internal(e.to_string()))
+    }
+}
+
+fn site_to_proto(domain: &str, site: &SiteConfig) -> Site {
+    Site {
+        domain: domain.to_string(),
+        root_dir: site.root_dir.clone(),
+        secure: site.secure,
+        php_version: site.php_version.clone().unwrap_or_default(),
+    }
+}
+
+#[tonic::async_trait]
+impl Mini for MiniControlPlane {
+    async fn list_sites(
+        &self,
+        _request: Request<ListSitesRequest>,
+    ) -> Result<Response<ListSitesResponse>, Status> {
+        let config = self.load()?;
+        let sites = config
+            .sites
+            .iter()
+            .map(|(domain, site)| site_to_proto(domain, site))
+            .collect();
+        Ok(Response::new(ListSitesResponse { sites }))
+    }
+
+    async fn link_site(&self, request: Request<LinkSiteRequest>) -> Result<Response<Site>, Status> {
+        let req = request.into_inner();
+        let mut config = self.load()?;
+        let site = SiteConfig {
+            root_dir: req.path,
+            domain: req.domain.clone(),
+            secure: false,
+            php_version: None,
+            env_vars: Default::default(),
+            driver: None,
+            notes: None,
+            tags: Vec::new(),
+            php_ini: Default::default(),
+            xdebug: false,
+            proxy_target: None,
+        };
+        config.add_site(req.domain.clone(), site.clone());
+        self.save(&config)?;
+        Ok(Response::new(site_to_proto(&req.domain, &site)))
+    }
+
+    async fn unlink_site(
+        &self,
+        request: Request<UnlinkSiteRequest>,
+    ) -> Result<Response<UnlinkSiteResponse>, Status> {
+        let domain = request.into_inner().domain;
+        let mut config = self.load()?;
+        config.remove_site(&domain);
+        self.save(&config)?;
+        Ok(Response::new(UnlinkSiteResponse {}))
+    }
+
+    async fn secure_site(
+        &self,
+        request: Request<SecureSiteRequest>,
+    ) -> Result<Response<Site>, Status> {
+        let domain = request.into_inner().domain;
+        let mut config = self.load()?;
+        let site = config
+            .sites
+            .get_mut(&domain)
+            .ok_or_else(|| Status::not_found(format!("{domain} is not linked")))?;
+        site.secure = true;
+        let proto_site = site_to_proto(&domain, site);
+        self.save(&config)?;
+        Ok(Response::new(proto_site))
+    }
+
+    async fn unsecure_site(
+        &self,
+        request: Request<UnsecureSiteRequest>,
+    ) -> Result<Response<Site>, Status> {
+        let domain = request.into_inner().domain;
+        let mut config = self.load()?;
+        let site = config
+            .sites
+            .get_mut(&domain)
+            .ok_or_else(|| Status::not_found(format!("{domain} is not linked")))?;
+        site.secure = false;
+        let proto_site = site_to_proto(&domain, site);
+        self.save(&config)?;
+        Ok(Response::new(proto_site))
+    }
+
+    async fn get_share(&self, request: Request<GetShareRequest>) -> Result<Response<ShareInfo>, Status> {
+        let domain = request.into_inner().domain;
+        let config = self.load()?;
+        let session = config
+            .shares
+            .get(&domain)
+            .ok_or_else(|| Status::not_found(format!("{domain} is not currently shared")))?;
+        Ok(Response::new(ShareInfo {
+            domain,
+            provider: session.provider.clone(),
+            url: session.url.clone(),
+            started_at: session.started_at,
+            expires_at: session.expires_at,
+        }))
+    }
+
+    type WatchStatusStream = Pin<Box<dyn Stream<Item = Result<ProtoStatusReport, Status>> + Send>>;
+
+    async fn watch_status(
+        &self,
+        _request: Request<WatchStatusRequest>,
+    ) -> Result<Response<Self::WatchStatusStream>, Status> {
+        let config_path = self.config_path.clone();
+        let (tx, rx) = mpsc::channel(4);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(WATCH_STATUS_INTERVAL);
+            loop {
+                interval.tick().await;
+                let Ok(config) = load_config(&config_path) else {
+                    break;
+                };
+                let report = status::gather_status(&config);
+                let proto = ProtoStatusReport {
+                    http_running: report.http.running,
+                    https_running: report.https.running,
+                    dns_running: report.dns.running,
+                    ca_trusted: report.ca_trusted,
+                    site_count: report.site_count as u32,
+                    parked_path_count: report.parked_path_count as u32,
+                    healthy: report.healthy,
+                };
+                if tx.send(Ok(proto)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+}
+
+/// Checks the same bearer token as the admin REST API (`admin::is_authorized`)
+/// before letting a request reach `MiniControlPlane` - without this, anything
+/// that can reach `grpc_listen_addr` could reconfigure sites with no auth at
+/// all, unlike the REST API it duplicates.
+#[derive(Clone)]
+struct AuthInterceptor {
+    token: String,
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let authorized = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            == Some(format!("Bearer {}", self.token).as_str());
+        if authorized {
+            Ok(request)
+        } else {
+            Err(Status::unauthenticated("unauthorized"))
+        }
+    }
+}
+
+/// Serve the gRPC control plane on `addr` until the process exits, rejecting
+/// any call that doesn't carry `token` as a bearer token.
+pub async fn serve(addr: SocketAddr, config_path: PathBuf, token: String) -> Result<()> {
+    let control_plane = MiniControlPlane::new(config_path);
+    let interceptor = AuthInterceptor { token };
+    Server::builder()
+        .add_service(MiniServer::with_interceptor(control_plane, interceptor))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
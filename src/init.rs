@@ -0,0 +1,105 @@
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use anyhow::Result;
+use is_terminal::IsTerminal;
+use tracing::info;
+
+use crate::config::ServerConfig;
+
+/// What the install wizard did or skipped, for a human- or JSON-formatted
+/// report.
+#[derive(Debug, Default, serde::Serialize)]
+pub struct InitReport {
+    pub config_dir_created: bool,
+    pub ca_generated: bool,
+    pub resolver_configured: bool,
+    pub service_installed: bool,
+    pub parked: Option<String>,
+}
+
+/// Guided first-run install: create the config directory, generate the
+/// local CA, wire up OS resolver entries, install the supervising service,
+/// and optionally park `~/Sites` — confirming each step with the operator
+/// unless `yes` is set (for scripted installs).
+///
+/// CA generation and resolver setup aren't implemented anywhere else in
+/// mini yet (see [`crate::status::StatusReport::ca_trusted`] and
+/// [`crate::tld::refresh_resolver_entries`]), so those two steps are
+/// recorded as skipped rather than faked.
+pub fn run(config: &mut ServerConfig, config_path: &Path, yes: bool) -> Result<InitReport> {
+    let mut report = InitReport::default();
+
+    if let Some(config_dir) = config_path.parent() {
+        std::fs::create_dir_all(config_dir)?;
+        report.config_dir_created = true;
+        println!("Created config directory {}", config_dir.display());
+    }
+
+    if confirm("Generate a local CA for trusted HTTPS on your sites?", yes) {
+        info!("CA generation isn't implemented yet; skipping");
+        println!("Skipped: CA generation is not implemented yet");
+    }
+
+    if confirm("Point the OS resolver at mini's DNS server?", yes) {
+        info!("resolver integration isn't implemented yet; skipping");
+        println!("Skipped: resolver setup is not implemented yet");
+    }
+
+    if cfg!(target_os = "linux") && confirm("Install and start the mini systemd service?", yes) {
+        crate::systemd::dispatch(crate::cli::ServiceAction::Install { user: true }, config)?;
+        report.service_installed = true;
+    }
+
+    if let Some(home) = std::env::var_os("HOME") {
+        let sites_dir = Path::new(&home).join("Sites");
+        if sites_dir.is_dir() && confirm("Park ~/Sites so every subdirectory becomes a site?", yes) {
+            let path = sites_dir.to_string_lossy().to_string();
+            config.add_parked_path(path.clone());
+            report.parked = Some(path);
+        }
+    }
+
+    println!("mini is ready. Run `mini start` to launch the daemon.");
+    Ok(report)
+}
+
+/// Ask `question` and return whether to proceed: always `true` under
+/// `--yes` or outside a TTY (so scripted/non-interactive installs don't
+/// hang waiting for input), otherwise a `y`/`n` prompt defaulting to yes.
+/// Shared with [`crate::driver::LaravelDriver::setup`], which gates
+/// `composer install` behind the same kind of consent.
+pub(crate) fn confirm(question: &str, yes: bool) -> bool {
+    if yes || !io::stdin().is_terminal() {
+        return true;
+    }
+
+    print!("{question} [Y/n] ");
+    let _ = io::stdout().flush();
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return true;
+    }
+    !matches!(answer.trim().to_lowercase().as_str(), "n" | "no")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_run_creates_the_config_directory_and_saves_config() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("nested").join("config.yaml");
+        let mut config = ServerConfig::default();
+
+        let report = run(&mut config, &config_path, true).unwrap();
+
+        assert!(report.config_dir_created);
+        assert!(!report.ca_generated);
+        assert!(!report.resolver_configured);
+        assert!(config_path.exists());
+    }
+}
@@ -0,0 +1,158 @@
+//! An embeddable handle onto a single mini instance.
+//!
+//! mini doesn't keep its site manager behind a `lazy_static` global today -
+//! `main.rs` already constructs one [`SiteManager`] explicitly and threads it
+//! through the daemon - but every `cli.rs` subcommand reloads config from
+//! disk and rebuilds its own throwaway [`DriverRegistry`]/[`SiteManager`] per
+//! invocation, which has the same effect for an embedder: there's no single
+//! long-lived object a host process can hold onto, reuse, or run twice with
+//! two different configs (e.g. from parallel integration tests). [`Mini`] is
+//! that object - built with [`Mini::builder`] rather than reached for as a
+//! global.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tokio::sync::broadcast;
+
+use crate::cli::load_config;
+use crate::config::ServerConfig;
+use crate::error::{MiniError, Result};
+use crate::events::MiniEvent;
+use crate::registry::DriverRegistry;
+use crate::site::SiteManager;
+
+/// How many unread [`MiniEvent`]s a lagging subscriber can fall behind by
+/// before it starts missing them - the same capacity `live_reload`'s watcher
+/// broadcast uses.
+const EVENT_CHANNEL_CAPACITY: usize = 16;
+
+/// A single mini instance: its config, driver registry, and site manager,
+/// all loaded from one config path rather than shared global state.
+pub struct Mini {
+    config_path: PathBuf,
+    config: ServerConfig,
+    registry: Arc<DriverRegistry>,
+    sites: Arc<SiteManager>,
+    events: broadcast::Sender<MiniEvent>,
+}
+
+impl Mini {
+    /// Start building a [`Mini`] handle.
+    pub fn builder() -> MiniBuilder {
+        MiniBuilder::default()
+    }
+
+    pub fn config(&self) -> &ServerConfig {
+        &self.config
+    }
+
+    pub fn config_path(&self) -> &PathBuf {
+        &self.config_path
+    }
+
+    /// The site manager for this instance - not yet consulted by the live
+    /// proxy path (see [`crate::MyProxy::upstream_peer`]'s doc comment), but
+    /// usable directly by an embedder the way `cli.rs` uses its own.
+    pub fn sites(&self) -> &Arc<SiteManager> {
+        &self.sites
+    }
+
+    pub fn registry(&self) -> &Arc<DriverRegistry> {
+        &self.registry
+    }
+
+    /// Subscribe to this instance's [`MiniEvent`]s, so a host (menu-bar app,
+    /// editor extension) can stay in sync without polling. Events published
+    /// before a subscriber calls this are missed, the same as any broadcast
+    /// channel - there's no history to replay.
+    pub fn subscribe(&self) -> broadcast::Receiver<MiniEvent> {
+        self.events.subscribe()
+    }
+
+    /// Publish `event` to every current subscriber. A no-op if nobody's
+    /// listening.
+    pub fn emit(&self, event: MiniEvent) {
+        let _ = self.events.send(event);
+    }
+}
+
+/// Builds a [`Mini`] handle from a config path.
+#[derive(Default)]
+pub struct MiniBuilder {
+    config_path: Option<PathBuf>,
+}
+
+impl MiniBuilder {
+    /// The `config.yaml` this instance loads from and saves to. Required.
+    pub fn config(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_path = Some(path.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Mini> {
+        let config_path = self
+            .config_path
+            .ok_or_else(|| MiniError::Config("Mini::builder() needs a config path".to_string()))?;
+        let config = load_config(&config_path).map_err(|e| MiniError::Config(e.to_string()))?;
+        let registry = Arc::new(DriverRegistry::with_known_drivers());
+        let sites = Arc::new(SiteManager::new(registry.clone()));
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Ok(Mini { config_path, config, registry, sites, events })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config_path(dir: &TempDir) -> PathBuf {
+        dir.path().join("config.yaml")
+    }
+
+    #[test]
+    fn test_build_without_a_config_path_is_a_config_error() {
+        let err = Mini::builder().build().unwrap_err();
+        assert!(matches!(err, MiniError::Config(_)));
+    }
+
+    #[test]
+    fn test_build_loads_defaults_when_no_config_file_exists_yet() {
+        let dir = TempDir::new().unwrap();
+        let mini = Mini::builder().config(config_path(&dir)).build().unwrap();
+        assert_eq!(mini.config_path(), &config_path(&dir));
+        assert_eq!(mini.config().sites.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_two_instances_with_different_configs_do_not_share_sites() {
+        let dir_a = TempDir::new().unwrap();
+        let dir_b = TempDir::new().unwrap();
+        let mini_a = Mini::builder().config(config_path(&dir_a)).build().unwrap();
+        let mini_b = Mini::builder().config(config_path(&dir_b)).build().unwrap();
+
+        mini_a.sites().add_site("a.test", PathBuf::from("/a")).await.unwrap();
+        assert!(mini_a.sites().get_site("a.test").await.is_some());
+        assert!(mini_b.sites().get_site("a.test").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_emitted_events() {
+        let dir = TempDir::new().unwrap();
+        let mini = Mini::builder().config(config_path(&dir)).build().unwrap();
+
+        let mut subscriber = mini.subscribe();
+        mini.emit(crate::events::MiniEvent::SiteLinked { domain: "app.test".to_string() });
+
+        let event = subscriber.recv().await.unwrap();
+        assert_eq!(event, crate::events::MiniEvent::SiteLinked { domain: "app.test".to_string() });
+    }
+
+    #[tokio::test]
+    async fn test_emit_without_any_subscribers_does_not_error() {
+        let dir = TempDir::new().unwrap();
+        let mini = Mini::builder().config(config_path(&dir)).build().unwrap();
+        mini.emit(crate::events::MiniEvent::ScanCompleted { added: 1, removed: 0 });
+    }
+}
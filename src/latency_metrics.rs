@@ -0,0 +1,155 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::Result;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use tracing::info;
+use prometheus_client::encoding::text::encode;
+use prometheus_client::registry::Registry;
+use prometools::histogram::TimeHistogram;
+
+/// Nothing in this module (or anywhere else in mini) registers a per-site
+/// label on a metric today - `LatencyHistograms` is one fixed histogram per
+/// listener, and the churn a label-per-site `Family` would create, `prometools`'
+/// `serde::Family` avoiding per-sample allocations for, doesn't exist yet to
+/// benchmark against. If per-site metrics get added later, whether that
+/// allocation path is worth building - and whether it belongs in
+/// `prometools` itself rather than here - is a question for that point, not
+/// this one.
+///
+/// Bucket layout (in seconds) used when a listener's config doesn't set its
+/// own - the same defaults the `prometheus` crate itself ships, since these
+/// histograms are timing the same requests it would otherwise bucket.
+pub fn default_buckets() -> Vec<f64> {
+    vec![0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]
+}
+
+/// A `Summary` metric (streaming quantiles over a sliding time window) would
+/// suit FastCGI latencies better than these histograms - that traffic can
+/// span 1ms to 60s, and no fixed bucket layout covers that range without
+/// either wasted resolution at one end or a bucket count that makes
+/// exposition expensive. `prometools` 0.2 has no such type, and adding one
+/// (a CKMS or t-digest estimator, wired into its `EncodeMetric` impl the
+/// same way `TimeHistogram` is) means changing `prometools` itself, which
+/// this crate depends on from crates.io rather than vendoring or patching.
+/// Until then, the per-listener `*_latency_buckets` config fields (see
+/// `ServerConfig`) are the available lever: widening them to cover the
+/// FastCGI tail is a config change, not a code one.
+///
+/// The lock-free `prometools` timing histograms [`MyProxy::logging`][logging]
+/// feeds every request's latency into: one per downstream listener, since an
+/// HTTP and an HTTPS listener can see very different traffic shapes and
+/// benefit from their own bucket layout, plus one shared histogram for time
+/// spent waiting on the upstream peer.
+///
+/// Exemplars (attaching a trace ID to the observation that tipped a bucket
+/// over, so a slow bucket in Grafana can jump straight to that trace) aren't
+/// wired up: `prometools` 0.2's `TimeHistogram::encode` hardcodes its
+/// `EncodeMetric` impl to report no exemplars, and the exemplar-carrying
+/// encode path it has internally isn't exposed on the public type. Doing
+/// this for real means forking or upstreaming a change to `prometools`
+/// itself; there's also no trace ID to attach yet, since this crate doesn't
+/// do distributed tracing (see the proxy's request-tracing backlog items).
+///
+/// These are `TimeHistogram`s with fixed, explicit bucket boundaries -
+/// Prometheus' exponential-bucket "native histogram" representation, which
+/// would give finer resolution without the `*_latency_buckets` config
+/// juggling above, isn't a layout `prometools` 0.2 can produce: there's no
+/// sparse-bucket type in its `histogram` module, and the protobuf exposition
+/// native histograms need is a different wire format entirely from the text
+/// `EncodeMetric` path `TimeHistogram` implements. Both would have to be
+/// added to `prometools` itself.
+///
+/// [logging]: crate::MyProxy::logging
+pub struct LatencyHistograms {
+    pub http: TimeHistogram,
+    pub https: TimeHistogram,
+    pub upstream: TimeHistogram,
+}
+
+impl LatencyHistograms {
+    pub fn new(http_buckets: &[f64], https_buckets: &[f64], upstream_buckets: &[f64]) -> Self {
+        LatencyHistograms {
+            http: TimeHistogram::new(http_buckets.iter().copied()),
+            https: TimeHistogram::new(https_buckets.iter().copied()),
+            upstream: TimeHistogram::new(upstream_buckets.iter().copied()),
+        }
+    }
+
+    fn registry(&self) -> Registry {
+        let mut registry = Registry::default();
+        registry.register(
+            "mini_http_request_duration_seconds",
+            "Latency of requests served on the plain HTTP listener",
+            Box::new(self.http.clone()),
+        );
+        registry.register(
+            "mini_https_request_duration_seconds",
+            "Latency of requests served on the TLS-terminated HTTPS listener",
+            Box::new(self.https.clone()),
+        );
+        registry.register(
+            "mini_upstream_duration_seconds",
+            "Time spent between connecting to the upstream peer and finishing the response",
+            Box::new(self.upstream.clone()),
+        );
+        registry
+    }
+}
+
+/// Always responds in OpenMetrics text format - `encode` (from
+/// `prometheus_client`, the crate `prometools`' metric types plug into via
+/// `EncodeMetric`) already writes the `# EOF` terminator and the
+/// `version=1.0.0` content-type this function sets, so there's no second
+/// format to content-negotiate between. What that encoder doesn't add -
+/// `_created` timestamps per series, or exemplar syntax (see
+/// [`LatencyHistograms`]'s doc comment for why there's nothing to attach as
+/// an exemplar yet anyway) - lives in `prometheus_client`'s own text encoder,
+/// a crates.io dependency with no source vendored or patched in this repo,
+/// so closing either gap isn't something this crate can do on its own.
+async fn handle(_req: Request<Body>, histograms: Arc<LatencyHistograms>) -> Result<Response<Body>> {
+    let mut buffer = Vec::new();
+    encode(&mut buffer, &histograms.registry())?;
+    Ok(Response::builder()
+        .header("content-type", "application/openmetrics-text; version=1.0.0; charset=utf-8")
+        .body(Body::from(buffer))?)
+}
+
+/// Serve `histograms` in OpenMetrics text format on `addr` until the process
+/// exits. A separate listener from the `prometheus` crate's own metrics
+/// endpoint (bound in `main.rs`) because the two crates' encoders and
+/// registries aren't compatible - `prometools`' histograms are built on
+/// `prometheus-client`, not the `prometheus` crate the rest of mini's
+/// metrics use.
+pub async fn serve(addr: SocketAddr, histograms: Arc<LatencyHistograms>) -> Result<()> {
+    let make_svc = make_service_fn(move |_conn| {
+        let histograms = histograms.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(req, histograms.clone()))) }
+    });
+
+    info!("Latency metrics listening on {addr}");
+    Server::bind(&addr).serve(make_svc).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_handle_encodes_observed_latencies_in_openmetrics_format() {
+        let histograms = Arc::new(LatencyHistograms::new(&default_buckets(), &default_buckets(), &default_buckets()));
+        histograms.http.observe(250_000_000);
+
+        let response = handle(Request::new(Body::empty()), histograms).await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+
+        assert!(text.contains("mini_http_request_duration_seconds"));
+        assert!(text.contains("mini_https_request_duration_seconds"));
+        assert!(text.contains("mini_upstream_duration_seconds"));
+        assert!(text.contains("# EOF"));
+    }
+}
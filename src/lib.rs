@@ -1,4 +1,71 @@
+//! mini is published as a single crate rather than split into `mini-core`
+//! (site/driver/registry/config) and `mini-server` (the pingora proxy,
+//! hickory DNS server, and CLI) workspace members. A GUI or tooling
+//! consumer that only wants `SiteManager`/`DriverRegistry` still has to
+//! compile pingora, hickory, and this crate's whole CLI surface to get at
+//! them - the `dns` and `ffi` Cargo features (see `Cargo.toml`) trim what
+//! gets *linked*, not what gets *compiled*, which is the complaint an
+//! actual crate split would address.
+//!
+//! That split isn't done here: `cli.rs`, `admin.rs`, and `main.rs`'s
+//! `MyProxy` all reach into `site`/`driver`/`config`/`registry` directly
+//! with no `pub(crate)` boundary between them today, so carving out a
+//! `mini-core` crate is a real API-design exercise (what's actually public
+//! across the boundary, whether `ServerConfig` belongs on one side or
+//! both) rather than a mechanical file move - too large a change to land
+//! as a drive-by alongside everything else in this module list.
+
+pub mod admin;
+pub mod cli;
+pub mod compose;
+pub mod config;
+pub mod container;
+pub mod daemon;
+#[cfg(feature = "dns")]
 pub mod dns;
 pub mod driver;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod error_feed;
+pub mod error_log;
+pub mod error_page;
+pub mod events;
+pub mod fastcgi;
+pub mod file_cache;
+pub mod grpc;
+pub mod init;
+pub mod instance;
+pub mod latency_metrics;
+pub mod live_reload;
+pub mod logs;
+pub mod loopback_alias;
+pub mod low_resource;
+pub mod mailbox;
+pub mod metrics_endpoint;
+pub mod network_access;
+pub mod open;
+pub mod parking;
+pub mod php;
+pub mod php_builtin;
+pub mod php_fpm;
+pub mod ports;
+pub mod privileges;
+pub mod rcd;
 pub mod registry;
+pub mod relay;
+pub mod request_log;
+pub mod request_tracing;
+pub mod runtime;
+pub mod secrets;
+pub mod self_update;
+pub mod services;
+pub mod share;
 pub mod site;
+pub mod socket_activation;
+pub mod status;
+pub mod sys;
+pub mod systemd;
+pub mod tld;
+pub mod uninstall;
+pub mod webhooks;
@@ -0,0 +1,256 @@
+//! Watches a static site's files for changes and pushes a reload event to
+//! every connected browser over a WebSocket, plus the HTML injection hook
+//! that gets a plain `index.html` talking to it without any build tooling
+//! of its own.
+//!
+//! Nothing in mini's live request path serves a parked site's files
+//! directly yet - see [`crate::file_cache`]'s doc comment for why - so
+//! [`serve`] and [`inject_reload_script`] are built and tested standalone,
+//! ready to sit in front of a real static-serving path once one exists.
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use hyper::header::{CONNECTION, UPGRADE};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::upgrade::Upgraded;
+use hyper::{Body, Request, Response, Server, StatusCode};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::{Message, Role};
+use tokio_tungstenite::WebSocketStream;
+use tracing::{info, warn};
+
+/// How often [`watch`] rescans a site's files for a changed fingerprint -
+/// the same poll-and-compare approach [`crate::logs::follow`] uses for
+/// `mini logs -f`, rather than pulling in a filesystem-event crate for a
+/// feature that only needs to notice "something under here changed".
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The HTTP path the reload client connects its WebSocket to.
+pub const RELOAD_PATH: &str = "/mini-live-reload";
+
+/// The id given to the `<script>` tag [`inject_reload_script`] inserts, so
+/// it can't collide with anything a site's own markup defines.
+const CLIENT_SCRIPT_ID: &str = "mini-live-reload";
+
+/// A cheap stand-in for hashing a whole directory tree: the number of files
+/// under `root` and the most recent modification time among them. Good
+/// enough to notice an edit, an added file, or a deleted one without
+/// reading any file's contents.
+fn fingerprint(root: &Path) -> (usize, SystemTime) {
+    let mut count = 0;
+    let mut latest = SystemTime::UNIX_EPOCH;
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            count += 1;
+            if let Ok(modified) = entry.metadata().and_then(|m| m.modified()) {
+                latest = latest.max(modified);
+            }
+        }
+    }
+
+    (count, latest)
+}
+
+/// Holds the broadcast side of a site's reload events - every connected
+/// browser's WebSocket task calls [`Self::subscribe`] to get its own
+/// receiver, so one file change reloads every open tab.
+pub struct ReloadWatcher {
+    reload: broadcast::Sender<()>,
+}
+
+impl ReloadWatcher {
+    pub fn subscribe(&self) -> broadcast::Receiver<()> {
+        self.reload.subscribe()
+    }
+}
+
+/// Poll `root` every [`POLL_INTERVAL`] and broadcast a reload event on every
+/// [`ReloadWatcher`] subscriber whenever its fingerprint changes. Runs for
+/// as long as the returned [`ReloadWatcher`] (or a clone of its sender)
+/// stays alive.
+pub fn watch(root: PathBuf) -> Arc<ReloadWatcher> {
+    let (reload, _) = broadcast::channel(16);
+    let watcher = Arc::new(ReloadWatcher { reload: reload.clone() });
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        let mut last = fingerprint(&root);
+        loop {
+            interval.tick().await;
+            let current = fingerprint(&root);
+            if current != last {
+                last = current;
+                // No subscribers yet (or all dropped) just means nobody's
+                // watching this reload right now, not a failure.
+                let _ = reload.send(());
+            }
+        }
+    });
+
+    watcher
+}
+
+/// Insert the live-reload client script just before `</body>`, or append it
+/// if `html` has none, so a browser viewing a static page reconnects to
+/// `websocket_url` and reloads on every message without the site needing
+/// any build tooling of its own.
+pub fn inject_reload_script(html: &str, websocket_url: &str) -> String {
+    let script = format!(
+        "<script id=\"{CLIENT_SCRIPT_ID}\">(function(){{\
+var ws=new WebSocket(\"{websocket_url}\");\
+ws.onmessage=function(){{location.reload();}};\
+ws.onclose=function(){{setTimeout(function(){{location.reload();}},1000);}};\
+}})();</script>"
+    );
+
+    match html.to_ascii_lowercase().rfind("</body>") {
+        Some(index) => {
+            let mut result = html.to_string();
+            result.insert_str(index, &script);
+            result
+        }
+        None => format!("{html}{script}"),
+    }
+}
+
+async fn push_reloads(mut stream: WebSocketStream<Upgraded>, watcher: Arc<ReloadWatcher>) -> Result<()> {
+    let mut reloads = watcher.subscribe();
+    loop {
+        reloads.recv().await?;
+        stream.send(Message::Text("reload".to_string())).await?;
+    }
+}
+
+/// Perform the WebSocket handshake on `req` and hand the upgraded
+/// connection off to a background task that pushes a message every time
+/// `watcher` sees a change, until the browser disconnects.
+fn upgrade(mut req: Request<Body>, watcher: Arc<ReloadWatcher>) -> Result<Response<Body>> {
+    let key = req
+        .headers()
+        .get("sec-websocket-key")
+        .context("missing Sec-WebSocket-Key header")?
+        .clone();
+    let accept = derive_accept_key(key.as_bytes());
+
+    tokio::spawn(async move {
+        match hyper::upgrade::on(&mut req).await {
+            Ok(upgraded) => {
+                let stream = WebSocketStream::from_raw_socket(upgraded, Role::Server, None).await;
+                if let Err(error) = push_reloads(stream, watcher).await {
+                    warn!("live-reload websocket closed: {error}");
+                }
+            }
+            Err(error) => warn!("live-reload upgrade failed: {error}"),
+        }
+    });
+
+    Ok(Response::builder()
+        .status(StatusCode::SWITCHING_PROTOCOLS)
+        .header(CONNECTION, "Upgrade")
+        .header(UPGRADE, "websocket")
+        .header("Sec-WebSocket-Accept", accept)
+        .body(Body::empty())?)
+}
+
+async fn handle(req: Request<Body>, watcher: Arc<ReloadWatcher>) -> Result<Response<Body>, Infallible> {
+    if req.uri().path() != RELOAD_PATH {
+        return Ok(Response::builder().status(StatusCode::NOT_FOUND).body(Body::from("not found")).unwrap());
+    }
+
+    Ok(upgrade(req, watcher).unwrap_or_else(|error| {
+        Response::builder().status(StatusCode::BAD_REQUEST).body(Body::from(error.to_string())).unwrap()
+    }))
+}
+
+/// Serve the live-reload WebSocket endpoint on `addr` until the process
+/// exits, pushing a reload message to every connected browser each time
+/// `watcher` sees a change.
+pub async fn serve(addr: SocketAddr, watcher: Arc<ReloadWatcher>) -> Result<()> {
+    let make_svc = make_service_fn(move |_conn| {
+        let watcher = watcher.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(req, watcher.clone()))) }
+    });
+
+    info!("Live-reload websocket listening on {addr}");
+    Server::bind(&addr).serve(make_svc).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration as StdDuration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_fingerprint_changes_when_a_file_is_edited() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index.html");
+        fs::write(&path, "hello").unwrap();
+        let before = fingerprint(dir.path());
+
+        std::thread::sleep(StdDuration::from_millis(10));
+        fs::write(&path, "goodbye").unwrap();
+
+        assert_ne!(before, fingerprint(dir.path()));
+    }
+
+    #[test]
+    fn test_fingerprint_changes_when_a_file_is_added() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("index.html"), "hello").unwrap();
+        let before = fingerprint(dir.path());
+
+        fs::write(dir.path().join("style.css"), "body {}").unwrap();
+
+        assert_ne!(before, fingerprint(dir.path()));
+    }
+
+    #[tokio::test]
+    async fn test_watch_broadcasts_on_change() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("index.html"), "hello").unwrap();
+
+        let watcher = watch(dir.path().to_path_buf());
+        let mut reloads = watcher.subscribe();
+
+        std::thread::sleep(StdDuration::from_millis(1100));
+        fs::write(dir.path().join("index.html"), "goodbye, but longer").unwrap();
+
+        tokio::time::timeout(StdDuration::from_secs(3), reloads.recv()).await.unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_inject_reload_script_lands_before_closing_body_tag() {
+        let html = "<html><body><h1>hi</h1></body></html>";
+        let injected = inject_reload_script(html, "ws://127.0.0.1:4000/mini-live-reload");
+
+        assert!(injected.contains(CLIENT_SCRIPT_ID));
+        assert!(injected.find(CLIENT_SCRIPT_ID).unwrap() < injected.find("</body>").unwrap());
+    }
+
+    #[test]
+    fn test_inject_reload_script_appends_without_a_body_tag() {
+        let html = "<h1>hi</h1>";
+        let injected = inject_reload_script(html, "ws://127.0.0.1:4000/mini-live-reload");
+
+        assert!(injected.starts_with(html));
+        assert!(injected.contains(CLIENT_SCRIPT_ID));
+    }
+}
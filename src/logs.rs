@@ -0,0 +1,150 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::config::ServerConfig;
+
+/// Known relative paths to a framework's own log file, checked in order.
+const FRAMEWORK_LOG_CANDIDATES: &[&str] = &["storage/logs/laravel.log"];
+
+/// How often a followed log is polled for new content.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+fn framework_log_path(site_root: &Path) -> Option<PathBuf> {
+    FRAMEWORK_LOG_CANDIDATES
+        .iter()
+        .map(|candidate| site_root.join(candidate))
+        .find(|path| path.exists())
+}
+
+/// A log file to read, and whether its lines need filtering down to
+/// `domain` (the proxy's shared error log) or are already scoped to a
+/// single site (a framework's own log file).
+struct LogSource {
+    path: PathBuf,
+    filter_by_domain: bool,
+}
+
+fn log_sources(config: &ServerConfig, domain: &str) -> Vec<LogSource> {
+    let mut sources = Vec::new();
+    if let Some(error_log) = &config.error_log {
+        sources.push(LogSource {
+            path: PathBuf::from(error_log),
+            filter_by_domain: true,
+        });
+    }
+    if let Some(site) = config.sites.get(domain) {
+        if let Some(path) = framework_log_path(Path::new(&site.root_dir)) {
+            sources.push(LogSource {
+                path,
+                filter_by_domain: false,
+            });
+        }
+    }
+    sources
+}
+
+fn print_matching_lines(reader: impl BufRead, domain: &str, filter_by_domain: bool) {
+    for line in reader.lines().flatten() {
+        if !filter_by_domain || line.contains(domain) {
+            println!("{line}");
+        }
+    }
+}
+
+/// Print every existing line in `source` relevant to `domain`, returning the
+/// byte offset reached so a follower can pick up from there.
+fn replay(source: &LogSource, domain: &str) -> u64 {
+    let Ok(mut file) = File::open(&source.path) else {
+        return 0;
+    };
+    print_matching_lines(BufReader::new(&file), domain, source.filter_by_domain);
+    file.seek(SeekFrom::End(0)).unwrap_or(0)
+}
+
+fn follow(sources: &[LogSource], domain: &str, mut offsets: Vec<u64>) {
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        for (source, offset) in sources.iter().zip(offsets.iter_mut()) {
+            let Ok(mut file) = File::open(&source.path) else {
+                continue;
+            };
+            let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+            if len < *offset {
+                // The log was rotated or truncated; start over.
+                *offset = 0;
+            }
+            if len == *offset {
+                continue;
+            }
+            if file.seek(SeekFrom::Start(*offset)).is_err() {
+                continue;
+            }
+            let mut contents = String::new();
+            if file.read_to_string(&mut contents).is_ok() {
+                print_matching_lines(contents.as_bytes(), domain, source.filter_by_domain);
+            }
+            *offset = len;
+        }
+    }
+}
+
+/// Tail the proxy's error log (filtered to `domain`) merged with the site's
+/// own framework log, if one is detectable. With `follow`, keeps printing
+/// new lines as they're appended, like `tail -f`.
+pub fn tail(config: &ServerConfig, domain: &str, follow_logs: bool) -> Result<()> {
+    let sources = log_sources(config, domain);
+    if sources.is_empty() {
+        anyhow::bail!("no log sources found for {domain}");
+    }
+
+    let offsets: Vec<u64> = sources.iter().map(|source| replay(source, domain)).collect();
+    if follow_logs {
+        follow(&sources, domain, offsets);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_framework_log_path_detects_laravel_log() {
+        let dir = TempDir::new().unwrap();
+        assert!(framework_log_path(dir.path()).is_none());
+
+        let logs_dir = dir.path().join("storage/logs");
+        fs::create_dir_all(&logs_dir).unwrap();
+        fs::write(logs_dir.join("laravel.log"), "").unwrap();
+
+        assert_eq!(
+            framework_log_path(dir.path()),
+            Some(logs_dir.join("laravel.log"))
+        );
+    }
+
+    #[test]
+    fn test_tail_errors_without_any_log_sources() {
+        let config = ServerConfig::default();
+        assert!(tail(&config, "missing.test", false).is_err());
+    }
+
+    #[test]
+    fn test_tail_succeeds_with_a_configured_error_log() {
+        let dir = TempDir::new().unwrap();
+        let log_path = dir.path().join("mini.log");
+        fs::write(&log_path, "myapp.test 200 GET /\nother.test 200 GET /\n").unwrap();
+
+        let mut config = ServerConfig::default();
+        config.error_log = Some(log_path.to_string_lossy().to_string());
+
+        assert!(tail(&config, "myapp.test", false).is_ok());
+    }
+}
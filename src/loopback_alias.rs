@@ -0,0 +1,82 @@
+//! macOS `lo0` loopback alias management for per-site IPs.
+//!
+//! Valet-style per-site addresses (each site getting its own `127.0.0.x`
+//! instead of sharing `127.0.0.1`) aren't something mini's config supports
+//! yet - there's no per-site IP field on [`crate::config::SiteConfig`], so
+//! nothing here is called from `site.rs` or the CLI in this commit. This
+//! module is the other half: once such a field exists, linking a site
+//! would call [`add_alias`] (and unlinking, [`remove_alias`]) so its IP is
+//! actually reachable without the user running `ifconfig` themselves, the
+//! way [`crate::privileges::drop_privileges`]'s doc comment describes
+//! doing with privileged listeners - while still root, before dropping to
+//! the configured unprivileged user.
+//!
+//! Only meaningful on macOS: `lo0` is this platform's loopback interface
+//! name (Linux's is `lo`, and aliasing it works differently - `ip addr
+//! add`, not `ifconfig alias`). Uses [`crate::sys::ProcessRunner`] rather
+//! than shelling out directly so the logic here is unit-testable against
+//! [`crate::sys::MockProcessRunner`] without actually touching network
+//! interfaces.
+
+use std::net::Ipv4Addr;
+use std::path::Path;
+
+use crate::sys::ProcessRunner;
+
+/// `ifconfig lo0 alias <ip> up` - adds `ip` as a loopback alias. Requires
+/// root, same as binding port 80/443/53 does.
+pub async fn add_alias(runner: &dyn ProcessRunner, ip: Ipv4Addr) -> std::io::Result<bool> {
+    let ip = ip.to_string();
+    let outcome = runner.run("ifconfig", &["lo0", "alias", &ip, "up"], Path::new("/")).await?;
+    Ok(outcome.success())
+}
+
+/// `ifconfig lo0 -alias <ip>` - removes a loopback alias [`add_alias`]
+/// added. Safe to call for an alias that's already gone - `ifconfig`
+/// itself treats that as a no-op success, not an error, so this doesn't
+/// have to track what it already removed.
+pub async fn remove_alias(runner: &dyn ProcessRunner, ip: Ipv4Addr) -> std::io::Result<bool> {
+    let ip = ip.to_string();
+    let outcome = runner.run("ifconfig", &["lo0", "-alias", &ip], Path::new("/")).await?;
+    Ok(outcome.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sys::MockProcessRunner;
+
+    #[tokio::test]
+    async fn test_add_alias_runs_ifconfig_with_the_expected_args() {
+        let runner = MockProcessRunner::new();
+        let added = add_alias(&runner, Ipv4Addr::new(127, 0, 0, 2)).await.unwrap();
+
+        assert!(added);
+        assert_eq!(
+            runner.calls(),
+            vec![(
+                "ifconfig".to_string(),
+                vec!["lo0".to_string(), "alias".to_string(), "127.0.0.2".to_string(), "up".to_string()]
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remove_alias_runs_ifconfig_with_the_expected_args() {
+        let runner = MockProcessRunner::new();
+        let removed = remove_alias(&runner, Ipv4Addr::new(127, 0, 0, 2)).await.unwrap();
+
+        assert!(removed);
+        assert_eq!(
+            runner.calls(),
+            vec![("ifconfig".to_string(), vec!["lo0".to_string(), "-alias".to_string(), "127.0.0.2".to_string()])]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_alias_reports_failure_from_a_failing_runner() {
+        let runner = MockProcessRunner::failing();
+        let added = add_alias(&runner, Ipv4Addr::new(127, 0, 0, 2)).await.unwrap();
+        assert!(!added);
+    }
+}
@@ -0,0 +1,70 @@
+//! A config profile for small machines (Raspberry Pi, a small VM) running
+//! mini as a tiny LAN dev server rather than a daily-driver proxy for a
+//! laptop full of sites.
+//!
+//! [`apply`] doesn't add any new settings - it just dials down the knobs
+//! `config.rs` already exposes the way someone resource-constrained would
+//! by hand: one tokio worker thread (`main.rs` builds a genuinely
+//! single-threaded runtime rather than a multi-thread one pinned to one
+//! worker, once `ServerConfig.threads` is `1` - see its call site in
+//! `main`), metrics/tracing off, and smaller in-memory buffers/connection
+//! limits than the defaults assume a beefier box has room for.
+
+use crate::config::ServerConfig;
+
+/// Apply the low-resource profile to `config` in place, the same way
+/// [`crate::tld::change`] mutates a loaded config before the caller saves
+/// it back.
+pub fn apply(config: &mut ServerConfig) {
+    config.threads = 1;
+    config.dns_threads = 1;
+    config.metrics_enabled = false;
+    config.tracing_enabled = false;
+    config.request_log_capacity = 50;
+    config.error_feed_capacity = 20;
+    config.upstream_pool.keepalive_pool_size = 16;
+    // Unlike the other knobs above, load shedding defaults to *off*
+    // (`max_in_flight_requests: 0`) - a small machine is exactly the case
+    // that safety valve is for, so this profile turns it on rather than
+    // just shrinking an already-enabled limit.
+    config.load_shedding.max_in_flight_requests = 32;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_selects_a_single_worker_thread() {
+        let mut config = ServerConfig::default();
+        apply(&mut config);
+
+        assert_eq!(config.threads, 1);
+        assert_eq!(config.dns_threads, 1);
+    }
+
+    #[test]
+    fn test_apply_disables_metrics_and_tracing() {
+        let mut config = ServerConfig::default();
+        apply(&mut config);
+
+        assert!(!config.metrics_enabled);
+        assert!(!config.tracing_enabled);
+    }
+
+    #[test]
+    fn test_apply_shrinks_buffers_and_enables_load_shedding() {
+        let mut config = ServerConfig::default();
+        let default_request_log_capacity = config.request_log_capacity;
+        let default_error_feed_capacity = config.error_feed_capacity;
+        let default_keepalive_pool_size = config.upstream_pool.keepalive_pool_size;
+        assert_eq!(config.load_shedding.max_in_flight_requests, 0);
+
+        apply(&mut config);
+
+        assert!(config.request_log_capacity < default_request_log_capacity);
+        assert!(config.error_feed_capacity < default_error_feed_capacity);
+        assert!(config.upstream_pool.keepalive_pool_size < default_keepalive_pool_size);
+        assert!(config.load_shedding.max_in_flight_requests > 0);
+    }
+}
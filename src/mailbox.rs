@@ -0,0 +1,322 @@
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::Serialize;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Assigns each [`CapturedMessage`] a process-lifetime-unique id, the same
+/// role [`crate::error_feed::NEXT_ERROR_ID`] plays for error feed entries.
+static NEXT_MESSAGE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// One message the mail catcher's SMTP listener accepted, never forwarded
+/// anywhere. `data` is the raw message exactly as `DATA` delivered it
+/// (headers and body together, dot-unstuffed) - good enough to read a
+/// message's contents without parsing MIME.
+#[derive(Debug, Clone, Serialize)]
+pub struct CapturedMessage {
+    pub id: u64,
+    pub received_at_unix: u64,
+    pub from: String,
+    pub to: Vec<String>,
+    pub subject: Option<String>,
+    pub data: String,
+}
+
+/// Pull a `Subject:` header's value out of a raw message, if it has one -
+/// just enough parsing to make the message list readable without pulling in
+/// a MIME crate for a feature that otherwise only cares about capturing raw
+/// bytes.
+fn parse_subject(data: &str) -> Option<String> {
+    for line in data.lines() {
+        if line.is_empty() {
+            // Blank line ends the headers; nothing after this is a header.
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Subject:").or_else(|| line.strip_prefix("subject:")) {
+            return Some(value.trim().to_string());
+        }
+    }
+    None
+}
+
+/// A fixed-capacity ring buffer of recently captured messages, the same
+/// eviction trade-off as [`crate::error_feed::ErrorFeed`] - this exists to
+/// answer "what did my app just try to send", not as a durable mailbox.
+pub struct MailStore {
+    capacity: usize,
+    messages: Mutex<VecDeque<CapturedMessage>>,
+}
+
+impl MailStore {
+    pub fn new(capacity: usize) -> Self {
+        MailStore {
+            capacity,
+            messages: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn record(&self, from: String, to: Vec<String>, data: String) -> u64 {
+        let message = CapturedMessage {
+            id: NEXT_MESSAGE_ID.fetch_add(1, Ordering::Relaxed),
+            received_at_unix: now_unix(),
+            from,
+            to,
+            subject: parse_subject(&data),
+            data,
+        };
+        let id = message.id;
+        let mut messages = self.messages.lock().unwrap();
+        if messages.len() >= self.capacity {
+            messages.pop_front();
+        }
+        messages.push_back(message);
+        id
+    }
+
+    /// The most recently captured messages, newest first, capped at `limit`
+    /// (or everything held, if `limit` is `None` or larger than that).
+    pub fn recent(&self, limit: Option<usize>) -> Vec<CapturedMessage> {
+        let messages = self.messages.lock().unwrap();
+        let limit = limit.unwrap_or(messages.len()).min(messages.len());
+        messages.iter().rev().take(limit).cloned().collect()
+    }
+
+    pub fn get(&self, id: u64) -> Option<CapturedMessage> {
+        self.messages.lock().unwrap().iter().find(|m| m.id == id).cloned()
+    }
+}
+
+/// Speak just enough SMTP to accept a message and throw it straight into
+/// `store` - `HELO`/`EHLO`, `MAIL FROM`, `RCPT TO`, `DATA`, `RSET`, `NOOP` and
+/// `QUIT` are handled; anything else gets a generic `502` so a well-behaved
+/// client falls back to a command this understands. There's no relaying,
+/// no auth and no TLS - the whole point is that nothing captured here ever
+/// leaves the machine.
+async fn handle_smtp_connection(stream: tokio::net::TcpStream, store: Arc<MailStore>) -> Result<()> {
+    let (read_half, mut write_half) = stream.into_split();
+    let mut lines = BufReader::new(read_half).lines();
+
+    write_half.write_all(b"220 mini mail catcher ready\r\n").await?;
+
+    let mut from = String::new();
+    let mut to = Vec::new();
+
+    while let Some(line) = lines.next_line().await? {
+        let line = line.trim_end();
+        let upper = line.to_ascii_uppercase();
+
+        if upper.starts_with("HELO") || upper.starts_with("EHLO") {
+            write_half.write_all(b"250 mini\r\n").await?;
+        } else if upper.starts_with("MAIL FROM:") {
+            from = line["MAIL FROM:".len()..].trim().to_string();
+            to.clear();
+            write_half.write_all(b"250 OK\r\n").await?;
+        } else if upper.starts_with("RCPT TO:") {
+            to.push(line["RCPT TO:".len()..].trim().to_string());
+            write_half.write_all(b"250 OK\r\n").await?;
+        } else if upper.starts_with("DATA") {
+            write_half.write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n").await?;
+            let mut data = String::new();
+            while let Some(data_line) = lines.next_line().await? {
+                if data_line == "." {
+                    break;
+                }
+                // Dot-unstuffing: a line that starts with an extra "." (added
+                // by the sender so a genuine "." line can't be mistaken for
+                // the terminator) loses that one leading dot here.
+                let data_line = data_line.strip_prefix('.').unwrap_or(&data_line);
+                data.push_str(data_line);
+                data.push('\n');
+            }
+            let id = store.record(from.clone(), to.clone(), data);
+            info!("mail catcher captured message {id} from {from} to {to:?}");
+            write_half.write_all(b"250 OK: queued\r\n").await?;
+        } else if upper.starts_with("RSET") {
+            from.clear();
+            to.clear();
+            write_half.write_all(b"250 OK\r\n").await?;
+        } else if upper.starts_with("NOOP") {
+            write_half.write_all(b"250 OK\r\n").await?;
+        } else if upper.starts_with("QUIT") {
+            write_half.write_all(b"221 Bye\r\n").await?;
+            break;
+        } else {
+            write_half.write_all(b"502 Command not implemented\r\n").await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Accept SMTP connections on `addr` forever, handing each one its own task
+/// so a slow or misbehaving client can't stall the others.
+pub async fn run_smtp_server(addr: SocketAddr, store: Arc<MailStore>) -> Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!("Mail catcher SMTP listener on {addr}");
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let store = store.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_smtp_connection(stream, store).await {
+                warn!("mail catcher connection from {peer} error: {e}");
+            }
+        });
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn render_index(messages: &[CapturedMessage]) -> String {
+    let mut rows = String::new();
+    for message in messages {
+        rows.push_str(&format!(
+            "<tr><td><a href=\"/messages/{id}\">{subject}</a></td><td>{from}</td><td>{to}</td><td>{id}</td></tr>\n",
+            id = message.id,
+            subject = escape_html(message.subject.as_deref().unwrap_or("(no subject)")),
+            from = escape_html(&message.from),
+            to = escape_html(&message.to.join(", ")),
+        ));
+    }
+    format!(
+        "<!DOCTYPE html><html><head><title>mini mail catcher</title></head><body>\
+         <h1>mini mail catcher</h1>\
+         <table border=\"1\" cellpadding=\"4\"><tr><th>Subject</th><th>From</th><th>To</th><th>Id</th></tr>\n{rows}</table>\
+         </body></html>"
+    )
+}
+
+fn render_message(message: &CapturedMessage) -> String {
+    format!(
+        "<!DOCTYPE html><html><head><title>{subject}</title></head><body>\
+         <p><a href=\"/\">&larr; back</a></p>\
+         <h1>{subject}</h1>\
+         <p>From: {from}<br>To: {to}</p>\
+         <pre>{data}</pre>\
+         </body></html>",
+        subject = escape_html(message.subject.as_deref().unwrap_or("(no subject)")),
+        from = escape_html(&message.from),
+        to = escape_html(&message.to.join(", ")),
+        data = escape_html(&message.data),
+    )
+}
+
+fn html_response(status: StatusCode, body: String) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "text/html; charset=utf-8")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+fn json_response<T: Serialize>(status: StatusCode, value: &T) -> Response<Body> {
+    let body = serde_json::to_string(value).unwrap_or_else(|_| "[]".to_string());
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+/// Pull the numeric id out of a `/messages/{id}` or `/api/messages/{id}`
+/// path, if it parses.
+fn message_id(path: &str) -> Option<u64> {
+    path.rsplit('/').next()?.parse().ok()
+}
+
+async fn handle(req: Request<Body>, store: Arc<MailStore>) -> Result<Response<Body>, std::convert::Infallible> {
+    let response = match (req.method(), req.uri().path()) {
+        (&Method::GET, "/") => html_response(StatusCode::OK, render_index(&store.recent(None))),
+        (&Method::GET, "/api/messages") => json_response(StatusCode::OK, &store.recent(None)),
+        (&Method::GET, path) if path.starts_with("/messages/") => match message_id(path).and_then(|id| store.get(id)) {
+            Some(message) => html_response(StatusCode::OK, render_message(&message)),
+            None => html_response(StatusCode::NOT_FOUND, "message not found".to_string()),
+        },
+        (&Method::GET, path) if path.starts_with("/api/messages/") => match message_id(path).and_then(|id| store.get(id)) {
+            Some(message) => json_response(StatusCode::OK, &message),
+            None => json_response(StatusCode::NOT_FOUND, &serde_json::json!({ "error": "message not found" })),
+        },
+        _ => html_response(StatusCode::NOT_FOUND, "not found".to_string()),
+    };
+    Ok(response)
+}
+
+/// Serve the mail catcher's web UI on `addr` until the process exits.
+pub async fn serve(addr: SocketAddr, store: Arc<MailStore>) -> Result<()> {
+    let make_svc = make_service_fn(move |_conn| {
+        let store = store.clone();
+        async move { Ok::<_, std::convert::Infallible>(service_fn(move |req| handle(req, store.clone()))) }
+    });
+
+    info!("Mail catcher web UI listening on {addr}");
+    Server::bind(&addr).serve(make_svc).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_assigns_increasing_ids_and_parses_subject() {
+        let store = MailStore::new(10);
+        let id = store.record(
+            "a@example.test".to_string(),
+            vec!["b@example.test".to_string()],
+            "Subject: hello\r\n\r\nbody\n".to_string(),
+        );
+        let message = store.get(id).unwrap();
+        assert_eq!(message.subject, Some("hello".to_string()));
+        assert_eq!(message.from, "a@example.test");
+        assert_eq!(message.to, vec!["b@example.test".to_string()]);
+    }
+
+    #[test]
+    fn test_recent_returns_newest_first_and_honors_limit() {
+        let store = MailStore::new(10);
+        store.record("a@example.test".to_string(), vec![], "Subject: one\r\n\r\n".to_string());
+        store.record("a@example.test".to_string(), vec![], "Subject: two\r\n\r\n".to_string());
+        store.record("a@example.test".to_string(), vec![], "Subject: three\r\n\r\n".to_string());
+
+        let recent = store.recent(Some(2));
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].subject, Some("three".to_string()));
+        assert_eq!(recent[1].subject, Some("two".to_string()));
+    }
+
+    #[test]
+    fn test_record_evicts_oldest_past_capacity() {
+        let store = MailStore::new(2);
+        let first = store.record("a@example.test".to_string(), vec![], "Subject: one\r\n\r\n".to_string());
+        store.record("a@example.test".to_string(), vec![], "Subject: two\r\n\r\n".to_string());
+        store.record("a@example.test".to_string(), vec![], "Subject: three\r\n\r\n".to_string());
+
+        assert!(store.get(first).is_none());
+        assert_eq!(store.recent(None).len(), 2);
+    }
+
+    #[test]
+    fn test_parse_subject_returns_none_without_a_subject_header() {
+        assert_eq!(parse_subject("From: a@example.test\r\n\r\nbody"), None);
+    }
+}
@@ -1,26 +1,83 @@
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::future::Future;
 use std::pin::Pin;
-use std::path::PathBuf;
-use log::{debug, error, info, warn};
+use std::net::SocketAddr;
+use cf_rustracing::tag::Tag;
+use cf_rustracing_jaeger::{Span, Tracer};
+use clap::Parser;
+use tracing::{debug, error, info, warn};
 use prometheus::{register_int_counter, register_int_gauge};
 use pingora_core::server::configuration::Opt;
 use pingora_core::server::Server;
 use pingora_core::listeners::tls::TlsSettings;
 use pingora_core::upstreams::peer::{HttpPeer, Peer};
-use pingora_error::{Error, ErrorType, Result};
+use pingora_error::{Error, ErrorType, Result as PingoraResult};
 use pingora_http::{RequestHeader, ResponseHeader};
 use pingora_proxy::{ProxyHttp, Session};
+#[cfg(feature = "dns")]
 use tokio::net::UdpSocket;
+use tokio::signal::unix::{signal, SignalKind};
 use async_trait::async_trait;
 
+mod admin;
+mod cli;
+mod compose;
+mod config;
+mod container;
+mod daemon;
 mod driver;
+mod error;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod error_feed;
+mod error_log;
+mod error_page;
+mod events;
+mod fastcgi;
+mod file_cache;
+mod grpc;
+mod init;
+mod instance;
+mod latency_metrics;
+mod live_reload;
+mod logs;
+mod loopback_alias;
+mod low_resource;
+mod mailbox;
+mod metrics_endpoint;
+mod network_access;
+mod open;
+mod parking;
+mod php;
+mod php_builtin;
+mod php_fpm;
+mod ports;
+mod privileges;
+mod rcd;
 mod registry;
+mod relay;
+mod request_log;
+mod request_tracing;
+mod runtime;
+mod secrets;
+mod self_update;
+mod services;
+mod share;
 mod site;
+mod socket_activation;
+mod status;
+mod sys;
+mod systemd;
+mod tld;
+mod uninstall;
+mod webhooks;
+#[cfg(feature = "dns")]
 mod dns;
 
-use crate::driver::LaravelDriver;
+use crate::cli::{Cli, Command};
+use crate::config::{LoadSheddingConfig, UpstreamPoolConfig};
 use crate::registry::DriverRegistry;
 use crate::site::SiteManager;
 
@@ -28,38 +85,261 @@ use crate::site::SiteManager;
 pub struct MyProxy {
     req_metric: prometheus::IntCounter,
     active_connections: prometheus::IntGauge,
+    /// Connections [`ProxyHttp::connected_to_upstream`] got to reuse from
+    /// pingora's keepalive pool vs. had to open fresh - the ratio is what
+    /// tells you whether `upstream_pool.proxied_ports.idle_timeout_secs` is
+    /// actually sized for your traffic.
+    upstream_reused_connections: prometheus::IntCounter,
+    upstream_new_connections: prometheus::IntCounter,
+    latency_histograms: Arc<latency_metrics::LatencyHistograms>,
+    /// `None` when `tracing_enabled` is unset in config, in which case every
+    /// span created off of it below is inert (see [`Span::is_sampled`]) and
+    /// costs nothing but a channel send nobody reads from.
+    tracer: Option<Tracer>,
+    upstream_pool: UpstreamPoolConfig,
+    request_log: Arc<request_log::RequestLog>,
+    capture_headers: bool,
+    error_feed: Arc<error_feed::ErrorFeed>,
+    /// Requests currently between [`ProxyHttp::request_filter`] and
+    /// [`ProxyHttp::logging`], checked against `load_shedding` on every new
+    /// request so a burst can be turned away with a `503` instead of queuing
+    /// up behind whatever's already in flight.
+    in_flight_requests: Arc<AtomicUsize>,
+    load_shedding: LoadSheddingConfig,
+    shed_requests: prometheus::IntCounter,
+    /// `None` unless `network_access` is enabled, in which case every
+    /// non-loopback request is checked against it before being proxied -
+    /// see [`MyProxy::check_network_access`].
+    network_access: Option<Arc<network_access::NetworkAccessGuard>>,
+}
+
+/// Per-request timing and tracing state.
+///
+/// `start`/`upstream_connected` feed [`MyProxy::observe_latency`]'s
+/// `prometools` histograms; `span`/`upstream_span` are this request's
+/// distributed-tracing spans (`None` whenever tracing is disabled).
+#[derive(Default)]
+pub struct RequestTiming {
+    start: Option<Instant>,
+    upstream_connected: Option<Instant>,
+    span: Option<Span>,
+    upstream_span: Option<Span>,
+    /// The incoming request's raw `tracestate` header, if any - carried
+    /// alongside `span` since `cf-rustracing-jaeger`'s `SpanContext` has
+    /// nowhere to hold it (see `request_tracing::extract_tracestate`).
+    tracestate: Option<String>,
+}
+
+impl MyProxy {
+    /// Record this request's total latency into the histogram for whichever
+    /// listener served it (TLS-terminated sessions carry an [`SslDigest`][ssl]
+    /// on their [`Digest`][digest]), plus its upstream-wait latency if we
+    /// ever connected to an upstream peer for it.
+    ///
+    /// [ssl]: pingora_core::protocols::tls::digest::SslDigest
+    /// [digest]: pingora_core::protocols::Digest
+    fn observe_latency(&self, session: &Session, ctx: &RequestTiming) {
+        let is_tls = session
+            .digest()
+            .map_or(false, |digest| digest.ssl_digest.is_some());
+
+        if let Some(start) = ctx.start {
+            let histogram = if is_tls { &self.latency_histograms.https } else { &self.latency_histograms.http };
+            histogram.observe(start.elapsed().as_nanos() as u64);
+        }
+        if let Some(connected) = ctx.upstream_connected {
+            self.latency_histograms.upstream.observe(connected.elapsed().as_nanos() as u64);
+        }
+    }
+
+    /// Append this request to [`Self::request_log`], the admin API's
+    /// `/api/requests/recent` feed. `matched_site` is always `None` today -
+    /// see its doc comment on [`request_log::RequestLogEntry`].
+    fn record_request_log(&self, session: &Session, status: u16, duration_ms: Option<u64>) {
+        let req_header = session.req_header();
+        let host = req_header
+            .headers
+            .get("host")
+            .and_then(|h| h.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+
+        let (request_headers, response_headers) = if self.capture_headers {
+            let request_headers = req_header
+                .headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_str().unwrap_or("").to_string()))
+                .collect();
+            let response_headers = session
+                .response_written()
+                .map(|resp| {
+                    resp.headers
+                        .iter()
+                        .map(|(name, value)| (name.to_string(), value.to_str().unwrap_or("").to_string()))
+                        .collect()
+                })
+                .unwrap_or_default();
+            (Some(request_headers), Some(response_headers))
+        } else {
+            (None, None)
+        };
+
+        self.request_log.record(request_log::RequestLogEntry {
+            timestamp_unix: 0,
+            method: req_header.method.to_string(),
+            host,
+            path: req_header.uri.path().to_string(),
+            status,
+            duration_ms,
+            matched_site: None,
+            request_headers,
+            response_headers,
+        });
+    }
+
+    /// Append this request to [`Self::error_feed`] if it's worth surfacing
+    /// on the admin API's `/api/errors/recent` feed - either pingora reported
+    /// an error proxying it, or the response it did get back was a 5xx.
+    fn record_error_if_any(&self, session: &Session, status: u16, error: Option<&pingora_core::Error>) {
+        if error.is_none() && status < 500 {
+            return;
+        }
+
+        let req_header = session.req_header();
+        let host = req_header
+            .headers
+            .get("host")
+            .and_then(|h| h.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+
+        self.error_feed.record(
+            host,
+            req_header.uri.path().to_string(),
+            status,
+            error.map(|e| e.to_string()),
+        );
+    }
+
+    /// Gate a request against `network_access`, if enabled: an already
+    /// authorized device (or anything without a peer address we can check,
+    /// e.g. a unix socket) passes through untouched; an unauthorized device
+    /// presenting the current one-time token in `?mini_token=` gets
+    /// authorized and redirected to the same URL without it; anyone else
+    /// gets a 403 pointing at the logged access link. Returns whether a
+    /// response was already written (in which case `request_filter` should
+    /// stop processing this request).
+    async fn check_network_access(&self, session: &mut Session) -> PingoraResult<bool> {
+        let Some(guard) = &self.network_access else { return Ok(false) };
+        let Some(addr) = session.client_addr().map(|addr| addr.ip().to_canonical()) else { return Ok(false) };
+        if guard.is_authorized(&addr) {
+            return Ok(false);
+        }
+
+        let candidate = session
+            .req_header()
+            .uri
+            .query()
+            .and_then(|query| query.split('&').find_map(|pair| pair.strip_prefix("mini_token=")));
+
+        if let Some(candidate) = candidate {
+            if guard.authorize(addr, candidate) {
+                let path = session.req_header().uri.path().to_string();
+                let mut resp = ResponseHeader::build(302, None)?;
+                resp.insert_header("Location", path).map_err(|_| Error::new(ErrorType::InvalidHTTPHeader))?;
+                resp.insert_header("Content-Length", "0").map_err(|_| Error::new(ErrorType::InvalidHTTPHeader))?;
+                session.write_response_header(Box::new(resp), true).await?;
+                return Ok(true);
+            }
+        }
+
+        let mut resp = ResponseHeader::build(403, None)?;
+        resp.insert_header("Content-Length", "0").map_err(|_| Error::new(ErrorType::InvalidHTTPHeader))?;
+        session.write_response_header(Box::new(resp), true).await?;
+        Ok(true)
+    }
 }
 
 #[async_trait]
 impl ProxyHttp for MyProxy {
-    type CTX = ();
+    type CTX = RequestTiming;
 
-    fn new_ctx(&self) -> Self::CTX {}
+    fn new_ctx(&self) -> Self::CTX {
+        RequestTiming { start: Some(Instant::now()), upstream_connected: None }
+    }
+
+    /// Shed load before it reaches an upstream, once more requests are
+    /// in flight than `load_shedding.max_in_flight_requests` allows. Shedding
+    /// happens here rather than after an attempted connection so a runaway
+    /// caller gets a fast, explicit `503` instead of queuing behind
+    /// everything already being served.
+    async fn request_filter(&self, session: &mut Session, _ctx: &mut Self::CTX) -> PingoraResult<bool> {
+        let in_flight = self.in_flight_requests.fetch_add(1, AtomicOrdering::Relaxed) + 1;
+        let max = self.load_shedding.max_in_flight_requests;
+        if max != 0 && in_flight > max {
+            self.shed_requests.inc();
+            let mut resp = ResponseHeader::build(503, None)?;
+            resp.insert_header("Retry-After", self.load_shedding.retry_after_secs.to_string())
+                .map_err(|_| Error::new(ErrorType::InvalidHTTPHeader))?;
+            resp.insert_header("Content-Length", "0")
+                .map_err(|_| Error::new(ErrorType::InvalidHTTPHeader))?;
+            session.write_response_header(Box::new(resp), true).await?;
+            return Ok(true);
+        }
+
+        self.check_network_access(session).await
+    }
 
     async fn upstream_peer(
         &self,
         session: &mut Session,
-        _ctx: &mut Self::CTX,
-    ) -> Result<Box<HttpPeer>> {
+        ctx: &mut Self::CTX,
+    ) -> PingoraResult<Box<HttpPeer>> {
         // Get host from request header
         let host = session
             .req_header()
             .headers
             .get("host")
             .and_then(|h| h.to_str().ok())
-            .unwrap_or("one.one.one.one");
+            .unwrap_or("one.one.one.one")
+            .to_string();
+
+        if let Some(tracer) = &self.tracer {
+            // Honor a trace context the caller (another `mini` instance, or
+            // whatever reverse proxy sits in front of this one) already
+            // started, rather than always beginning a fresh trace here.
+            let incoming = request_tracing::extract_context(&session.req_header().headers);
+            ctx.tracestate = request_tracing::extract_tracestate(&session.req_header().headers);
+            let mut span_opts = tracer.span("request");
+            if let Some(context) = &incoming {
+                span_opts = span_opts.child_of(context);
+            }
+            let mut root = span_opts.start();
+            root.set_tag(|| Tag::new("http.host", host.clone()));
+            // Site lookup is currently just reading the Host header above;
+            // once the proxy actually consults `SiteManager` to route by
+            // host this child span will cover that real work instead of
+            // bracketing something instantaneous.
+            drop(root.child("resolve_host", |o| o.start()));
+            ctx.span = Some(root);
+        }
 
         // Default to 1.1.1.1 as upstream
         let mut peer = Box::new(HttpPeer::new(
             ("1.1.1.1", 443),
             true,
-            host.to_string(),
+            host,
         ));
 
         // Configure timeouts
         peer.options.connection_timeout = Some(Duration::from_secs(10));
         peer.options.read_timeout = Some(Duration::from_secs(30));
         peer.options.write_timeout = Some(Duration::from_secs(30));
+        // This is the only upstream class `upstream_peer` can actually reach
+        // today (see `UpstreamPoolConfig`'s doc comment), so `proxied_ports`
+        // is the one applied here regardless of what's actually being proxied.
+        peer.options.idle_timeout =
+            Some(Duration::from_secs(self.upstream_pool.proxied_ports.idle_timeout_secs));
 
         Ok(peer)
     }
@@ -68,12 +348,25 @@ impl ProxyHttp for MyProxy {
         &self,
         _session: &mut Session,
         upstream_request: &mut RequestHeader,
-        _ctx: &mut Self::CTX,
-    ) -> Result<()> {
+        ctx: &mut Self::CTX,
+    ) -> PingoraResult<()> {
         // Add any custom headers
         upstream_request
             .insert_header("X-Forwarded-By", "MyProxy")
             .map_err(|_| Error::new(ErrorType::InvalidHTTPHeader))?;
+
+        // Carry this request's trace context to the upstream, so the trace
+        // continues into whatever's serving it (see `request_tracing` for
+        // why both a `traceparent` and an `uber-trace-id` header are sent).
+        if let Some(context) = ctx.span.as_ref().and_then(|span| span.context()) {
+            let headers = request_tracing::inject_headers(context, ctx.tracestate.as_deref())
+                .map_err(|_| Error::new(ErrorType::InvalidHTTPHeader))?;
+            for (name, value) in headers {
+                upstream_request
+                    .insert_header(name, value)
+                    .map_err(|_| Error::new(ErrorType::InvalidHTTPHeader))?;
+            }
+        }
         Ok(())
     }
 
@@ -82,7 +375,7 @@ impl ProxyHttp for MyProxy {
         _session: &mut Session,
         upstream_response: &mut ResponseHeader,
         _ctx: &mut Self::CTX,
-    ) -> Result<()> {
+    ) -> PingoraResult<()> {
         // Add custom response headers
         upstream_response
             .insert_header("Server", "MyProxy")
@@ -94,28 +387,45 @@ impl ProxyHttp for MyProxy {
         &self,
         session: &mut Session,
         error: Option<&pingora_core::Error>,
-        _ctx: &mut Self::CTX,
+        ctx: &mut Self::CTX,
     ) {
         let response_code = session
             .response_written()
             .map_or(0, |resp| resp.status.as_u16());
+        let duration_ms = ctx.start.map(|start| start.elapsed().as_millis() as u64);
 
         if let Some(e) = error {
             error!(
-                "Request failed: {} response_code: {} error: {}",
-                self.request_summary(session, _ctx),
-                response_code,
-                e
+                status = response_code,
+                duration_ms,
+                error = %e,
+                "Request failed: {}",
+                self.request_summary(session, ctx)
             );
         } else {
             info!(
-                "{} response_code: {}",
-                self.request_summary(session, _ctx),
-                response_code
+                status = response_code,
+                duration_ms,
+                "{}",
+                self.request_summary(session, ctx)
             );
         }
 
         self.req_metric.inc();
+        self.observe_latency(session, ctx);
+        self.record_request_log(session, response_code, duration_ms);
+        self.record_error_if_any(session, response_code, error);
+        self.in_flight_requests.fetch_sub(1, AtomicOrdering::Relaxed);
+
+        // Drop order matters: the upstream child span must finish (and be
+        // sent to the reporter) before its parent, so finish it first.
+        if let Some(upstream_span) = ctx.upstream_span.take() {
+            drop(upstream_span);
+        }
+        if let Some(mut span) = ctx.span.take() {
+            span.set_tag(|| Tag::new("http.status_code", response_code as i64));
+            drop(span);
+        }
     }
 
     async fn connected_to_upstream(
@@ -126,85 +436,419 @@ impl ProxyHttp for MyProxy {
         #[cfg(unix)] _fd: std::os::unix::io::RawFd,
         #[cfg(windows)] _sock: std::os::windows::io::RawSocket,
         _digest: Option<&pingora_core::protocols::Digest>,
-        _ctx: &mut Self::CTX,
-    ) -> Result<()> {
+        ctx: &mut Self::CTX,
+    ) -> PingoraResult<()> {
+        ctx.upstream_connected = Some(Instant::now());
+        if let Some(root) = &ctx.span {
+            // Covers time spent from here until `logging` runs, i.e.
+            // waiting on/streaming from the upstream peer - the closest
+            // analogue this proxy has today to "upstream/FastCGI time",
+            // since it doesn't yet speak FastCGI to php-fpm itself (see
+            // `fastcgi`/`php_fpm`, which aren't wired into `MyProxy` yet).
+            ctx.upstream_span = Some(root.child("upstream", |o| o.start()));
+        }
         debug!(
             "Connected to upstream {} (reused: {})",
             peer.address().to_string(),
             reused
         );
+        if reused {
+            self.upstream_reused_connections.inc();
+        } else {
+            self.upstream_new_connections.inc();
+        }
         self.active_connections.inc();
         Ok(())
     }
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize logging
-    env_logger::init();
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
+    // `mini start` either runs the server right here (when it was re-exec'd
+    // with `--foreground`, or when `config.daemon` is unset so there's no
+    // detaching to do) or hands off to `daemon::start`, which forks/detaches
+    // and re-execs into this same branch. Every other command always goes
+    // through `dispatch`.
+    let admin_config_path = cli::default_config_path();
+    let mut startup_config = cli::load_config(&admin_config_path).unwrap_or_default();
+    container::apply_env_overrides(&mut startup_config);
+    let run_inline = match cli.command {
+        Command::Start { foreground: true } => true,
+        Command::Start { foreground: false } => !startup_config.daemon,
+        Command::RelayServer { .. } => true,
+        _ => false,
+    };
+    if !run_inline {
+        // Dispatch-handled commands report their own output via println!
+        // and never touch the log crate, so only the running server needs
+        // a tokio runtime or error_log routing.
+        return cli::dispatch(cli.command, &admin_config_path, cli.json);
+    }
+
+    // `ServerConfig.threads` sizes the tokio runtime the proxy and its
+    // supporting services actually run on, not just pingora's own
+    // `ServerConf.threads` (applied below) - a runtime built by `#[tokio::main]`
+    // before we'd loaded config could never have honored it. `relay-server`
+    // doesn't read `ServerConfig.threads` (it isn't meant to run alongside a
+    // `mini start`, so it has no shared config to size itself from) and just
+    // takes tokio's own default multi-thread sizing.
+    if let Command::RelayServer { control_listen, public_listen, cert, key, token, url_template } = cli.command {
+        let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+        return runtime.block_on(relay::run_server(relay::RelayServerSettings {
+            control_listen_addr: control_listen,
+            public_listen_addr: public_listen,
+            cert_path: cert,
+            key_path: key,
+            token,
+            public_url_template: url_template,
+        }));
+    }
+
+    // A single configured worker thread (the `low_resource` profile's
+    // choice) gets a genuinely single-threaded runtime instead of a
+    // multi-thread one pinned to one worker - less scheduler overhead,
+    // which is the whole point on a Raspberry Pi or small VM.
+    let worker_threads = startup_config.threads.max(1);
+    let runtime = if worker_threads == 1 {
+        tokio::runtime::Builder::new_current_thread().enable_all().build()?
+    } else {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(worker_threads)
+            .enable_all()
+            .build()?
+    };
+    runtime.block_on(run_server(admin_config_path, startup_config))
+}
+
+async fn run_server(admin_config_path: std::path::PathBuf, startup_config: config::ServerConfig) -> anyhow::Result<()> {
+    let startup_began = Instant::now();
+    error_log::init(&startup_config)?;
 
-    // Create server
-    let mut server = Server::new(Some(Opt::default())).unwrap();
+    // Create server. `upstream_keepalive_pool_size` is pingora's own conf
+    // knob for the process-wide keepalive pool; everything else about
+    // `admin_config.upstream_pool` is applied per-peer below.
+    let mut server_conf = pingora_core::server::configuration::ServerConf::default();
+    server_conf.upstream_keepalive_pool_size = startup_config.upstream_pool.keepalive_pool_size;
+    server_conf.threads = startup_config.threads.max(1);
+    let mut server = Server::new_with_opt_and_conf(Some(Opt::default()), server_conf);
     server.bootstrap();
 
     // Initialize site manager and driver registry
-    let registry = Arc::new(DriverRegistry::new());
-    let _site_manager = Arc::new(SiteManager::new(registry.clone()));
+    let registry = Arc::new(DriverRegistry::with_known_drivers());
+    let site_manager = Arc::new(SiteManager::new(registry.clone()));
+
+    // Periodically ping every managed php-fpm pool and restart any that
+    // are dead or wedged, so a crashed FPM doesn't mean every site on
+    // that PHP version 502s until someone notices and restarts it by hand.
+    let health_check_pool_manager = registry.pool_manager();
+    let health_check_future = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(php_fpm::HEALTH_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            health_check_pool_manager.run_health_checks().await;
+        }
+    });
 
-    // Register Laravel driver with default PHP version
-    registry.register(Arc::new(LaravelDriver::new(
-        PathBuf::from("/path/to/app"),
-        "8.2".to_string(),
-    )));
+    // Reuse the config loaded above (now known to agree with `run_inline`) so
+    // the port strategy and admin/gRPC listeners all see the same settings
+    // (and the same persisted admin token, once generated).
+    let mut admin_config = startup_config;
 
     // Setup proxy service
+    let latency_histograms = Arc::new(latency_metrics::LatencyHistograms::new(
+        &admin_config.http_latency_buckets,
+        &admin_config.https_latency_buckets,
+        &admin_config.upstream_latency_buckets,
+    ));
+    // Distributed tracing is opt-in: most local dev setups don't have a
+    // Jaeger agent running, and `request_tracing::init` needs one reachable
+    // to hand its reporter a socket, so a failure here is logged and
+    // treated the same as tracing being unconfigured rather than fatal.
+    let tracer = if admin_config.tracing_enabled {
+        let jaeger_agent_addr: SocketAddr = admin_config
+            .jaeger_agent_addr
+            .parse()
+            .unwrap_or_else(|_| "127.0.0.1:6831".parse().unwrap());
+        match request_tracing::init(jaeger_agent_addr).await {
+            Ok(tracer) => Some(tracer),
+            Err(e) => {
+                error!("Failed to start distributed tracing: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let request_log = Arc::new(request_log::RequestLog::new(admin_config.request_log_capacity));
+    let error_feed = Arc::new(error_feed::ErrorFeed::new(admin_config.error_feed_capacity));
+
+    // Resolved ahead of the `MyProxy` struct below (rather than alongside
+    // the rest of the listener setup further down) because `network_access`
+    // needs the HTTP listener's actual bound address to build the access
+    // link it challenges unauthorized devices with.
+    let http_requested = network_access::resolve_listen_addr(&admin_config.http_listen_addr, admin_config.network_access, 80);
+    let http_bound = ports::choose_listen_addr(http_requested, 8080, ports::Protocol::Tcp);
+
+    let network_access = if admin_config.network_access {
+        let token = network_access::ensure_network_access_token(&mut admin_config);
+        if let Err(e) = cli::save_config(&admin_config, &admin_config_path) {
+            error!("failed to persist network_access token: {e}");
+        }
+        let guard = Arc::new(network_access::NetworkAccessGuard::new(
+            token,
+            http_bound.actual,
+            admin_config_path.clone(),
+        ));
+        guard.announce();
+        Some(guard)
+    } else {
+        None
+    };
+
     let proxy = MyProxy {
         req_metric: register_int_counter!("req_counter", "Number of requests").unwrap(),
         active_connections: register_int_gauge!("active_connections", "Number of active connections").unwrap(),
+        upstream_reused_connections: register_int_counter!(
+            "mini_upstream_reused_connections_total",
+            "Upstream connections served from pingora's keepalive pool instead of opened fresh"
+        )
+        .unwrap(),
+        upstream_new_connections: register_int_counter!(
+            "mini_upstream_new_connections_total",
+            "Upstream connections opened fresh because none were idle in the keepalive pool"
+        )
+        .unwrap(),
+        latency_histograms: latency_histograms.clone(),
+        tracer,
+        upstream_pool: admin_config.upstream_pool.clone(),
+        request_log: request_log.clone(),
+        capture_headers: admin_config.request_log_capture_headers,
+        error_feed: error_feed.clone(),
+        in_flight_requests: Arc::new(AtomicUsize::new(0)),
+        load_shedding: admin_config.load_shedding.clone(),
+        shed_requests: register_int_counter!(
+            "mini_shed_requests_total",
+            "Requests turned away with a 503 because load_shedding.max_in_flight_requests was exceeded"
+        )
+        .unwrap(),
+        network_access,
     };
 
     let mut proxy_service = pingora_proxy::http_proxy_service(&server.configuration, proxy);
 
-    // Add plain HTTP listener
-    proxy_service.add_tcp("0.0.0.0:80");
+    // Add plain HTTP listener, falling back to a high port (with
+    // best-effort OS port forwarding) if 80 isn't bindable without
+    // elevated privileges. `http_bound` was already resolved above (see the
+    // `network_access` setup) since it's needed before `MyProxy` is built.
+    proxy_service.add_tcp(&http_bound.actual.to_string());
+    info!("HTTP listening on {}", http_bound.actual);
 
-    // Add HTTPS listener with TLS
+    // Add HTTPS listener with TLS, under the same fallback strategy.
     let cert_path = "certs/server.crt";
     let key_path = "certs/server.key";
-    if std::path::Path::new(cert_path).exists() && std::path::Path::new(key_path).exists() {
+    let https_bound = if std::path::Path::new(cert_path).exists() && std::path::Path::new(key_path).exists() {
+        let https_requested = network_access::resolve_listen_addr(&admin_config.https_listen_addr, admin_config.network_access, 443);
+        let https_bound = ports::choose_listen_addr(https_requested, 8443, ports::Protocol::Tcp);
         let mut tls_settings = TlsSettings::intermediate(cert_path, key_path).unwrap();
         tls_settings.enable_h2();
-        proxy_service.add_tls_with_settings("0.0.0.0:443", None, tls_settings);
+        proxy_service.add_tls_with_settings(&https_bound.actual.to_string(), None, tls_settings);
+        info!("HTTPS listening on {}", https_bound.actual);
+        Some(https_bound)
     } else {
         warn!("TLS certificates not found, HTTPS listener disabled");
-    }
+        None
+    };
 
-    // Add prometheus metrics endpoint
-    let mut prometheus_service = pingora_core::services::listening::Service::prometheus_http_service();
-    prometheus_service.add_tcp("127.0.0.1:9090");
+    // Add the prometheus metrics endpoint, unless the user disabled it (e.g.
+    // because they already run a real Prometheus on the default port).
+    let metrics_service = metrics_endpoint::service(
+        admin_config.metrics_enabled,
+        admin_config.metrics_token.clone(),
+    )
+    .map(|mut service| {
+        match (&admin_config.metrics_tls_cert_path, &admin_config.metrics_tls_key_path) {
+            (Some(cert_path), Some(key_path)) => {
+                let mut tls_settings = TlsSettings::intermediate(cert_path, key_path).unwrap();
+                tls_settings.enable_h2();
+                service.add_tls_with_settings(&admin_config.metrics_listen_addr, None, tls_settings);
+            }
+            _ => service.add_tcp(&admin_config.metrics_listen_addr),
+        }
+        service
+    });
+    if metrics_service.is_some() {
+        info!("Metrics listening on {}", admin_config.metrics_listen_addr);
+    } else {
+        info!("Metrics endpoint disabled");
+    }
 
     // Add services to server
     server.add_service(proxy_service);
-    server.add_service(prometheus_service);
-
-    // Start DNS server
-    let dns_handler = dns::DnsHandler::new();
-    let mut dns_server = hickory_server::ServerFuture::new(dns_handler);
+    if let Some(metrics_service) = metrics_service {
+        server.add_service(metrics_service);
+    }
 
-    match UdpSocket::bind("0.0.0.0:53").await {
-        Ok(socket) => {
-            dns_server.register_socket(socket);
-            info!("DNS server listening on 0.0.0.0:53");
+    // Populate the site manager from `admin_config` (explicitly linked
+    // sites, then a parked-directory rescan) off the critical startup path -
+    // this can mean globbing hundreds of parked subdirectories, and none of
+    // it needs to finish before the listeners above start accepting
+    // connections. FPM pools need no equivalent warmup here: they're already
+    // started lazily, on each php_version's first request, by
+    // `php_fpm::PoolManager::ensure_running`.
+    let init_site_manager = site_manager.clone();
+    let init_sites = admin_config.sites.clone();
+    let init_parked_paths = admin_config.parked_paths.clone();
+    tokio::spawn(async move {
+        let started = Instant::now();
+        for (domain, site) in &init_sites {
+            let options = site::LinkOptions {
+                driver_override: site.driver.clone(),
+                secure: site.secure,
+                php_version: site.php_version.clone(),
+                aliases: Vec::new(),
+            };
+            if let Err(e) = init_site_manager
+                .link(domain, std::path::PathBuf::from(&site.root_dir), options)
+                .await
+            {
+                error!("Failed to link configured site {domain}: {e}");
+            }
         }
-        Err(e) => {
-            error!("Failed to bind DNS server to port 53: {}", e);
-            // Continue without DNS server
+        match init_site_manager.rescan_parked(&init_parked_paths).await {
+            Ok(diff) => info!(
+                "Site manager ready in {:?} ({} linked, {} parked added, {} parked removed)",
+                started.elapsed(),
+                init_sites.len(),
+                diff.added.len(),
+                diff.removed.len(),
+            ),
+            Err(e) => error!("Failed to scan parked paths: {}", e),
+        }
+    });
+
+    // Start DNS server, with the same privileged-port fallback strategy.
+    // Gated behind the `dns` feature (see Cargo.toml) - on by default, so
+    // this is a no-op for the `mini` binary itself. `dns_server` is handed
+    // off to its own dedicated runtime further down, where `dns_future` is
+    // built.
+    #[cfg(feature = "dns")]
+    let mut dns_server = {
+        let dns_handler = dns::DnsHandler::new();
+        let mut dns_server = hickory_server::ServerFuture::new(dns_handler);
+
+        let dns_requested: SocketAddr = "0.0.0.0:53".parse().unwrap();
+        let dns_bound = ports::choose_listen_addr(dns_requested, 5353, ports::Protocol::Udp);
+        match UdpSocket::bind(dns_bound.actual).await {
+            Ok(socket) => {
+                dns_server.register_socket(socket);
+                info!("DNS server listening on {}", dns_bound.actual);
+            }
+            Err(e) => {
+                error!("Failed to bind DNS server to {}: {}", dns_bound.actual, e);
+                // Continue without DNS server
+            }
         }
+        dns_server
+    };
+
+    // Start the admin REST API on its own loopback listener
+    let admin_token = admin::ensure_admin_token(&mut admin_config);
+    if let Err(e) = cli::save_config(&admin_config, &admin_config_path) {
+        error!("Failed to persist admin token: {}", e);
     }
+    let admin_addr: SocketAddr = admin_config
+        .admin_listen_addr
+        .parse()
+        .unwrap_or_else(|_| "127.0.0.1:7472".parse().unwrap());
+
+    let admin_site_manager = site_manager.clone();
+    let admin_request_log = request_log.clone();
+    let admin_error_feed = error_feed.clone();
+    let grpc_token = admin_token.clone();
+    let admin_future = tokio::spawn(async move {
+        if let Err(e) = admin::serve(
+            admin_addr,
+            admin_config_path,
+            admin_token,
+            admin_site_manager,
+            admin_request_log,
+            admin_error_feed,
+        )
+        .await
+        {
+            error!("Admin API error: {}", e);
+        }
+        info!("Admin API stopped");
+    });
+
+    // Start the gRPC control plane on its own loopback listener
+    let grpc_addr: SocketAddr = admin_config
+        .grpc_listen_addr
+        .parse()
+        .unwrap_or_else(|_| "127.0.0.1:50051".parse().unwrap());
+    let grpc_config_path = cli::default_config_path();
+
+    let grpc_future = tokio::spawn(async move {
+        if let Err(e) = grpc::serve(grpc_addr, grpc_config_path, grpc_token).await {
+            error!("gRPC control plane error: {}", e);
+        }
+        info!("gRPC control plane stopped");
+    });
+
+    // Start the prometools latency-histogram endpoint on its own loopback
+    // listener, since its OpenMetrics encoder isn't compatible with the
+    // `prometheus` crate's own metrics endpoint above.
+    let latency_metrics_addr: SocketAddr = admin_config
+        .latency_metrics_listen_addr
+        .parse()
+        .unwrap_or_else(|_| "127.0.0.1:9091".parse().unwrap());
 
-    // Run both servers
+    let latency_metrics_future = tokio::spawn(async move {
+        if let Err(e) = latency_metrics::serve(latency_metrics_addr, latency_histograms).await {
+            error!("Latency metrics error: {}", e);
+        }
+        info!("Latency metrics stopped");
+    });
+
+    // Start the self-hosted relay client, if `relay_client.server_addr` is
+    // configured - the daemon-resident counterpart to `mini relay-server`,
+    // an alternative to the third-party tunnel providers in `share.rs`.
+    // Disabled (the common case) it just parks forever, the same way the
+    // metrics endpoint above being disabled just skips adding its service.
+    let relay_config_path = cli::default_config_path();
+    let relay_client_config = admin_config.relay_client.clone();
+    let relay_share_port = admin_config.share_port;
+    let relay_future = tokio::spawn(run_relay_client(relay_client_config, relay_share_port, relay_config_path));
+
+    // Start the built-in mail catcher, if enabled - an SMTP listener plus a
+    // web UI to browse what it caught, the same "parks forever when
+    // disabled" idiom as the relay client above.
+    let mail_catcher_enabled = admin_config.mail_catcher_enabled;
+    let mail_catcher_smtp_addr: SocketAddr = admin_config
+        .mail_catcher_smtp_listen_addr
+        .parse()
+        .unwrap_or_else(|_| "127.0.0.1:1025".parse().unwrap());
+    let mail_catcher_http_addr: SocketAddr = admin_config
+        .mail_catcher_http_listen_addr
+        .parse()
+        .unwrap_or_else(|_| "127.0.0.1:8025".parse().unwrap());
+    let mail_catcher_capacity = admin_config.mail_catcher_capacity;
+    let mail_catcher_future = tokio::spawn(run_mail_catcher(
+        mail_catcher_enabled,
+        mail_catcher_smtp_addr,
+        mail_catcher_http_addr,
+        mail_catcher_capacity,
+    ));
+
+    // Run the proxy and DNS servers. This has to happen before
+    // `drop_privileges` below: `add_tcp`/`add_tls_with_settings` above only
+    // registered these addresses with pingora's `Listeners` - the real
+    // `bind()` for privileged ports like 80/443 happens on a thread
+    // `run_forever` spawns per service, once it's called.
     let proxy_future = tokio::spawn(async move {
-        let server_future: Pin<Box<dyn Future<Output = Result<()>> + Send>> = Box::pin(async move {
+        let server_future: Pin<Box<dyn Future<Output = PingoraResult<()>> + Send>> = Box::pin(async move {
             server.run_forever()
         });
         if let Err(e) = server_future.await {
@@ -213,15 +857,177 @@ async fn main() -> Result<()> {
         info!("HTTP proxy server stopped");
     });
 
-    let dns_future = tokio::spawn(async move {
-        let _ = dns_server.block_until_done().await;
-        info!("DNS server stopped");
+    // `run_forever`'s bind happens asynchronously on a thread it spawns, so
+    // there's no callback for "listening now" to wait on - poll each
+    // configured address with a real connection attempt instead of racing a
+    // guess about how long that takes. If a privileged port never comes up
+    // (misconfiguration, the bind itself failing) we still drop privileges
+    // rather than hang startup forever; the proxy logs its own bind error.
+    wait_until_listening(http_bound.actual, "HTTP").await;
+    if let Some(https_bound) = https_bound {
+        wait_until_listening(https_bound.actual, "HTTPS").await;
+    }
+
+    // Listeners are bound (or gave up trying) and the admin token is
+    // persisted; drop to the configured user/group before serving any
+    // requests.
+    if let Err(e) = privileges::drop_privileges(&admin_config) {
+        error!("Failed to drop privileges: {}", e);
+        return Err(e);
+    }
+
+    info!("Startup finished in {:?}", startup_began.elapsed());
+
+    // The DNS service gets its own dedicated tokio runtime, sized by
+    // `dns_threads` rather than sharing the main runtime's `threads` worker
+    // pool - local DNS lookups are lightweight and shouldn't have to compete
+    // with proxy traffic for a slot, and a laptop running on battery usually
+    // only needs the one thread `dns_threads` defaults to.
+    #[cfg(feature = "dns")]
+    let dns_future = {
+        let dns_threads = admin_config.dns_threads.max(1);
+        tokio::task::spawn_blocking(move || {
+            let dns_runtime = tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(dns_threads)
+                .enable_all()
+                .build()
+                .expect("failed to build the DNS service's dedicated tokio runtime");
+            dns_runtime.block_on(async {
+                let _ = dns_server.block_until_done().await;
+            });
+            info!("DNS server stopped");
+        })
+    };
+    // With the `dns` feature off there's no DNS service to wait on; a
+    // pending future keeps its `tokio::select!` arm below from ever firing.
+    #[cfg(not(feature = "dns"))]
+    let dns_future = std::future::pending::<()>();
+
+    // `mini stop` signals us with SIGTERM, and a foreground `mini start`
+    // gets SIGINT from Ctrl-C; without our own handlers the default
+    // disposition just kills the process, leaving every php-fpm master this
+    // daemon spawned (see `php_fpm::PoolManager`) orphaned rather than shut
+    // down. Drain them gracefully before exiting either way.
+    //
+    // This drains php-fpm directly rather than through
+    // `runtime::RuntimeHandle::shutdown` - the proxy/DNS/admin tasks above
+    // are still spawned inline here, not through a `Runtime` (see that
+    // module's doc comment), so there's no `RuntimeHandle` in `main()` to
+    // route this through yet.
+    let shutdown_pool_manager = registry.pool_manager();
+    let shutdown_future = tokio::spawn(async move {
+        let mut sigterm = signal(SignalKind::terminate()).expect("failed to install a SIGTERM handler");
+        let mut sigint = signal(SignalKind::interrupt()).expect("failed to install a SIGINT handler");
+        tokio::select! {
+            _ = sigterm.recv() => info!("received SIGTERM, shutting down managed php-fpm pools"),
+            _ = sigint.recv() => info!("received SIGINT, shutting down managed php-fpm pools"),
+        }
+        shutdown_pool_manager.shutdown_all().await;
     });
 
     tokio::select! {
         _ = proxy_future => {}
         _ = dns_future => {}
+        _ = admin_future => {}
+        _ = grpc_future => {}
+        _ = latency_metrics_future => {}
+        _ = relay_future => {}
+        _ = mail_catcher_future => {}
+        _ = health_check_future => {}
+        _ = shutdown_future => {}
     }
 
     Ok(())
 }
+
+/// Poll `addr` with real connection attempts until one succeeds, logging
+/// and giving up after a few seconds rather than hanging startup forever if
+/// the listener never comes up (the bind itself failed, or failed over to a
+/// different port than `addr` - either way `drop_privileges` proceeding is
+/// no worse than it binding after the drop would have been).
+async fn wait_until_listening(addr: SocketAddr, label: &str) {
+    const ATTEMPTS: u32 = 50;
+    const RETRY_DELAY: Duration = Duration::from_millis(20);
+
+    for attempt in 0..ATTEMPTS {
+        if tokio::net::TcpStream::connect(addr).await.is_ok() {
+            return;
+        }
+        if attempt + 1 < ATTEMPTS {
+            tokio::time::sleep(RETRY_DELAY).await;
+        }
+    }
+    warn!("{label} listener at {addr} did not come up within {:?}; dropping privileges anyway", RETRY_DELAY * ATTEMPTS);
+}
+
+/// Keep a self-hosted relay client connection registered for as long as the
+/// daemon runs, reconnecting on a fixed delay if the relay server drops the
+/// connection. Parks forever without ever touching the network if
+/// `relay_client.server_addr` isn't set, the same as every other optional
+/// subsystem in `run_server` above.
+async fn run_relay_client(relay_client_config: config::RelayClientConfig, share_port: u16, config_path: std::path::PathBuf) {
+    let Some(server_addr) = relay_client_config.server_addr.clone() else {
+        std::future::pending::<()>().await;
+        return;
+    };
+    let Some(domain) = relay_client_config.domain.clone() else {
+        error!("relay_client.server_addr is set but relay_client.domain is not - relay client disabled");
+        std::future::pending::<()>().await;
+        return;
+    };
+    let Some(ca_cert_path) = relay_client_config.ca_cert_path.clone() else {
+        error!("relay_client.server_addr is set but relay_client.ca_cert_path is not - relay client disabled");
+        std::future::pending::<()>().await;
+        return;
+    };
+    let local_addr = format!("127.0.0.1:{share_port}");
+    let token = relay_client_config.token.clone();
+
+    loop {
+        let persist_config_path = config_path.clone();
+        let persist_domain = domain.clone();
+        let result = relay::run_client(
+            &server_addr,
+            &domain,
+            &local_addr,
+            token.as_deref(),
+            std::path::Path::new(&ca_cert_path),
+            move |url| {
+                info!("relay client registered {persist_domain} at {url}");
+                match cli::load_config(&persist_config_path) {
+                    Ok(mut config) => {
+                        config.relay_client.assigned_url = Some(url);
+                        if let Err(e) = cli::save_config(&config, &persist_config_path) {
+                            error!("failed to persist relay URL for {persist_domain}: {e}");
+                        }
+                    }
+                    Err(e) => error!("failed to load config to persist relay URL for {persist_domain}: {e}"),
+                }
+            },
+        )
+        .await;
+        if let Err(e) = result {
+            error!("relay client for {domain} error: {e}");
+        }
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
+
+/// Run the built-in mail catcher's SMTP listener and web UI for as long as
+/// the daemon runs. Parks forever without binding either socket if
+/// `mail_catcher_enabled` is false, the same as `run_relay_client` above.
+async fn run_mail_catcher(enabled: bool, smtp_addr: SocketAddr, http_addr: SocketAddr, capacity: usize) {
+    if !enabled {
+        std::future::pending::<()>().await;
+        return;
+    }
+
+    let store = Arc::new(mailbox::MailStore::new(capacity));
+    let result = tokio::try_join!(
+        mailbox::run_smtp_server(smtp_addr, store.clone()),
+        mailbox::serve(http_addr, store),
+    );
+    if let Err(e) = result {
+        error!("mail catcher error: {e}");
+    }
+}
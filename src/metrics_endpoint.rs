@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use http::Response;
+use pingora_core::apps::http_app::{HttpServer, ServeHttp};
+use pingora_core::apps::prometheus_http_app::PrometheusHttpApp;
+use pingora_core::protocols::http::ServerSession;
+
+/// Wraps pingora's built-in [`PrometheusHttpApp`] with an optional
+/// bearer-token check, mirroring [`crate::admin::is_authorized`] - some
+/// users run a real Prometheus on the default `9090` already and don't
+/// want mini's own metrics reachable by anyone who can reach that port.
+pub struct MetricsApp {
+    token: Option<String>,
+    inner: PrometheusHttpApp,
+}
+
+impl MetricsApp {
+    pub fn new(token: Option<String>) -> Self {
+        MetricsApp {
+            token,
+            inner: PrometheusHttpApp,
+        }
+    }
+}
+
+#[async_trait]
+impl ServeHttp for MetricsApp {
+    async fn response(&self, http_session: &mut ServerSession) -> Response<Vec<u8>> {
+        if let Some(token) = &self.token {
+            let authorized = http_session
+                .req_header()
+                .headers
+                .get("authorization")
+                .and_then(|value| value.to_str().ok())
+                == Some(format!("Bearer {token}").as_str());
+            if !authorized {
+                return Response::builder()
+                    .status(401)
+                    .body(b"unauthorized".to_vec())
+                    .unwrap();
+            }
+        }
+        self.inner.response(http_session).await
+    }
+}
+
+/// Build the metrics listening service, or `None` if `enabled` is false -
+/// the caller skips adding it to the server entirely in that case, rather
+/// than binding a socket nobody's meant to hit.
+pub fn service(
+    enabled: bool,
+    token: Option<String>,
+) -> Option<pingora_core::services::listening::Service<HttpServer<MetricsApp>>> {
+    if !enabled {
+        return None;
+    }
+    Some(pingora_core::services::listening::Service::new(
+        "Prometheus Metrics Service".to_string(),
+        HttpServer::new_app(MetricsApp::new(token)),
+    ))
+}
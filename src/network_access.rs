@@ -0,0 +1,204 @@
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use tracing::{error, info};
+
+use crate::cli::{load_config, save_config};
+use crate::config::ServerConfig;
+
+fn generate_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(24)
+        .map(char::from)
+        .collect()
+}
+
+/// Generate a fresh one-time device-authorization token, persisting it into
+/// `config` if it doesn't already have one - the same pattern as
+/// `admin::ensure_admin_token`, except this one gets spent and rotated (see
+/// [`NetworkAccessGuard::authorize`]) rather than kept for the daemon's
+/// whole run, since it's meant to authorize exactly one device.
+pub fn ensure_network_access_token(config: &mut ServerConfig) -> String {
+    if let Some(token) = &config.network_access_token {
+        return token.clone();
+    }
+    let token = generate_token();
+    config.network_access_token = Some(token.clone());
+    token
+}
+
+/// Resolve the address a listener should actually bind to: `configured`,
+/// unless `network_access` is set, in which case the host is forced to
+/// `0.0.0.0` (keeping whatever port `configured` named) - overriding
+/// `http_listen_addr`/`https_listen_addr`, which otherwise default to
+/// loopback-only and would make `network_access` do nothing.
+pub fn resolve_listen_addr(configured: &str, network_access: bool, fallback_port: u16) -> SocketAddr {
+    let requested: SocketAddr = configured
+        .parse()
+        .unwrap_or_else(|_| SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), fallback_port));
+    if network_access {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), requested.port())
+    } else {
+        requested
+    }
+}
+
+/// The link to print (to the log) for whoever's on the LAN to open on the
+/// device they want to authorize.
+pub fn access_url(listen_addr: &SocketAddr, token: &str) -> String {
+    let host = match listen_addr.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => local_lan_ip()
+            .map(|ip| ip.to_string())
+            .unwrap_or_else(|| "<this-machine's-lan-ip>".to_string()),
+        ip => ip.to_string(),
+    };
+    format!("http://{host}:{}/?mini_token={token}", listen_addr.port())
+}
+
+/// Best-effort guess at the LAN-facing IPv4 address to put in
+/// [`access_url`], found the same way any program finds "my own address": ask
+/// the OS what it'd use to route to an arbitrary external address, without
+/// actually sending anything.
+fn local_lan_ip() -> Option<IpAddr> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("1.1.1.1:80").ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+/// Tracks which LAN devices have already presented a valid one-time token.
+/// Loopback traffic (the machine running mini itself) is always exempt -
+/// only requests arriving from elsewhere on the network are gated.
+///
+/// Authorized devices are tracked in memory only, not persisted - a daemon
+/// restart means every device needs to re-authorize, the same trade-off
+/// `network_access` already makes by not running as a long-lived service
+/// with real accounts.
+pub struct NetworkAccessGuard {
+    token: Mutex<String>,
+    authorized: Mutex<HashSet<IpAddr>>,
+    listen_addr: SocketAddr,
+    config_path: PathBuf,
+}
+
+impl NetworkAccessGuard {
+    pub fn new(token: String, listen_addr: SocketAddr, config_path: PathBuf) -> Self {
+        NetworkAccessGuard {
+            token: Mutex::new(token),
+            authorized: Mutex::new(HashSet::new()),
+            listen_addr,
+            config_path,
+        }
+    }
+
+    /// Log the link for the next device to authorize with.
+    pub fn announce(&self) {
+        let token = self.token.lock().unwrap().clone();
+        info!("network_access: next device can authorize at {}", access_url(&self.listen_addr, &token));
+    }
+
+    pub fn is_authorized(&self, addr: &IpAddr) -> bool {
+        addr.is_loopback() || self.authorized.lock().unwrap().contains(addr)
+    }
+
+    /// Check `candidate` against the current one-time token; if it matches,
+    /// authorize `addr` for the rest of this daemon's run and rotate to a
+    /// fresh token (persisting the rotation) so `candidate` can't be reused
+    /// to authorize a second device. Returns whether `candidate` was
+    /// accepted.
+    pub fn authorize(&self, addr: IpAddr, candidate: &str) -> bool {
+        let fresh = {
+            let mut token = self.token.lock().unwrap();
+            if candidate != *token {
+                return false;
+            }
+            *token = generate_token();
+            token.clone()
+        };
+        self.authorized.lock().unwrap().insert(addr);
+        self.persist_token(&fresh);
+        self.announce();
+        true
+    }
+
+    fn persist_token(&self, token: &str) {
+        match load_config(&self.config_path) {
+            Ok(mut config) => {
+                config.network_access_token = Some(token.to_string());
+                if let Err(e) = save_config(&config, &self.config_path) {
+                    error!("failed to persist rotated network_access token: {e}");
+                }
+            }
+            Err(e) => error!("failed to load config to persist rotated network_access token: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resolve_listen_addr_keeps_configured_host_when_disabled() {
+        let addr = resolve_listen_addr("127.0.0.1:8080", false, 80);
+        assert_eq!(addr, "127.0.0.1:8080".parse().unwrap());
+    }
+
+    #[test]
+    fn test_resolve_listen_addr_forces_unspecified_host_when_enabled() {
+        let addr = resolve_listen_addr("127.0.0.1:8080", true, 80);
+        assert_eq!(addr, "0.0.0.0:8080".parse().unwrap());
+    }
+
+    #[test]
+    fn test_resolve_listen_addr_falls_back_to_the_default_port_unparsable() {
+        let addr = resolve_listen_addr("not-an-addr", false, 8080);
+        assert_eq!(addr, "0.0.0.0:8080".parse().unwrap());
+    }
+
+    #[test]
+    fn test_network_access_guard_exempts_loopback_without_a_token() {
+        let guard = NetworkAccessGuard::new(
+            "secret".to_string(),
+            "0.0.0.0:80".parse().unwrap(),
+            PathBuf::from("/dev/null"),
+        );
+        assert!(guard.is_authorized(&"127.0.0.1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_network_access_guard_rejects_the_wrong_token() {
+        let guard = NetworkAccessGuard::new(
+            "secret".to_string(),
+            "0.0.0.0:80".parse().unwrap(),
+            PathBuf::from("/dev/null"),
+        );
+        let lan_ip: IpAddr = "192.168.1.50".parse().unwrap();
+        assert!(!guard.authorize(lan_ip, "wrong"));
+        assert!(!guard.is_authorized(&lan_ip));
+    }
+
+    #[test]
+    fn test_network_access_guard_authorizes_and_rotates_on_a_valid_token() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("config.yaml");
+        let guard = NetworkAccessGuard::new(
+            "secret".to_string(),
+            "0.0.0.0:80".parse().unwrap(),
+            config_path,
+        );
+        let lan_ip: IpAddr = "192.168.1.50".parse().unwrap();
+
+        assert!(guard.authorize(lan_ip, "secret"));
+        assert!(guard.is_authorized(&lan_ip));
+        // The spent token no longer works for a second device.
+        let other_ip: IpAddr = "192.168.1.51".parse().unwrap();
+        assert!(!guard.authorize(other_ip, "secret"));
+        assert!(!guard.is_authorized(&other_ip));
+    }
+}
@@ -0,0 +1,140 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::Result;
+
+use crate::config::ServerConfig;
+use crate::parking;
+
+/// Resolve `domain` if given, otherwise figure out which linked or parked
+/// site `cwd` belongs to.
+pub(crate) fn resolve_domain(config: &ServerConfig, domain: Option<String>, cwd: &Path) -> Result<String> {
+    if let Some(domain) = domain {
+        return Ok(domain);
+    }
+
+    if let Some(site) = config.sites.values().find(|site| Path::new(&site.root_dir) == cwd) {
+        return Ok(site.domain.clone());
+    }
+
+    let (candidates, _) = parking::scan_parked_paths(&config.parked_paths);
+    if let Some(candidate) = candidates.iter().find(|candidate| candidate.path == cwd) {
+        return Ok(candidate.domain.clone());
+    }
+
+    anyhow::bail!("{} is not a linked or parked site", cwd.display())
+}
+
+/// The URL a browser should open for `domain`, using https if the site is
+/// secured and defaulting to http for domains mini doesn't know about.
+fn site_url(config: &ServerConfig, domain: &str) -> String {
+    let secure = config.sites.get(domain).map(|site| site.secure).unwrap_or(false);
+    let scheme = if secure { "https" } else { "http" };
+    format!("{scheme}://{domain}")
+}
+
+/// Launch the OS default browser at `url`.
+fn open_in_browser(url: &str) -> Result<()> {
+    let status = if cfg!(target_os = "macos") {
+        Command::new("open").arg(url).status()
+    } else if cfg!(target_os = "windows") {
+        Command::new("cmd").args(["/C", "start", "", url]).status()
+    } else {
+        Command::new("xdg-open").arg(url).status()
+    };
+
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => anyhow::bail!("failed to open browser (exit status {status})"),
+        Err(e) => anyhow::bail!("failed to launch browser: {e}"),
+    }
+}
+
+/// Resolve `domain` (or the site at `cwd`) and open it in the browser.
+pub fn open(config: &ServerConfig, domain: Option<String>, cwd: &Path) -> Result<()> {
+    let domain = resolve_domain(config, domain, cwd)?;
+    let url = site_url(config, &domain);
+    println!("Opening {url}");
+    open_in_browser(&url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SiteConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resolve_domain_prefers_the_explicit_argument() {
+        let config = ServerConfig::default();
+        let domain = resolve_domain(&config, Some("explicit.test".to_string()), Path::new("/tmp"));
+        assert_eq!(domain.unwrap(), "explicit.test");
+    }
+
+    #[test]
+    fn test_resolve_domain_matches_a_linked_site_by_root_dir() {
+        let mut config = ServerConfig::default();
+        config.add_site(
+            "myapp.test".to_string(),
+            SiteConfig {
+                root_dir: "/Users/test/Sites/myapp".to_string(),
+                domain: "myapp.test".to_string(),
+                secure: false,
+                php_version: None,
+                env_vars: Default::default(),
+                driver: None,
+                notes: None,
+                tags: Vec::new(),
+                php_ini: Default::default(),
+                xdebug: false,
+                proxy_target: None,
+            },
+        );
+
+        let domain = resolve_domain(&config, None, Path::new("/Users/test/Sites/myapp"));
+        assert_eq!(domain.unwrap(), "myapp.test");
+    }
+
+    #[test]
+    fn test_resolve_domain_matches_a_parked_candidate() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("blog")).unwrap();
+
+        let mut config = ServerConfig::default();
+        config.add_parked_path(dir.path().to_str().unwrap());
+
+        let domain = resolve_domain(&config, None, &dir.path().join("blog"));
+        assert_eq!(domain.unwrap(), "blog");
+    }
+
+    #[test]
+    fn test_resolve_domain_errors_for_an_unknown_directory() {
+        let config = ServerConfig::default();
+        assert!(resolve_domain(&config, None, Path::new("/nowhere")).is_err());
+    }
+
+    #[test]
+    fn test_site_url_uses_https_for_secured_sites() {
+        let mut config = ServerConfig::default();
+        config.add_site(
+            "secure.test".to_string(),
+            SiteConfig {
+                root_dir: "/Users/test/Sites/secure".to_string(),
+                domain: "secure.test".to_string(),
+                secure: true,
+                php_version: None,
+                env_vars: Default::default(),
+                driver: None,
+                notes: None,
+                tags: Vec::new(),
+                php_ini: Default::default(),
+                xdebug: false,
+                proxy_target: None,
+            },
+        );
+
+        assert_eq!(site_url(&config, "secure.test"), "https://secure.test");
+        assert_eq!(site_url(&config, "unknown.test"), "http://unknown.test");
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::config::ParkedPath;
+
+/// A site folder discovered while scanning a parked directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParkedCandidate {
+    pub domain: String,
+    pub path: PathBuf,
+}
+
+/// A naming conflict: more than one parked path produced the same domain.
+/// `winner` is the one precedence picked; `shadowed` lists the rest.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParkConflict {
+    pub domain: String,
+    pub winner: PathBuf,
+    pub shadowed: Vec<PathBuf>,
+}
+
+/// What scanning a single parked path found, for `mini paths`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParkedPathReport {
+    pub path: PathBuf,
+    pub scanned_at: u64,
+    pub domains: Vec<String>,
+    /// Set if the directory couldn't be read (missing, permissions, etc);
+    /// `domains` is empty in that case.
+    pub error: Option<String>,
+}
+
+/// Everything `mini paths` reports: a per-path breakdown plus any
+/// cross-path domain conflicts.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PathsReport {
+    pub paths: Vec<ParkedPathReport>,
+    pub conflicts: Vec<ParkConflict>,
+}
+
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Scan parked directories for site folders.
+///
+/// Precedence is config order: if two parked paths both contain a folder
+/// that maps to the same domain (after each path's prefix/suffix is
+/// applied), the earliest path in `parked_paths` wins and the rest are
+/// reported in the returned conflicts instead of silently overwriting it.
+/// Directories that can't be read (missing, permissions) are skipped.
+pub fn scan_parked_paths(parked_paths: &[ParkedPath]) -> (Vec<ParkedCandidate>, Vec<ParkConflict>) {
+    let mut winners: HashMap<String, PathBuf> = HashMap::new();
+    let mut shadowed: HashMap<String, Vec<PathBuf>> = HashMap::new();
+
+    for parked in parked_paths {
+        let entries = match fs::read_dir(&parked.path) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let Some(folder_name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let domain = parked.domain_for(folder_name);
+
+            if winners.contains_key(&domain) {
+                shadowed.entry(domain).or_default().push(path);
+            } else {
+                winners.insert(domain, path);
+            }
+        }
+    }
+
+    let candidates = winners
+        .iter()
+        .map(|(domain, path)| ParkedCandidate {
+            domain: domain.clone(),
+            path: path.clone(),
+        })
+        .collect();
+
+    let conflicts = shadowed
+        .into_iter()
+        .map(|(domain, shadowed_paths)| ParkConflict {
+            winner: winners[&domain].clone(),
+            domain,
+            shadowed: shadowed_paths,
+        })
+        .collect();
+
+    (candidates, conflicts)
+}
+
+/// Scan each parked path individually and report what it found, for `mini
+/// paths`: the domains it would serve, when it was scanned, and why it
+/// contributed nothing if its directory couldn't be read. Conflicts are
+/// reported once per domain across all paths, same as `scan_parked_paths`.
+pub fn describe_parked_paths(parked_paths: &[ParkedPath]) -> PathsReport {
+    let scanned_at = now_unix();
+    let (_, conflicts) = scan_parked_paths(parked_paths);
+
+    let paths = parked_paths
+        .iter()
+        .map(|parked| {
+            let entries = match fs::read_dir(&parked.path) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    return ParkedPathReport {
+                        path: PathBuf::from(&parked.path),
+                        scanned_at,
+                        domains: Vec::new(),
+                        error: Some(e.to_string()),
+                    };
+                }
+            };
+
+            let mut domains: Vec<String> = entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| {
+                    entry
+                        .file_name()
+                        .to_str()
+                        .map(|name| parked.domain_for(name))
+                })
+                .collect();
+            domains.sort();
+
+            ParkedPathReport {
+                path: PathBuf::from(&parked.path),
+                scanned_at,
+                domains,
+                error: None,
+            }
+        })
+        .collect();
+
+    PathsReport { paths, conflicts }
+}
+
+impl PathsReport {
+    pub fn print_human(&self) {
+        for path in &self.paths {
+            println!("{}", path.path.display());
+            match &path.error {
+                Some(error) => println!("  error: {error}"),
+                None if path.domains.is_empty() => println!("  (no sites found)"),
+                None => {
+                    for domain in &path.domains {
+                        println!("  {domain}");
+                    }
+                }
+            }
+        }
+        for conflict in &self.conflicts {
+            println!(
+                "conflict: {} served from {} (shadows {})",
+                conflict.domain,
+                conflict.winner.display(),
+                conflict
+                    .shadowed
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_scan_single_parked_path() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("blog")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("shop")).unwrap();
+
+        let parked = vec![ParkedPath::new(temp_dir.path().to_str().unwrap())];
+        let (candidates, conflicts) = scan_parked_paths(&parked);
+
+        assert_eq!(candidates.len(), 2);
+        assert!(conflicts.is_empty());
+        assert!(candidates.iter().any(|c| c.domain == "blog"));
+        assert!(candidates.iter().any(|c| c.domain == "shop"));
+    }
+
+    #[test]
+    fn test_scan_applies_prefix_and_suffix() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("blog")).unwrap();
+
+        let mut parked = ParkedPath::new(temp_dir.path().to_str().unwrap());
+        parked.prefix = Some("client-a-".to_string());
+        let (candidates, _) = scan_parked_paths(&[parked]);
+
+        assert_eq!(candidates[0].domain, "client-a-blog");
+    }
+
+    #[test]
+    fn test_scan_precedence_on_conflict() {
+        let first = TempDir::new().unwrap();
+        let second = TempDir::new().unwrap();
+        fs::create_dir_all(first.path().join("blog")).unwrap();
+        fs::create_dir_all(second.path().join("blog")).unwrap();
+
+        let parked = vec![
+            ParkedPath::new(first.path().to_str().unwrap()),
+            ParkedPath::new(second.path().to_str().unwrap()),
+        ];
+        let (candidates, conflicts) = scan_parked_paths(&parked);
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].path, first.path().join("blog"));
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].domain, "blog");
+        assert_eq!(conflicts[0].winner, first.path().join("blog"));
+        assert_eq!(conflicts[0].shadowed, vec![second.path().join("blog")]);
+    }
+
+    #[test]
+    fn test_describe_parked_paths_reports_domains_and_scan_time() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("blog")).unwrap();
+
+        let parked = vec![ParkedPath::new(temp_dir.path().to_str().unwrap())];
+        let report = describe_parked_paths(&parked);
+
+        assert_eq!(report.paths.len(), 1);
+        assert_eq!(report.paths[0].domains, vec!["blog".to_string()]);
+        assert!(report.paths[0].error.is_none());
+        assert!(report.paths[0].scanned_at > 0);
+        assert!(report.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_describe_parked_paths_reports_an_unreadable_directory() {
+        let parked = vec![ParkedPath::new("/does/not/exist")];
+        let report = describe_parked_paths(&parked);
+
+        assert_eq!(report.paths.len(), 1);
+        assert!(report.paths[0].domains.is_empty());
+        assert!(report.paths[0].error.is_some());
+    }
+}
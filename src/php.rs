@@ -0,0 +1,242 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::status::php_fpm_socket_candidates;
+
+/// A PHP installation `isolate()` can point a site at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhpInstallation {
+    pub version: String,
+    pub binary_path: PathBuf,
+    /// Where this install's own FPM pool is already listening, if one is
+    /// running under a system-package convention. `None` means mini's own
+    /// [`crate::php_fpm::PoolManager`] should start a pool for it instead.
+    pub fpm_socket: Option<PathBuf>,
+}
+
+/// Find every PHP installation mini can isolate a site to: Homebrew
+/// kegs, apt/dnf-style versioned packages, phpenv/asdf version managers,
+/// and finally whatever `php` resolves to on PATH. Earlier sources win
+/// when more than one reports the same version.
+pub fn discover() -> Vec<PhpInstallation> {
+    let mut found = Vec::new();
+    found.extend(discover_homebrew());
+    found.extend(discover_system_packages());
+    found.extend(discover_version_managers());
+    if let Some(installation) = discover_path() {
+        found.push(installation);
+    }
+
+    let mut seen = HashSet::new();
+    found.retain(|installation| seen.insert(installation.version.clone()));
+    found
+}
+
+/// Homebrew installs each PHP version as its own keg, `php@8.2` etc,
+/// under `/opt/homebrew/opt` (Apple Silicon) or `/usr/local/opt` (Intel).
+fn discover_homebrew() -> Vec<PhpInstallation> {
+    let mut found = Vec::new();
+    for prefix in ["/opt/homebrew/opt", "/usr/local/opt"] {
+        let Ok(entries) = std::fs::read_dir(prefix) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Some(version) = name.strip_prefix("php@") else {
+                continue;
+            };
+            let binary_path = entry.path().join("bin").join("php");
+            if !binary_path.exists() {
+                continue;
+            }
+            let fpm_socket = existing_fpm_socket(version);
+            found.push(PhpInstallation {
+                version: version.to_string(),
+                binary_path,
+                fpm_socket,
+            });
+        }
+    }
+    found
+}
+
+/// Debian/Ubuntu (`php8.2-fpm`) and Fedora/RHEL (`php8.2`) packages both
+/// install a versioned `php8.2` binary alongside the unversioned default.
+fn discover_system_packages() -> Vec<PhpInstallation> {
+    let mut found = Vec::new();
+    for dir in ["/usr/bin", "/usr/sbin"] {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Some(version) = versioned_php_binary_version(&name) else {
+                continue;
+            };
+            found.push(PhpInstallation {
+                version: version.clone(),
+                binary_path: entry.path(),
+                fpm_socket: existing_fpm_socket(&version),
+            });
+        }
+    }
+    found
+}
+
+/// `phpN.M` (Fedora's `php`, `php-fpm`) or `phpN.M-fpm` names a version;
+/// bare `php`/`php-fpm` doesn't.
+fn versioned_php_binary_version(name: &str) -> Option<String> {
+    let rest = name.strip_prefix("php")?;
+    let rest = rest.strip_suffix("-fpm").unwrap_or(rest);
+    let mut parts = rest.splitn(2, '.');
+    let major = parts.next()?;
+    let minor = parts.next()?;
+    if !major.is_empty()
+        && major.chars().all(|c| c.is_ascii_digit())
+        && !minor.is_empty()
+        && minor.chars().all(|c| c.is_ascii_digit())
+    {
+        Some(format!("{major}.{minor}"))
+    } else {
+        None
+    }
+}
+
+/// phpenv and asdf both lay out installed versions as
+/// `<root>/versions-or-installs/<version>/bin/php`, named after the exact
+/// version installed rather than mini's `major.minor` convention.
+fn discover_version_managers() -> Vec<PhpInstallation> {
+    let Some(home) = std::env::var_os("HOME") else {
+        return Vec::new();
+    };
+    let home = PathBuf::from(home);
+    let mut found = Vec::new();
+    for versions_dir in [
+        home.join(".phpenv").join("versions"),
+        home.join(".asdf").join("installs").join("php"),
+    ] {
+        let Ok(entries) = std::fs::read_dir(&versions_dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let binary_path = entry.path().join("bin").join("php");
+            if !binary_path.exists() {
+                continue;
+            }
+            let version = entry.file_name().to_string_lossy().into_owned();
+            found.push(PhpInstallation {
+                version,
+                binary_path,
+                fpm_socket: None,
+            });
+        }
+    }
+    found
+}
+
+/// Whatever `php` resolves to on PATH, asked for its own version rather
+/// than guessed from a file name.
+fn discover_path() -> Option<PhpInstallation> {
+    let path_var = std::env::var_os("PATH")?;
+    for dir in std::env::split_paths(&path_var) {
+        let candidate = dir.join("php");
+        if !candidate.is_file() {
+            continue;
+        }
+        let version = php_version_of_binary(&candidate)?;
+        return Some(PhpInstallation {
+            version,
+            binary_path: candidate,
+            fpm_socket: None,
+        });
+    }
+    None
+}
+
+/// Run `php -r 'echo PHP_VERSION;'` to ask a binary its own version,
+/// rather than trying to infer it from the binary's path.
+fn php_version_of_binary(binary: &Path) -> Option<String> {
+    let output = Command::new(binary).arg("-r").arg("echo PHP_VERSION;").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let version = String::from_utf8(output.stdout).ok()?;
+    let version = version.trim();
+    if version.is_empty() {
+        None
+    } else {
+        Some(version.to_string())
+    }
+}
+
+/// The first of [`php_fpm_socket_candidates`] that already exists, i.e. a
+/// pool this PHP install's own package already set up and is running.
+fn existing_fpm_socket(version: &str) -> Option<PathBuf> {
+    php_fpm_socket_candidates(version)
+        .into_iter()
+        .map(PathBuf::from)
+        .find(|candidate| candidate.exists())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versioned_php_binary_version_accepts_major_minor_names() {
+        assert_eq!(versioned_php_binary_version("php8.2"), Some("8.2".to_string()));
+        assert_eq!(versioned_php_binary_version("php8.2-fpm"), Some("8.2".to_string()));
+    }
+
+    #[test]
+    fn test_versioned_php_binary_version_rejects_unversioned_or_unrelated_names() {
+        assert_eq!(versioned_php_binary_version("php"), None);
+        assert_eq!(versioned_php_binary_version("php-fpm"), None);
+        assert_eq!(versioned_php_binary_version("phpize"), None);
+        assert_eq!(versioned_php_binary_version("python3.11"), None);
+    }
+
+    #[test]
+    fn test_discover_version_managers_finds_phpenv_style_installs() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let versions_dir = temp_dir.path().join(".phpenv").join("versions").join("8.2.10");
+        std::fs::create_dir_all(versions_dir.join("bin")).unwrap();
+        std::fs::write(versions_dir.join("bin").join("php"), "").unwrap();
+
+        let previous_home = std::env::var_os("HOME");
+        std::env::set_var("HOME", temp_dir.path());
+        let found = discover_version_managers();
+        if let Some(home) = previous_home {
+            std::env::set_var("HOME", home);
+        }
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].version, "8.2.10");
+        assert!(found[0].fpm_socket.is_none());
+    }
+
+    #[test]
+    fn test_discover_deduplicates_versions_across_sources() {
+        // discover() itself only runs real filesystem probes, but the
+        // dedup logic it applies is what this test exercises directly.
+        let duplicated = vec![
+            PhpInstallation {
+                version: "8.2".to_string(),
+                binary_path: PathBuf::from("/opt/homebrew/opt/php@8.2/bin/php"),
+                fpm_socket: None,
+            },
+            PhpInstallation {
+                version: "8.2".to_string(),
+                binary_path: PathBuf::from("/usr/bin/php8.2"),
+                fpm_socket: None,
+            },
+        ];
+        let mut seen = HashSet::new();
+        let deduped: Vec<_> = duplicated
+            .into_iter()
+            .filter(|installation| seen.insert(installation.version.clone()))
+            .collect();
+        assert_eq!(deduped.len(), 1);
+    }
+}
@@ -0,0 +1,239 @@
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use hyper::{Body, Client, Request};
+use tokio::io::AsyncReadExt;
+use tokio::process::{Child, Command};
+use tokio::time::sleep;
+
+use crate::fastcgi::{self, FastCgiRequest, FastCgiResponse, RequestBody};
+
+/// Find `php` on PATH, for machines that only have the CLI binary
+/// installed and no php-fpm - the fallback [`PoolManager::ensure_running`]
+/// reaches for once no php-fpm binary can be found for a version.
+///
+/// [`PoolManager::ensure_running`]: crate::php_fpm::PoolManager::ensure_running
+fn locate_binary() -> Result<PathBuf> {
+    let path_var = std::env::var_os("PATH").context("PATH is not set")?;
+    std::env::split_paths(&path_var)
+        .map(|dir| dir.join("php"))
+        .find(|candidate| candidate.is_file())
+        .ok_or_else(|| anyhow::anyhow!("no php binary found on PATH"))
+}
+
+/// A `php -S` development server running for one site's document root,
+/// stood up as a managed child process when no php-fpm binary is
+/// available for the site's PHP version.
+pub struct BuiltinServer {
+    pub addr: SocketAddr,
+    process: Child,
+}
+
+impl BuiltinServer {
+    /// Spawn `php -S 127.0.0.1:<port> -t <docroot>` on a free loopback
+    /// port and wait for it to start accepting connections. The port
+    /// itself is reserved up front rather than left to php to pick, so
+    /// the caller has somewhere to reverse-proxy to as soon as this
+    /// returns.
+    pub async fn spawn(docroot: &Path) -> Result<Self> {
+        let binary = locate_binary()?;
+        let addr = reserve_loopback_port()?;
+
+        let process = Command::new(binary)
+            .arg("-S")
+            .arg(addr.to_string())
+            .arg("-t")
+            .arg(docroot)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .context("failed to launch the PHP built-in server")?;
+
+        wait_for_port(addr).await?;
+        Ok(BuiltinServer { addr, process })
+    }
+
+    /// Whether the server is still running, for [`PoolManager`][pm] to
+    /// decide whether to reuse it or spawn a fresh one.
+    ///
+    /// [pm]: crate::php_fpm::PoolManager
+    pub fn is_alive(&mut self) -> bool {
+        matches!(self.process.try_wait(), Ok(None))
+    }
+}
+
+/// Bind an OS-assigned loopback port, then release it immediately. `php
+/// -S` takes a concrete port on its command line, so this stands in for
+/// the ":0 = any free port" convention a listener that's about to be
+/// handed to another process can't express on its own.
+fn reserve_loopback_port() -> Result<SocketAddr> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").context("failed to reserve a loopback port")?;
+    listener.local_addr().map_err(Into::into)
+}
+
+async fn wait_for_port(addr: SocketAddr) -> Result<()> {
+    for _ in 0..50 {
+        if tokio::net::TcpStream::connect(addr).await.is_ok() {
+            return Ok(());
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    anyhow::bail!("PHP built-in server did not start listening on {addr}")
+}
+
+/// Reverse-proxy `request` to a PHP built-in server listening at `addr`,
+/// translating it to and from the same [`FastCgiRequest`]/
+/// [`FastCgiResponse`] shapes [`fastcgi::send`] uses, so callers don't
+/// need to care which backend actually served the request.
+pub async fn send(addr: SocketAddr, request: &mut FastCgiRequest) -> Result<FastCgiResponse> {
+    let client = Client::new();
+    let body = resolve_body(&mut request.body).await?;
+    let http_request = build_http_request(addr, request, body)?;
+    let timeout = fastcgi::response_timeout(request);
+
+    let response = match tokio::time::timeout(timeout, client.request(http_request)).await {
+        Ok(result) => result.context("PHP built-in server request failed")?,
+        Err(_) => anyhow::bail!("PHP built-in server did not respond within {timeout:?}"),
+    };
+
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_str().unwrap_or_default().to_string()))
+        .collect();
+    let body = hyper::body::to_bytes(response.into_body()).await?.to_vec();
+
+    Ok(FastCgiResponse { status, headers, body, stderr: Vec::new() })
+}
+
+/// The built-in server is only ever a local-dev fallback for when no
+/// php-fpm binary is installed, so unlike the primary FastCGI path it
+/// isn't worth threading a true streaming body through hyper for - a
+/// [`RequestBody::Stream`] is simply read to completion here first.
+async fn resolve_body(body: &mut RequestBody) -> Result<Body> {
+    match body {
+        RequestBody::Bytes(bytes) => Ok(Body::from(bytes.clone())),
+        RequestBody::Stream { reader, .. } => {
+            let mut buffer = Vec::new();
+            reader.read_to_end(&mut buffer).await?;
+            Ok(Body::from(buffer))
+        }
+    }
+}
+
+fn build_http_request(addr: SocketAddr, request: &FastCgiRequest, body: Body) -> Result<Request<Body>> {
+    let mut path = "/".to_string();
+    if !request.query_string.is_empty() {
+        path.push('?');
+        path.push_str(&request.query_string);
+    }
+
+    let mut builder = Request::builder().method(request.method.as_str()).uri(format!("http://{addr}{path}"));
+    for (name, value) in &request.headers {
+        builder = builder.header(name, value);
+    }
+    if let Some(content_type) = &request.content_type {
+        builder = builder.header("Content-Type", content_type);
+    }
+    builder.body(body).context("failed to build the proxied request")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Response, Server};
+
+    #[test]
+    fn test_build_http_request_sends_the_method_and_appends_the_query_string() {
+        let addr: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+        let request = FastCgiRequest {
+            method: "POST".to_string(),
+            query_string: "page=2".to_string(),
+            ..Default::default()
+        };
+        let http_request = build_http_request(addr, &request, Body::empty()).unwrap();
+        assert_eq!(http_request.method(), "POST");
+        assert_eq!(http_request.uri().to_string(), "http://127.0.0.1:9000/?page=2");
+    }
+
+    #[test]
+    fn test_build_http_request_omits_the_query_separator_when_there_is_none() {
+        let addr: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+        let request = FastCgiRequest::default();
+        let http_request = build_http_request(addr, &request, Body::empty()).unwrap();
+        assert_eq!(http_request.uri().to_string(), "http://127.0.0.1:9000/");
+    }
+
+    #[test]
+    fn test_build_http_request_forwards_headers_and_content_type() {
+        let addr: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+        let request = FastCgiRequest {
+            headers: vec![("X-Request-Id".to_string(), "abc".to_string())],
+            content_type: Some("application/json".to_string()),
+            ..Default::default()
+        };
+        let http_request = build_http_request(addr, &request, Body::empty()).unwrap();
+        assert_eq!(http_request.headers().get("X-Request-Id").unwrap(), "abc");
+        assert_eq!(http_request.headers().get("Content-Type").unwrap(), "application/json");
+    }
+
+    #[test]
+    fn test_reserve_loopback_port_returns_a_loopback_address_with_a_real_port() {
+        let addr = reserve_loopback_port().unwrap();
+        assert!(addr.ip().is_loopback());
+        assert_ne!(addr.port(), 0);
+    }
+
+    /// Exercises [`send`] end to end against a real (in-process) HTTP
+    /// server standing in for `php -S`, rather than spawning a process -
+    /// this is what actually needs to round-trip correctly, independent
+    /// of whether `php` is installed wherever the tests run.
+    #[tokio::test]
+    async fn test_send_reverse_proxies_a_request_and_returns_the_response() {
+        let make_service = make_service_fn(|_conn| async {
+            Ok::<_, Infallible>(service_fn(|req: Request<Body>| async move {
+                let echoed = format!("{} {}", req.method(), req.uri());
+                Ok::<_, Infallible>(Response::new(Body::from(echoed)))
+            }))
+        });
+        let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+
+        let mut request = FastCgiRequest {
+            method: "GET".to_string(),
+            query_string: "a=1".to_string(),
+            ..Default::default()
+        };
+        let response = send(addr, &mut request).await.unwrap();
+
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, format!("GET http://{addr}/?a=1").as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_body_drains_a_streaming_body_into_bytes() {
+        let mut body = RequestBody::from_stream(std::io::Cursor::new(b"uploaded bytes".to_vec()), 14);
+        let resolved = resolve_body(&mut body).await.unwrap();
+        let bytes = hyper::body::to_bytes(resolved).await.unwrap();
+        assert_eq!(bytes.as_ref(), b"uploaded bytes");
+    }
+
+    #[test]
+    fn test_locate_binary_errors_when_php_is_not_on_path() {
+        let previous_path = std::env::var_os("PATH");
+        std::env::set_var("PATH", "");
+        let result = locate_binary();
+        if let Some(path) = previous_path {
+            std::env::set_var("PATH", path);
+        }
+        assert!(result.is_err());
+    }
+}
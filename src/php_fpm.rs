@@ -0,0 +1,537 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use tracing::{info, warn};
+use nix::sys::signal::{self, Signal};
+use nix::unistd::Pid;
+use prometheus::{register_int_counter_vec, IntCounterVec};
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+use crate::fastcgi::{self, FastCgiAddr, FastCgiRequest};
+use crate::php_builtin::BuiltinServer;
+
+/// The path php-fpm's own ping handler answers directly (configured via
+/// `ping.path` in [`pool_config`]), without invoking PHP - used by
+/// [`PoolManager::run_health_checks`] to tell a wedged pool from a
+/// healthy one.
+const PING_PATH: &str = "/mini-ping";
+const PING_RESPONSE: &[u8] = b"pong";
+
+/// How often [`PoolManager::run_health_checks`] pings every pool it's
+/// managing.
+pub const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How long [`PoolManager::shutdown_all`] waits for a php-fpm master to
+/// exit after asking it to (`SIGQUIT`) before giving up and killing it.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+lazy_static! {
+    static ref HEALTH_CHECK_FAILURES: IntCounterVec = register_int_counter_vec!(
+        "mini_php_fpm_health_check_failures_total",
+        "Number of failed health checks against a managed PHP-FPM pool",
+        &["pool"]
+    )
+    .unwrap();
+    static ref POOL_RESTARTS: IntCounterVec = register_int_counter_vec!(
+        "mini_php_fpm_pool_restarts_total",
+        "Number of times a PHP-FPM pool was restarted after failing its health check",
+        &["pool"]
+    )
+    .unwrap();
+}
+
+/// Where a request for a site's PHP version ended up being served: a
+/// real php-fpm pool over FastCGI, or (when no php-fpm binary is
+/// installed for that version) a `php -S` built-in server over plain
+/// HTTP.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Backend {
+    FastCgi(FastCgiAddr),
+    Http(SocketAddr),
+}
+
+/// Where a php-fpm binary for a given version might be installed, checked
+/// in order. Mirrors the socket-location candidates `mini status` probes
+/// in [`crate::status`], but for the binary itself rather than a pool it
+/// might already be running.
+fn php_fpm_binary_candidates(version: &str) -> [String; 3] {
+    [
+        format!("/usr/sbin/php-fpm{version}"),
+        format!("/usr/local/sbin/php-fpm{version}"),
+        format!("/opt/homebrew/opt/php@{version}/sbin/php-fpm"),
+    ]
+}
+
+fn locate_binary(version: &str) -> Result<PathBuf> {
+    php_fpm_binary_candidates(version)
+        .into_iter()
+        .map(PathBuf::from)
+        .find(|candidate| candidate.exists())
+        .ok_or_else(|| anyhow::anyhow!("no php-fpm binary found for PHP {version}"))
+}
+
+/// Where pool configs and sockets live by default, alongside mini's own
+/// config file.
+pub fn default_runtime_dir() -> PathBuf {
+    crate::cli::default_config_path()
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("php-fpm")
+}
+
+/// A minimal php-fpm pool config: one pool named after the version,
+/// listening on its own unix socket, with conservative process-manager
+/// defaults suitable for a handful of local sites. `xdebug` adds the
+/// `env[XDEBUG_CONFIG]` directive sites isolated into the debug pool need
+/// to pick up a step-debugging session.
+fn pool_config(pool_name: &str, socket_path: &Path, xdebug: bool) -> String {
+    let mut config = format!(
+        "[{pool_name}]\n\
+         listen = {}\n\
+         pm = dynamic\n\
+         pm.max_children = 5\n\
+         pm.start_servers = 1\n\
+         pm.min_spare_servers = 1\n\
+         pm.max_spare_servers = 3\n\
+         ping.path = {PING_PATH}\n\
+         ping.response = {}\n",
+        socket_path.display(),
+        String::from_utf8_lossy(PING_RESPONSE),
+    );
+    if xdebug {
+        config.push_str("env[XDEBUG_CONFIG] = \"idekey=mini\"\n");
+    }
+    config
+}
+
+/// The pool key a version/xdebug combination is cached and configured
+/// under - sites with Xdebug enabled get their own pool per version so
+/// enabling it for one site doesn't turn it on for every other site
+/// sharing that PHP version.
+fn pool_name(version: &str, xdebug: bool) -> String {
+    if xdebug {
+        format!("mini-{version}-xdebug")
+    } else {
+        format!("mini-{version}")
+    }
+}
+
+async fn wait_for_socket(socket_path: &Path) -> Result<()> {
+    for _ in 0..50 {
+        if socket_path.exists() {
+            return Ok(());
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    anyhow::bail!("php-fpm did not create its socket at {}", socket_path.display())
+}
+
+/// A single running per-version pool: either a php-fpm process listening
+/// on its own unix socket, or - when no php-fpm binary could be found
+/// for the version - a `php -S` built-in server standing in for it.
+enum Pool {
+    Fpm { socket_path: PathBuf, process: Child },
+    Builtin(BuiltinServer),
+}
+
+impl Pool {
+    fn backend(&self) -> Backend {
+        match self {
+            Pool::Fpm { socket_path, .. } => Backend::FastCgi(FastCgiAddr::Unix(socket_path.clone())),
+            Pool::Builtin(server) => Backend::Http(server.addr),
+        }
+    }
+
+    fn is_alive(&mut self) -> bool {
+        match self {
+            Pool::Fpm { process, .. } => matches!(process.try_wait(), Ok(None)),
+            Pool::Builtin(server) => server.is_alive(),
+        }
+    }
+}
+
+/// A running pool plus what's needed to restart it identically if a
+/// health check finds it dead or wedged: the version/xdebug combination
+/// it was started for, and (for the built-in-server fallback) the
+/// docroot it's serving.
+struct PoolEntry {
+    pool: Pool,
+    version: String,
+    xdebug: bool,
+    docroot: PathBuf,
+}
+
+/// Ping php-fpm's built-in ping handler (configured via `ping.path` in
+/// [`pool_config`]), which answers directly without invoking PHP - a
+/// process that's still running but wedged (e.g. every worker stuck on a
+/// slow request) will fail to answer this even though [`Pool::is_alive`]
+/// still reports it as running.
+async fn ping(addr: &FastCgiAddr) -> Result<()> {
+    let mut request = FastCgiRequest {
+        method: "GET".to_string(),
+        script_name: PING_PATH.to_string(),
+        ..Default::default()
+    };
+    let response = fastcgi::send(addr, &mut request).await?;
+    if response.body == PING_RESPONSE {
+        Ok(())
+    } else {
+        anyhow::bail!("unexpected ping response: {:?}", response.body)
+    }
+}
+
+/// Starts and reuses one php-fpm pool per PHP version under mini's own
+/// runtime dir, so every site isolated to the same version shares a pool
+/// instead of spawning one per site.
+pub struct PoolManager {
+    runtime_dir: PathBuf,
+    pools: Mutex<HashMap<String, PoolEntry>>,
+}
+
+impl PoolManager {
+    pub fn new(runtime_dir: PathBuf) -> Self {
+        PoolManager {
+            runtime_dir,
+            pools: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Ensure a backend for `version` (with or without Xdebug enabled) is
+    /// running and return where to reach it, starting one if it isn't
+    /// already running (or if it died since it was last used). Falls back
+    /// to a `php -S` built-in server rooted at `docroot` when no php-fpm
+    /// binary can be found for `version`, so mini still works on machines
+    /// with only the PHP CLI installed.
+    pub async fn ensure_running(&self, version: &str, xdebug: bool, docroot: &Path) -> Result<Backend> {
+        let key = pool_name(version, xdebug);
+        let mut pools = self.pools.lock().await;
+
+        if let Some(entry) = pools.get_mut(&key) {
+            if entry.pool.is_alive() {
+                return Ok(entry.pool.backend());
+            }
+            pools.remove(&key);
+        }
+
+        let entry = self.spawn_entry(&key, version, xdebug, docroot).await?;
+        let backend = entry.pool.backend();
+        pools.insert(key, entry);
+        Ok(backend)
+    }
+
+    async fn spawn_entry(&self, key: &str, version: &str, xdebug: bool, docroot: &Path) -> Result<PoolEntry> {
+        let pool = match locate_binary(version) {
+            Ok(binary) => self.spawn_pool(key, version, &binary, xdebug).await?,
+            Err(_) => Pool::Builtin(BuiltinServer::spawn(docroot).await.with_context(|| {
+                format!("no php-fpm binary found for PHP {version}, and the PHP built-in server fallback failed")
+            })?),
+        };
+        Ok(PoolEntry { pool, version: version.to_string(), xdebug, docroot: docroot.to_path_buf() })
+    }
+
+    /// Ping every pool currently running and restart any that are dead or
+    /// fail to answer the ping (wedged), logging and counting both
+    /// outcomes so a crashed/stuck php-fpm shows up in `mini`'s metrics
+    /// rather than silently 502ing every site on that version until
+    /// someone notices and restarts it by hand. Intended to be run on a
+    /// timer (see [`HEALTH_CHECK_INTERVAL`]) for the lifetime of the
+    /// daemon.
+    pub async fn run_health_checks(&self) {
+        let keys: Vec<String> = self.pools.lock().await.keys().cloned().collect();
+        for key in keys {
+            if let Err(error) = self.check_and_restart(&key).await {
+                warn!("health check failed for php-fpm pool {key}: {error}");
+            }
+        }
+    }
+
+    async fn check_and_restart(&self, key: &str) -> Result<()> {
+        let mut pools = self.pools.lock().await;
+        let Some(entry) = pools.get_mut(key) else {
+            return Ok(());
+        };
+
+        let healthy = if !entry.pool.is_alive() {
+            false
+        } else {
+            match entry.pool.backend() {
+                // The built-in server fallback has no ping endpoint to
+                // speak of - process liveness, already checked above, is
+                // all there is to go on.
+                Backend::FastCgi(addr) => ping(&addr).await.is_ok(),
+                Backend::Http(_) => true,
+            }
+        };
+
+        if healthy {
+            return Ok(());
+        }
+
+        HEALTH_CHECK_FAILURES.with_label_values(&[key]).inc();
+        warn!("php-fpm pool {key} failed its health check, restarting it");
+
+        let (version, xdebug, docroot) = (entry.version.clone(), entry.xdebug, entry.docroot.clone());
+        pools.remove(key);
+        let restarted = self.spawn_entry(key, &version, xdebug, &docroot).await?;
+        pools.insert(key.to_string(), restarted);
+
+        POOL_RESTARTS.with_label_values(&[key]).inc();
+        info!("restarted php-fpm pool {key} after a failed health check");
+        Ok(())
+    }
+
+    async fn spawn_pool(&self, key: &str, version: &str, binary: &Path, xdebug: bool) -> Result<Pool> {
+        std::fs::create_dir_all(&self.runtime_dir)?;
+        let socket_path = self.runtime_dir.join(format!("{key}.sock"));
+        let config_path = self.runtime_dir.join(format!("{key}.conf"));
+        // A pool that was killed uncleanly can leave its socket behind;
+        // php-fpm refuses to bind over a stale one.
+        let _ = std::fs::remove_file(&socket_path);
+
+        std::fs::write(&config_path, pool_config(key, &socket_path, xdebug))
+            .with_context(|| format!("failed to write pool config for PHP {version}"))?;
+
+        let log_path = self.runtime_dir.join(format!("{key}.log"));
+        let stdout_log = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)
+            .with_context(|| format!("failed to open log file at {}", log_path.display()))?;
+        let stderr_log = stdout_log.try_clone().context("failed to duplicate the pool log file handle")?;
+
+        let process = Command::new(binary)
+            .args(["--nodaemonize", "--fpm-config"])
+            .arg(&config_path)
+            .stdout(Stdio::from(stdout_log))
+            .stderr(Stdio::from(stderr_log))
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("failed to launch {}", binary.display()))?;
+
+        wait_for_socket(&socket_path).await?;
+        Ok(Pool::Fpm { socket_path, process })
+    }
+
+    /// Gracefully stop every pool this manager is running, for the daemon's
+    /// own shutdown path: each php-fpm master is sent `SIGQUIT` (its own
+    /// graceful-shutdown signal - finish in-flight requests, then exit) and
+    /// given [`SHUTDOWN_TIMEOUT`] to act on it, rather than left to be
+    /// reaped by `kill_on_drop`'s unconditional `SIGKILL` or orphaned
+    /// outright if the daemon process just exits. Built-in `php -S`
+    /// fallback servers have no graceful shutdown of their own, so they're
+    /// just dropped, which falls back to `kill_on_drop`.
+    pub async fn shutdown_all(&self) {
+        let mut pools = self.pools.lock().await;
+        for (key, entry) in pools.drain() {
+            if let Pool::Fpm { mut process, .. } = entry.pool {
+                shutdown_fpm_process(&key, &mut process).await;
+            }
+        }
+    }
+}
+
+async fn shutdown_fpm_process(key: &str, process: &mut Child) {
+    let Some(pid) = process.id() else {
+        return;
+    };
+    if let Err(error) = signal::kill(Pid::from_raw(pid as i32), Signal::SIGQUIT) {
+        warn!("failed to signal php-fpm pool {key} to shut down: {error}");
+        return;
+    }
+    if tokio::time::timeout(SHUTDOWN_TIMEOUT, process.wait()).await.is_err() {
+        warn!("php-fpm pool {key} did not exit within {SHUTDOWN_TIMEOUT:?}, killing it");
+        let _ = process.kill().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_locate_binary_errors_when_nothing_is_installed() {
+        assert!(locate_binary("99.99").is_err());
+    }
+
+    #[test]
+    fn test_pool_config_sets_the_listen_directive_to_the_socket_path() {
+        let config = pool_config("mini-8.2", Path::new("/tmp/php8.2-fpm.sock"), false);
+        assert!(config.contains("[mini-8.2]"));
+        assert!(config.contains("listen = /tmp/php8.2-fpm.sock"));
+        assert!(config.contains("ping.path = /mini-ping"));
+        assert!(!config.contains("XDEBUG_CONFIG"));
+    }
+
+    #[test]
+    fn test_pool_config_adds_xdebug_config_env_when_enabled() {
+        let config = pool_config("mini-8.2-xdebug", Path::new("/tmp/php8.2-xdebug-fpm.sock"), true);
+        assert!(config.contains("env[XDEBUG_CONFIG]"));
+    }
+
+    #[test]
+    fn test_pool_name_distinguishes_xdebug_pools_from_plain_ones() {
+        assert_ne!(pool_name("8.2", false), pool_name("8.2", true));
+    }
+
+    /// Write a fake php-fpm binary that touches the socket path it finds in
+    /// the `--fpm-config` file it's given, then stays alive - standing in
+    /// for a real pool so `ensure_running`/reuse logic can be tested
+    /// without depending on php-fpm being installed.
+    fn write_fake_php_fpm(dir: &Path) -> PathBuf {
+        let script = dir.join("fake-php-fpm.sh");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\n\
+             sleep 0.1\n\
+             listen=$(grep '^listen' \"$3\" | cut -d'=' -f2 | xargs)\n\
+             touch \"$listen\"\n\
+             sleep 5\n",
+        )
+        .unwrap();
+        let mut perms = std::fs::metadata(&script).unwrap().permissions();
+        perms.set_mode(0o755);
+        std::fs::set_permissions(&script, perms).unwrap();
+        script
+    }
+
+    #[tokio::test]
+    async fn test_spawn_pool_waits_for_the_socket_then_reuses_it() {
+        let dir = TempDir::new().unwrap();
+        let manager = PoolManager::new(dir.path().join("runtime"));
+        let binary = write_fake_php_fpm(dir.path());
+
+        let addr = manager.ensure_running_with_binary("8.2", false, &binary).await.unwrap();
+        let Backend::FastCgi(FastCgiAddr::Unix(socket_path)) = &addr else {
+            panic!("expected a unix socket address");
+        };
+        assert!(socket_path.exists());
+
+        // A second call while the pool is still alive reuses it rather
+        // than spawning a second php-fpm process.
+        let again = manager.ensure_running_with_binary("8.2", false, &binary).await.unwrap();
+        assert_eq!(addr, again);
+    }
+
+    #[tokio::test]
+    async fn test_xdebug_pool_is_separate_from_the_plain_pool() {
+        let dir = TempDir::new().unwrap();
+        let manager = PoolManager::new(dir.path().join("runtime"));
+        let binary = write_fake_php_fpm(dir.path());
+
+        let plain = manager.ensure_running_with_binary("8.2", false, &binary).await.unwrap();
+        let debug = manager.ensure_running_with_binary("8.2", true, &binary).await.unwrap();
+        assert_ne!(plain, debug);
+    }
+
+    #[tokio::test]
+    async fn test_run_health_checks_counts_a_pool_that_is_alive_but_unreachable() {
+        let dir = TempDir::new().unwrap();
+        let manager = PoolManager::new(dir.path().join("runtime"));
+        let binary = write_fake_php_fpm(dir.path());
+        // A version string unique to this test, so its counter label
+        // doesn't collide with other tests exercising the same global
+        // prometheus registry.
+        let version = "99.99-wedged-test";
+
+        manager.ensure_running_with_binary(version, false, &binary).await.unwrap();
+        let key = pool_name(version, false);
+        let failures_before = HEALTH_CHECK_FAILURES.with_label_values(&[&key]).get();
+
+        // `write_fake_php_fpm` only touches its socket path as a plain
+        // file rather than actually binding it, so the process is alive
+        // but unreachable - a health check should treat that as a failed
+        // ping, the same as a genuinely wedged pool.
+        manager.run_health_checks().await;
+
+        assert_eq!(HEALTH_CHECK_FAILURES.with_label_values(&[&key]).get(), failures_before + 1);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_all_stops_running_pools_and_clears_them() {
+        let dir = TempDir::new().unwrap();
+        let manager = PoolManager::new(dir.path().join("runtime"));
+        let binary = write_fake_php_fpm(dir.path());
+
+        manager.ensure_running_with_binary("8.2", false, &binary).await.unwrap();
+        assert!(!manager.pools.lock().await.is_empty());
+
+        manager.shutdown_all().await;
+
+        assert!(manager.pools.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_spawn_pool_captures_stdout_and_stderr_to_a_log_file() {
+        let dir = TempDir::new().unwrap();
+        let manager = PoolManager::new(dir.path().join("runtime"));
+        let script = dir.path().join("noisy-php-fpm.sh");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\n\
+             echo starting up\n\
+             echo a warning >&2\n\
+             listen=$(grep '^listen' \"$3\" | cut -d'=' -f2 | xargs)\n\
+             touch \"$listen\"\n\
+             sleep 5\n",
+        )
+        .unwrap();
+        let mut perms = std::fs::metadata(&script).unwrap().permissions();
+        perms.set_mode(0o755);
+        std::fs::set_permissions(&script, perms).unwrap();
+
+        manager.ensure_running_with_binary("8.2", false, &script).await.unwrap();
+        let log_path = dir.path().join("runtime").join(format!("{}.log", pool_name("8.2", false)));
+
+        // The log may still be arriving when we check, since the script
+        // writes asynchronously to the pool's startup - give it a moment.
+        sleep(Duration::from_millis(200)).await;
+        let log = std::fs::read_to_string(&log_path).unwrap();
+        assert!(log.contains("starting up"));
+        assert!(log.contains("a warning"));
+    }
+
+    #[tokio::test]
+    async fn test_ensure_running_errors_cleanly_when_neither_fpm_nor_a_php_cli_are_installed() {
+        // No real php-fpm or php binary exists in tests, so the fallback
+        // to the built-in server should also fail - this should error
+        // cleanly rather than hang trying to spawn anything.
+        let dir = TempDir::new().unwrap();
+        let manager = PoolManager::new(dir.path().join("runtime"));
+        let docroot = dir.path().join("public");
+        std::fs::create_dir_all(&docroot).unwrap();
+
+        assert!(manager.ensure_running("99.99", false, &docroot).await.is_err());
+    }
+
+    impl PoolManager {
+        /// Test-only entry point that skips `locate_binary`, so tests can
+        /// inject a fake binary instead of depending on a real php-fpm
+        /// install.
+        async fn ensure_running_with_binary(&self, version: &str, xdebug: bool, binary: &Path) -> Result<Backend> {
+            let key = pool_name(version, xdebug);
+            let mut pools = self.pools.lock().await;
+
+            if let Some(entry) = pools.get_mut(&key) {
+                if entry.pool.is_alive() {
+                    return Ok(entry.pool.backend());
+                }
+                pools.remove(&key);
+            }
+
+            let pool = self.spawn_pool(&key, version, binary, xdebug).await?;
+            let backend = pool.backend();
+            let docroot = self.runtime_dir.clone();
+            pools.insert(key, PoolEntry { pool, version: version.to_string(), xdebug, docroot });
+            Ok(backend)
+        }
+    }
+}
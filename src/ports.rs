@@ -0,0 +1,176 @@
+use std::net::{SocketAddr, TcpListener, UdpSocket};
+use std::process::Command;
+
+use anyhow::Result;
+use tracing::{info, warn};
+
+/// Whether a port strategy should probe/forward for TCP or UDP traffic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Tcp,
+    Udp,
+}
+
+impl Protocol {
+    fn label(&self) -> &'static str {
+        match self {
+            Protocol::Tcp => "tcp",
+            Protocol::Udp => "udp",
+        }
+    }
+}
+
+/// Where a privileged-port service ended up actually listening.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundPort {
+    pub requested: SocketAddr,
+    pub actual: SocketAddr,
+}
+
+impl BoundPort {
+    /// True if the requested privileged port wasn't available and a high
+    /// port was used instead.
+    pub fn fell_back(&self) -> bool {
+        self.actual.port() != self.requested.port()
+    }
+}
+
+fn can_bind(addr: SocketAddr, protocol: Protocol) -> bool {
+    match protocol {
+        Protocol::Tcp => TcpListener::bind(addr).is_ok(),
+        Protocol::Udp => UdpSocket::bind(addr).is_ok(),
+    }
+}
+
+/// Decide which address a privileged-port service should actually bind:
+/// `requested` itself if that's bindable right now (running as root, or
+/// granted CAP_NET_BIND_SERVICE — see `systemd::unit_contents`'s
+/// `AmbientCapabilities=`), or `fallback_port` on the same host with
+/// best-effort OS port forwarding from `requested`, so installation works
+/// without running the whole daemon as root.
+pub fn choose_listen_addr(requested: SocketAddr, fallback_port: u16, protocol: Protocol) -> BoundPort {
+    if requested.port() == fallback_port || can_bind(requested, protocol) {
+        return BoundPort {
+            requested,
+            actual: requested,
+        };
+    }
+
+    warn!(
+        "could not bind {requested} ({}) without elevated privileges; falling back to port {fallback_port}",
+        protocol.label()
+    );
+    let actual = SocketAddr::new(requested.ip(), fallback_port);
+    configure_port_forward(requested.port(), fallback_port, protocol);
+    BoundPort { requested, actual }
+}
+
+/// Best-effort: ask the OS firewall to forward `from_port` to `to_port`, so
+/// the service stays reachable on the port users expect even though it's
+/// actually listening on a high port. Failures are logged, not fatal —
+/// mini still works on the fallback port either way.
+fn configure_port_forward(from_port: u16, to_port: u16, protocol: Protocol) {
+    let result = if cfg!(target_os = "linux") {
+        run_iptables_forward(from_port, to_port, protocol)
+    } else if cfg!(target_os = "macos") {
+        log_pf_forward(from_port, to_port, protocol)
+    } else if cfg!(target_os = "windows") {
+        run_netsh_forward(from_port, to_port)
+    } else {
+        Err(anyhow::anyhow!("no port-forwarding strategy for this platform"))
+    };
+
+    if let Err(e) = result {
+        warn!("could not set up port forwarding {from_port} -> {to_port}: {e}");
+    }
+}
+
+fn run_iptables_forward(from_port: u16, to_port: u16, protocol: Protocol) -> Result<()> {
+    run(Command::new("iptables").args([
+        "-t",
+        "nat",
+        "-A",
+        "PREROUTING",
+        "-p",
+        protocol.label(),
+        "--dport",
+        &from_port.to_string(),
+        "-j",
+        "REDIRECT",
+        "--to-port",
+        &to_port.to_string(),
+    ]))
+}
+
+/// pf rules normally live in `/etc/pf.conf` plus an anchor load, which
+/// isn't safe to rewrite automatically; log the rule an operator would add.
+fn log_pf_forward(from_port: u16, to_port: u16, protocol: Protocol) -> Result<()> {
+    info!(
+        "add to /etc/pf.conf: rdr pass on lo0 inet proto {} from any to any port {from_port} -> port {to_port}",
+        protocol.label()
+    );
+    Ok(())
+}
+
+fn run_netsh_forward(from_port: u16, to_port: u16) -> Result<()> {
+    run(Command::new("netsh").args([
+        "interface",
+        "portproxy",
+        "add",
+        "v4tov4",
+        &format!("listenport={from_port}"),
+        "listenaddress=0.0.0.0",
+        &format!("connectport={to_port}"),
+        "connectaddress=127.0.0.1",
+    ]))
+}
+
+fn run(command: &mut Command) -> Result<()> {
+    let status = command
+        .status()
+        .map_err(|e| anyhow::anyhow!("failed to run {command:?}: {e}"))?;
+    if !status.success() {
+        anyhow::bail!("{command:?} exited with {status}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose_listen_addr_uses_the_requested_port_when_available() {
+        let probe = TcpListener::bind("127.0.0.1:0").unwrap();
+        let requested = probe.local_addr().unwrap();
+        drop(probe);
+
+        let bound = choose_listen_addr(requested, requested.port() + 1, Protocol::Tcp);
+        assert!(!bound.fell_back());
+        assert_eq!(bound.actual, requested);
+    }
+
+    #[test]
+    fn test_choose_listen_addr_falls_back_when_the_requested_port_is_unavailable() {
+        let held = TcpListener::bind("127.0.0.1:0").unwrap();
+        let requested = held.local_addr().unwrap();
+
+        let probe = TcpListener::bind("127.0.0.1:0").unwrap();
+        let fallback_port = probe.local_addr().unwrap().port();
+        drop(probe);
+
+        let bound = choose_listen_addr(requested, fallback_port, Protocol::Tcp);
+        assert!(bound.fell_back());
+        assert_eq!(bound.actual.port(), fallback_port);
+    }
+
+    #[test]
+    fn test_choose_listen_addr_is_a_noop_when_requested_equals_fallback() {
+        let held = TcpListener::bind("127.0.0.1:0").unwrap();
+        let requested = held.local_addr().unwrap();
+
+        let bound = choose_listen_addr(requested, requested.port(), Protocol::Tcp);
+        assert!(!bound.fell_back());
+        assert_eq!(bound.actual, requested);
+    }
+}
@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use anyhow::Result;
+use tracing::info;
+use nix::unistd::{self, Gid, Group, Uid, User};
+
+use crate::config::ServerConfig;
+
+/// Drop from root to the account configured in `ServerConfig.user`/`group`,
+/// once privileged listeners are already bound. A no-op if `user` isn't
+/// set, or if we're not running as root (nothing to drop).
+pub fn drop_privileges(config: &ServerConfig) -> Result<()> {
+    if !unistd::getuid().is_root() {
+        return Ok(());
+    }
+
+    let Some(user) = &config.user else {
+        return Ok(());
+    };
+
+    let account = User::from_name(user)?.ok_or_else(|| anyhow::anyhow!("user {user} does not exist"))?;
+
+    let gid = match &config.group {
+        Some(group) => {
+            Group::from_name(group)?
+                .ok_or_else(|| anyhow::anyhow!("group {group} does not exist"))?
+                .gid
+        }
+        None => account.gid,
+    };
+
+    reown_configured_paths(config, account.uid, gid)?;
+
+    // Clear root's supplementary groups (commonly including gid 0) before
+    // touching the primary gid/uid — setgid/setuid alone leave those
+    // inherited groups in place, so a process that "dropped privileges"
+    // would still pass a `getgroups` check for root's group.
+    unistd::setgroups(&[gid])?;
+    // Drop the group before the uid — once the uid is dropped we may no
+    // longer have permission to change our group membership.
+    unistd::setgid(gid)?;
+    unistd::setuid(account.uid)?;
+    info!("dropped privileges to uid={} gid={}", account.uid, gid);
+    Ok(())
+}
+
+/// Re-own the cert/key/log/pid files `config` points at so the account
+/// we're about to drop to can still read and write them.
+fn reown_configured_paths(config: &ServerConfig, uid: Uid, gid: Gid) -> Result<()> {
+    let paths = [
+        &config.tls_cert_path,
+        &config.tls_key_path,
+        &config.error_log,
+        &config.pid_file,
+    ];
+
+    for path in paths.into_iter().flatten() {
+        if Path::new(path).exists() {
+            unistd::chown(path.as_str(), Some(uid), Some(gid))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop_privileges_is_a_noop_without_a_configured_user() {
+        let config = ServerConfig::default();
+        assert!(drop_privileges(&config).is_ok());
+    }
+
+    #[test]
+    fn test_drop_privileges_errors_for_an_unknown_user() {
+        if !unistd::getuid().is_root() {
+            // Dropping privileges only applies when started as root; skip
+            // under an unprivileged test runner.
+            return;
+        }
+
+        let mut config = ServerConfig::default();
+        config.user = Some("definitely-not-a-real-user".to_string());
+        assert!(drop_privileges(&config).is_err());
+    }
+
+    #[test]
+    fn test_drop_privileges_clears_inherited_supplementary_groups() {
+        if !unistd::getuid().is_root() {
+            // Dropping privileges only applies when started as root; skip
+            // under an unprivileged test runner.
+            return;
+        }
+
+        let mut config = ServerConfig::default();
+        config.user = Some("nobody".to_string());
+        drop_privileges(&config).unwrap();
+
+        // Root's supplementary groups (gid 0 included) should be gone,
+        // leaving only the account's own gid.
+        let groups = unistd::getgroups().unwrap();
+        assert!(!groups.contains(&Gid::from_raw(0)));
+    }
+}
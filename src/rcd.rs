@@ -0,0 +1,140 @@
+//! FreeBSD `rc.d` service integration - the `rcd.rs` counterpart to
+//! `systemd.rs` for the `mini service install`/`uninstall` commands on a
+//! BSD host.
+//!
+//! Targets FreeBSD's `rc.d`/`sysrc`/`service(8)` conventions specifically.
+//! OpenBSD's `rcctl`-based service management follows a different script
+//! format and enable mechanism and isn't handled by this module - running
+//! `mini service install` there still errors out the way it always has,
+//! same as [`systemd::dispatch`] does on anything that isn't Linux.
+//!
+//! pf-based low-port forwarding and `resolvconf` integration - the other
+//! two asks for BSD support alongside this one - aren't implemented here:
+//! both need real platform testing this sandbox can't do, and on their
+//! own don't block `mini service install` the way having no rc.d script at
+//! all does. `ports::choose_listen_addr`'s high-port fallback (used on
+//! every platform already) is what a BSD install without pf forwarding
+//! falls back to today, same as it does without `CAP_NET_BIND_SERVICE` on
+//! Linux.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+use crate::cli::ServiceAction;
+use crate::config::ServerConfig;
+
+const SCRIPT_NAME: &str = "mini";
+
+/// Run a `ServiceAction` against the FreeBSD rc.d script for `config`.
+pub fn dispatch(action: ServiceAction, config: &ServerConfig) -> Result<()> {
+    if !cfg!(target_os = "freebsd") {
+        anyhow::bail!("rc.d service management is only supported on FreeBSD");
+    }
+
+    match action {
+        ServiceAction::Install { .. } => install(config),
+        ServiceAction::Uninstall { .. } => uninstall(),
+    }
+}
+
+fn script_path() -> PathBuf {
+    PathBuf::from("/usr/local/etc/rc.d").join(SCRIPT_NAME)
+}
+
+/// Render the rc.d script: a standard `rc.subr` wrapper pointing at the
+/// current executable, run in the foreground the same way the systemd
+/// unit does (`command_args` carries `start --foreground` rather than
+/// relying on `mini start`'s own daemonizing, so `rc.subr`'s own process
+/// supervision is what's actually tracking it).
+fn script_contents(exe: &Path, config: &ServerConfig) -> String {
+    format!(
+        "#!/bin/sh\n\
+         #\n\
+         # PROVIDE: mini\n\
+         # REQUIRE: NETWORKING\n\
+         # KEYWORD: shutdown\n\
+         #\n\
+         # Listens on {http} (HTTP), {https} (HTTPS), and 0.0.0.0:53 (DNS)\n\
+         \n\
+         . /etc/rc.subr\n\
+         \n\
+         name=\"mini\"\n\
+         rcvar=\"mini_enable\"\n\
+         command=\"{exe}\"\n\
+         command_args=\"start --foreground &\"\n\
+         pidfile=\"/var/run/${{name}}.pid\"\n\
+         \n\
+         load_rc_config $name\n\
+         run_rc_command \"$1\"\n",
+        exe = exe.display(),
+        http = config.http_listen_addr,
+        https = config.https_listen_addr,
+    )
+}
+
+fn run(program: &str, args: &[&str]) -> Result<()> {
+    let status = Command::new(program)
+        .args(args)
+        .status()
+        .with_context(|| format!("failed to run {program} - is it installed?"))?;
+    if !status.success() {
+        anyhow::bail!("{program} {args:?} exited with {status}");
+    }
+    Ok(())
+}
+
+fn install(config: &ServerConfig) -> Result<()> {
+    let exe = std::env::current_exe().context("could not determine the mini executable path")?;
+    let path = script_path();
+    fs::write(&path, script_contents(&exe, config))?;
+
+    let mut perms = fs::metadata(&path)?.permissions();
+    std::os::unix::fs::PermissionsExt::set_mode(&mut perms, 0o755);
+    fs::set_permissions(&path, perms)?;
+
+    run("sysrc", &["mini_enable=YES"])?;
+    run("service", &["mini", "start"])?;
+
+    println!("Installed and started {}", path.display());
+    Ok(())
+}
+
+pub(crate) fn uninstall() -> Result<()> {
+    let path = script_path();
+
+    // Best-effort: the service may already be stopped if the script was
+    // deleted out from under it.
+    let _ = run("service", &["mini", "stop"]);
+    let _ = run("sysrc", &["-x", "mini_enable"]);
+
+    if path.exists() {
+        fs::remove_file(&path)?;
+    }
+
+    println!("Uninstalled {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_contents_references_executable_and_listeners() {
+        let config = ServerConfig::default();
+        let contents = script_contents(Path::new("/usr/local/bin/mini"), &config);
+
+        assert!(contents.contains("command=\"/usr/local/bin/mini\""));
+        assert!(contents.contains(&config.http_listen_addr));
+        assert!(contents.contains(&config.https_listen_addr));
+        assert!(contents.contains("PROVIDE: mini"));
+    }
+
+    #[test]
+    fn test_script_path_is_under_rc_d() {
+        assert_eq!(script_path(), PathBuf::from("/usr/local/etc/rc.d/mini"));
+    }
+}
@@ -1,18 +1,89 @@
 use std::sync::{Arc, RwLock};
 use std::collections::HashMap;
-use crate::driver::Driver;
+use std::path::{Path, PathBuf};
+use crate::driver::{
+    BedrockDriver, CraftDriver, Driver, DriverContext, KirbyDriver, LaravelDriver, StaticOutputDriver, SymfonyDriver,
+    WordPressDriver,
+};
+use crate::php_fpm::{self, PoolManager};
 
 pub struct DriverRegistry {
     drivers: RwLock<HashMap<String, Arc<dyn Driver>>>,
+    pool_manager: Arc<PoolManager>,
+}
+
+/// Result of `DriverRegistry::which`: the matched driver, or an explanation
+/// of what each registered driver was looking for.
+pub struct WhichReport {
+    pub driver: Option<Arc<dyn Driver>>,
+    /// `(driver name, requirements)` for every driver that didn't match.
+    pub unmatched: Vec<(String, &'static str)>,
 }
 
 impl DriverRegistry {
     pub fn new() -> Self {
         DriverRegistry {
             drivers: RwLock::new(HashMap::new()),
+            pool_manager: Arc::new(PoolManager::new(php_fpm::default_runtime_dir())),
         }
     }
 
+    /// Build a registry pre-loaded with every driver mini ships with.
+    pub fn with_known_drivers() -> Self {
+        let registry = Self::new();
+        registry.register(Arc::new(LaravelDriver::new(
+            PathBuf::from("/path/to/app"),
+            "8.2".to_string(),
+            HashMap::new(),
+            false,
+            registry.pool_manager.clone(),
+        )));
+        registry.register(Arc::new(WordPressDriver::new(
+            PathBuf::from("/path/to/app"),
+            "8.2".to_string(),
+            HashMap::new(),
+            false,
+            registry.pool_manager.clone(),
+        )));
+        registry.register(Arc::new(SymfonyDriver::new(
+            PathBuf::from("/path/to/app"),
+            "8.2".to_string(),
+            HashMap::new(),
+            false,
+            registry.pool_manager.clone(),
+        )));
+        registry.register(Arc::new(CraftDriver::new(
+            PathBuf::from("/path/to/app"),
+            "8.2".to_string(),
+            HashMap::new(),
+            false,
+            registry.pool_manager.clone(),
+        )));
+        registry.register(Arc::new(BedrockDriver::new(
+            PathBuf::from("/path/to/app"),
+            "8.2".to_string(),
+            HashMap::new(),
+            false,
+            registry.pool_manager.clone(),
+        )));
+        registry.register(Arc::new(KirbyDriver::new(
+            PathBuf::from("/path/to/app"),
+            "8.2".to_string(),
+            HashMap::new(),
+            false,
+            registry.pool_manager.clone(),
+        )));
+        registry.register(Arc::new(StaticOutputDriver::new(PathBuf::from("/path/to/app"))));
+        registry
+    }
+
+    /// The pool manager shared by every driver this registry hands out -
+    /// exposed so the daemon can run [`PoolManager::run_health_checks`]
+    /// against it on a timer.
+    pub fn pool_manager(&self) -> Arc<PoolManager> {
+        self.pool_manager.clone()
+    }
+
     pub fn register(&self, driver: Arc<dyn Driver>) {
         let mut drivers = self.drivers.write().unwrap();
         drivers.insert(driver.name().to_string(), driver);
@@ -22,13 +93,47 @@ impl DriverRegistry {
         let drivers = self.drivers.read().unwrap();
         drivers.get(name).cloned()
     }
+
+    /// Auto-detect which registered driver can serve the given site root.
+    /// If more than one driver matches, which one wins is unspecified.
+    ///
+    /// Only `path` is known this early - a [`DriverContext`] built from it
+    /// is all any driver's `supports` gets to look at during detection; see
+    /// that type's doc comment.
+    pub fn detect(&self, path: &Path) -> Option<Arc<dyn Driver>> {
+        let ctx = DriverContext::from_path(path);
+        let drivers = self.drivers.read().unwrap();
+        drivers.values().find(|driver| driver.supports(&ctx)).cloned()
+    }
+
+    /// Like `detect`, but also explains what each non-matching driver was
+    /// looking for, for `mini which` to surface to the user.
+    pub fn which(&self, path: &Path) -> WhichReport {
+        let ctx = DriverContext::from_path(path);
+        let drivers = self.drivers.read().unwrap();
+        let mut unmatched = Vec::new();
+        for driver in drivers.values() {
+            if driver.supports(&ctx) {
+                return WhichReport {
+                    driver: Some(driver.clone()),
+                    unmatched,
+                };
+            }
+            unmatched.push((driver.name().to_string(), driver.requirements()));
+        }
+        WhichReport {
+            driver: None,
+            unmatched,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::path::PathBuf;
-    use crate::driver::LaravelDriver;
+    use tempfile::TempDir;
+    use tokio::fs;
 
     #[tokio::test]
     async fn test_driver_registry() {
@@ -36,10 +141,45 @@ mod tests {
         let driver = Arc::new(LaravelDriver::new(
             PathBuf::from("/path/to/app"),
             "8.2".to_string(),
+            HashMap::new(),
+            false,
+            Arc::new(PoolManager::new(php_fpm::default_runtime_dir())),
         ));
 
         registry.register(driver.clone());
         let retrieved = registry.get("Laravel").unwrap();
         assert_eq!(retrieved.name(), "Laravel");
     }
+
+    #[tokio::test]
+    async fn test_which_reports_a_match() {
+        let registry = DriverRegistry::with_known_drivers();
+
+        let temp_dir = TempDir::new().unwrap();
+        let site_path = temp_dir.path().to_path_buf();
+        fs::create_dir_all(site_path.join("public")).await.unwrap();
+        fs::write(site_path.join("artisan"), "").await.unwrap();
+        fs::write(site_path.join("public/index.php"), "").await.unwrap();
+
+        let report = registry.which(&site_path);
+        assert_eq!(report.driver.unwrap().name(), "Laravel");
+        assert!(report.unmatched.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_which_explains_a_non_match() {
+        let registry = DriverRegistry::with_known_drivers();
+        let temp_dir = TempDir::new().unwrap();
+
+        let report = registry.which(temp_dir.path());
+        assert!(report.driver.is_none());
+        assert_eq!(report.unmatched.len(), 7);
+        assert!(report.unmatched.iter().any(|(name, requirements)| name == "Laravel" && requirements.contains("artisan")));
+        assert!(report.unmatched.iter().any(|(name, requirements)| name == "WordPress" && requirements.contains("wp-config.php")));
+        assert!(report.unmatched.iter().any(|(name, requirements)| name == "Symfony" && requirements.contains("bin/console")));
+        assert!(report.unmatched.iter().any(|(name, requirements)| name == "Craft" && requirements.contains("craft")));
+        assert!(report.unmatched.iter().any(|(name, requirements)| name == "Bedrock" && requirements.contains("web/wp")));
+        assert!(report.unmatched.iter().any(|(name, requirements)| name == "Kirby" && requirements.contains("bootstrap.php")));
+        assert!(report.unmatched.iter().any(|(name, requirements)| name == "Static Output" && requirements.contains("dist/")));
+    }
 }
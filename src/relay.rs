@@ -0,0 +1,631 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, error, info, warn};
+
+/// A mini-native alternative to the third-party tunnel providers in
+/// `share.rs`: a single outbound TLS connection from the daemon (the
+/// client, in [`run_client`]) to a `mini relay-server` process (the server,
+/// in [`run_server`]), multiplexing every visitor connection to a shared
+/// domain over that one connection as newline-delimited JSON frames. Lets a
+/// team run their own relay on a VPS instead of depending on ngrok/
+/// cloudflared/Expose.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum RelayMessage {
+    /// Sent once by the client to register a domain.
+    Hello { domain: String, token: Option<String> },
+    /// Sent by the server once a `Hello` is accepted.
+    Registered { url: String },
+    /// Sent by the server instead of `Registered` when a `Hello` is refused
+    /// (bad token, or the domain is already registered by another client).
+    Rejected { reason: String },
+    /// A visitor connection arrived on the server's public listener; the
+    /// client should dial its local site and start relaying.
+    Open { stream_id: u64 },
+    /// A chunk of one multiplexed stream's bytes, base64-encoded since a
+    /// JSON string can't carry arbitrary bytes.
+    Data { stream_id: u64, data: String },
+    /// One side of a multiplexed stream closed; the other should close its
+    /// matching half too.
+    Close { stream_id: u64 },
+}
+
+async fn write_message<W: AsyncWrite + Unpin>(writer: &mut W, message: &RelayMessage) -> Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+async fn read_message<R: AsyncBufReadExt + Unpin>(reader: &mut R) -> Result<Option<RelayMessage>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&line)?))
+}
+
+/// Case-insensitively find `name`'s value in an HTTP header block (the part
+/// of a request/response before the blank line that ends it). Hand-rolled
+/// rather than pulling in a full HTTP parser, the same reasoning as the
+/// log-scraping helpers in `share.rs` - this module only ever needs one
+/// header out of the block.
+fn find_header_value(headers: &str, name: &str) -> Option<String> {
+    let prefix = format!("{name}:");
+    headers.lines().find_map(|line| {
+        if line.len() > prefix.len() && line[..prefix.len()].eq_ignore_ascii_case(&prefix) {
+            Some(line[prefix.len()..].trim().to_string())
+        } else {
+            None
+        }
+    })
+}
+
+/// Extract the `Host` header from a buffer that's expected to hold a full
+/// set of HTTP headers (ending in `\r\n\r\n`). `None` either means there's
+/// no `Host` header or the buffer doesn't have a complete header block yet.
+fn extract_host_header(buf: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(buf).ok()?;
+    let header_end = text.find("\r\n\r\n")?;
+    find_header_value(&text[..header_end], "Host")
+}
+
+/// Replace a request's `Host` header with `new_host` - used on the client
+/// side of [`run_client`] so a visitor's request, which arrives with the
+/// relay's own public hostname as `Host`, gets routed to the right site by
+/// mini's own Host-based reverse proxy once it lands on `local_addr`.
+/// Leaves `buf` untouched if it doesn't contain a complete header block or
+/// has no `Host` header to replace.
+fn rewrite_host_header(buf: &[u8], new_host: &str) -> Vec<u8> {
+    let Ok(text) = std::str::from_utf8(buf) else {
+        return buf.to_vec();
+    };
+    let Some(header_end) = text.find("\r\n\r\n") else {
+        return buf.to_vec();
+    };
+    let (headers, rest) = text.split_at(header_end);
+    let prefix = "Host:";
+    let mut replaced = false;
+    let rewritten: Vec<String> = headers
+        .split("\r\n")
+        .map(|line| {
+            if !replaced && line.len() > prefix.len() && line[..prefix.len()].eq_ignore_ascii_case(prefix) {
+                replaced = true;
+                format!("Host: {new_host}")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    if !replaced {
+        return buf.to_vec();
+    }
+    format!("{}{}", rewritten.join("\r\n"), rest).into_bytes()
+}
+
+type BoxedWriter = Box<dyn AsyncWrite + Send + Unpin>;
+/// The relay connection's write half, shared across every multiplexed
+/// stream's task so each can send its own `Data`/`Close` frames.
+type RelayWriter = Arc<Mutex<BoxedWriter>>;
+type StreamSender = mpsc::UnboundedSender<Vec<u8>>;
+/// Per-stream-id queues for bytes that arrived over the relay connection and
+/// are waiting to be written to that stream's local socket.
+type StreamRegistry = Arc<Mutex<HashMap<u64, StreamSender>>>;
+
+async fn send_data(writer: &RelayWriter, stream_id: u64, data: &[u8]) -> Result<()> {
+    let message = RelayMessage::Data { stream_id, data: BASE64.encode(data) };
+    write_message(&mut *writer.lock().await, &message).await
+}
+
+async fn send_close(writer: &RelayWriter, stream_id: u64) -> Result<()> {
+    write_message(&mut *writer.lock().await, &RelayMessage::Close { stream_id }).await
+}
+
+/// Pump one multiplexed stream: bytes read off `local` become `Data` frames
+/// sent to the relay peer over `writer`; bytes queued on `incoming` (fed by
+/// the connection's read loop) are written back out to `local`. Used by
+/// [`run_client`] for each `Open`ed stream, where `writer` is the one TLS
+/// connection shared with every other stream. [`handle_public_connection`]
+/// needs the same byte-shuffling but has no `RelayWriter` to share - its
+/// outbound frames go over an `mpsc` channel instead - so it runs its own
+/// copy of this loop rather than reusing it.
+async fn pump_stream(
+    stream_id: u64,
+    mut local: TcpStream,
+    writer: RelayWriter,
+    mut incoming: mpsc::UnboundedReceiver<Vec<u8>>,
+) {
+    let mut buf = vec![0u8; 16 * 1024];
+    loop {
+        tokio::select! {
+            result = local.read(&mut buf) => {
+                match result {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        if send_data(&writer, stream_id, &buf[..n]).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+            chunk = incoming.recv() => {
+                match chunk {
+                    Some(chunk) if local.write_all(&chunk).await.is_ok() => {}
+                    _ => break,
+                }
+            }
+        }
+    }
+    let _ = send_close(&writer, stream_id).await;
+}
+
+/// Load a [`rustls::ClientConfig`] that trusts only the relay operator's own
+/// CA certificate. There's no bundled set of public root CAs to fall back
+/// to in this build (`webpki-roots`/`rustls-native-certs` aren't vendored),
+/// which fits a self-hosted relay anyway: the operator hands their CA (or a
+/// self-signed cert) to every daemon that should be able to register with
+/// it, the same trust model as a WireGuard/Tailscale peer rather than a
+/// publicly-trusted certificate.
+fn load_client_tls_config(ca_cert_path: &Path) -> Result<rustls::ClientConfig> {
+    let pem = std::fs::read(ca_cert_path)
+        .with_context(|| format!("failed to read relay CA certificate at {}", ca_cert_path.display()))?;
+    let certs = rustls_pemfile::certs(&mut pem.as_slice())
+        .with_context(|| format!("failed to parse relay CA certificate at {}", ca_cert_path.display()))?;
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add_parsable_certificates(&certs);
+    Ok(rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth())
+}
+
+fn load_server_tls_config(cert_path: &Path, key_path: &Path) -> Result<rustls::ServerConfig> {
+    let cert_pem = std::fs::read(cert_path)
+        .with_context(|| format!("failed to read relay server certificate at {}", cert_path.display()))?;
+    let cert_chain = rustls_pemfile::certs(&mut cert_pem.as_slice())
+        .with_context(|| format!("failed to parse relay server certificate at {}", cert_path.display()))?
+        .into_iter()
+        .map(rustls::Certificate)
+        .collect();
+
+    let key_pem = std::fs::read(key_path)
+        .with_context(|| format!("failed to read relay server key at {}", key_path.display()))?;
+    let key = rustls_pemfile::pkcs8_private_keys(&mut key_pem.as_slice())
+        .with_context(|| format!("failed to parse relay server key at {}", key_path.display()))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("no PKCS#8 private key found in {}", key_path.display()))?;
+
+    Ok(rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(cert_chain, rustls::PrivateKey(key))?)
+}
+
+/// Connect to a self-hosted relay server and keep `domain` registered for
+/// as long as the connection holds - reconnecting is the caller's job (see
+/// `main.rs`'s relay client task), since a dropped connection here just
+/// means `domain` stops being reachable through the relay until someone
+/// reconnects, the same as any other network client.
+///
+/// `on_registered` is called once, with the public URL the server assigned,
+/// as soon as registration succeeds.
+pub async fn run_client(
+    server_addr: &str,
+    domain: &str,
+    local_addr: &str,
+    token: Option<&str>,
+    ca_cert_path: &Path,
+    on_registered: impl FnOnce(String) + Send + 'static,
+) -> Result<()> {
+    let tls_config = Arc::new(load_client_tls_config(ca_cert_path)?);
+    let connector = tokio_rustls::TlsConnector::from(tls_config);
+    let server_name = rustls::ServerName::try_from(host_only(server_addr))
+        .map_err(|_| anyhow::anyhow!("invalid relay server address {server_addr}"))?;
+
+    let tcp = TcpStream::connect(server_addr)
+        .await
+        .with_context(|| format!("failed to connect to relay server {server_addr}"))?;
+    let tls = connector
+        .connect(server_name, tcp)
+        .await
+        .with_context(|| format!("TLS handshake with relay server {server_addr} failed"))?;
+
+    let (read_half, write_half) = tokio::io::split(tls);
+    let mut reader = BufReader::new(read_half);
+    let writer: RelayWriter = Arc::new(Mutex::new(Box::new(write_half)));
+
+    write_message(
+        &mut *writer.lock().await,
+        &RelayMessage::Hello { domain: domain.to_string(), token: token.map(str::to_string) },
+    )
+    .await?;
+
+    match read_message(&mut reader).await? {
+        Some(RelayMessage::Registered { url }) => on_registered(url),
+        Some(RelayMessage::Rejected { reason }) => {
+            anyhow::bail!("relay server rejected {domain}: {reason}")
+        }
+        Some(other) => anyhow::bail!("unexpected message from relay server before registration: {other:?}"),
+        None => anyhow::bail!("relay server closed the connection before registering {domain}"),
+    }
+
+    let streams: StreamRegistry = Arc::new(Mutex::new(HashMap::new()));
+    // A request's `Host` header only needs rewriting once, on the first
+    // `Data` chunk of a stream - everything after that is request body or
+    // (on a keep-alive connection) a later request this module doesn't
+    // attempt to re-parse. That's an accepted limitation: see
+    // `rewrite_host_header`'s doc comment.
+    let mut header_rewritten: std::collections::HashSet<u64> = std::collections::HashSet::new();
+
+    loop {
+        match read_message(&mut reader).await? {
+            Some(RelayMessage::Open { stream_id }) => {
+                let writer = writer.clone();
+                let streams = streams.clone();
+                let local_addr = local_addr.to_string();
+                let domain = domain.to_string();
+                tokio::spawn(async move {
+                    let local = match TcpStream::connect(&local_addr).await {
+                        Ok(local) => local,
+                        Err(e) => {
+                            error!("relay client couldn't reach local site {domain} at {local_addr} for stream {stream_id}: {e}");
+                            let _ = send_close(&writer, stream_id).await;
+                            return;
+                        }
+                    };
+                    let (sender, receiver) = mpsc::unbounded_channel();
+                    streams.lock().await.insert(stream_id, sender);
+                    pump_stream(stream_id, local, writer, receiver).await;
+                    streams.lock().await.remove(&stream_id);
+                });
+            }
+            Some(RelayMessage::Data { stream_id, data }) => {
+                let mut bytes = BASE64.decode(&data).unwrap_or_default();
+                if header_rewritten.insert(stream_id) {
+                    bytes = rewrite_host_header(&bytes, domain);
+                }
+                if let Some(sender) = streams.lock().await.get(&stream_id) {
+                    let _ = sender.send(bytes);
+                }
+            }
+            Some(RelayMessage::Close { stream_id }) => {
+                header_rewritten.remove(&stream_id);
+                streams.lock().await.remove(&stream_id);
+            }
+            Some(other) => warn!("unexpected message from relay server: {other:?}"),
+            None => anyhow::bail!("relay server {server_addr} closed the connection"),
+        }
+    }
+}
+
+/// Strip a trailing `:port` off `addr`, for building the [`rustls::ServerName`]
+/// a TLS handshake validates the peer's certificate against.
+fn host_only(addr: &str) -> &str {
+    addr.rsplit_once(':').map(|(host, _)| host).unwrap_or(addr)
+}
+
+/// A relay client currently registered for a domain: a sink for frames
+/// destined back to it, and the per-stream registry [`handle_public_connection`]
+/// feeds visitor bytes into once it's opened a stream for that client.
+struct RegisteredDomain {
+    to_client: mpsc::UnboundedSender<RelayMessage>,
+    streams: StreamRegistry,
+    next_stream_id: AtomicU64,
+}
+
+type DomainTable = Arc<Mutex<HashMap<String, RegisteredDomain>>>;
+
+/// Everything needed to run a `mini relay-server` process: where relay
+/// clients (daemons) register over TLS, where visitor traffic arrives in
+/// plain TCP, and how to answer a registering client with its public URL.
+pub struct RelayServerSettings {
+    pub control_listen_addr: String,
+    pub public_listen_addr: String,
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    /// Required `Hello.token` for every registering client, if set.
+    /// Unset means any client can register any domain.
+    pub token: Option<String>,
+    /// The public URL handed back in `Registered`, with `{domain}`
+    /// substituted for the registering client's domain - e.g.
+    /// `https://{domain}` when DNS for every relayed domain already points
+    /// at this relay.
+    pub public_url_template: String,
+}
+
+/// Run a `mini relay-server` process: a TLS control listener relay clients
+/// register against, and a plain-TCP public listener visitor traffic
+/// arrives on. Runs until one of the two listeners fails.
+pub async fn run_server(settings: RelayServerSettings) -> Result<()> {
+    let tls_config = Arc::new(load_server_tls_config(&settings.cert_path, &settings.key_path)?);
+    let acceptor = tokio_rustls::TlsAcceptor::from(tls_config);
+    let domains: DomainTable = Arc::new(Mutex::new(HashMap::new()));
+
+    let control_listener = TcpListener::bind(&settings.control_listen_addr)
+        .await
+        .with_context(|| format!("failed to bind relay control listener on {}", settings.control_listen_addr))?;
+    let public_listener = TcpListener::bind(&settings.public_listen_addr)
+        .await
+        .with_context(|| format!("failed to bind relay public listener on {}", settings.public_listen_addr))?;
+
+    info!("Relay control listening on {}", settings.control_listen_addr);
+    info!("Relay public listening on {}", settings.public_listen_addr);
+
+    let token = settings.token;
+    let url_template = settings.public_url_template;
+    let control_domains = domains.clone();
+    let control_future = tokio::spawn(async move {
+        loop {
+            let (socket, peer) = match control_listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    error!("relay control accept error: {e}");
+                    continue;
+                }
+            };
+            let acceptor = acceptor.clone();
+            let domains = control_domains.clone();
+            let token = token.clone();
+            let url_template = url_template.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_control_connection(socket, acceptor, domains, token, url_template).await {
+                    warn!("relay control connection from {peer} ended: {e}");
+                }
+            });
+        }
+    });
+
+    let public_future = tokio::spawn(async move {
+        loop {
+            let (socket, peer) = match public_listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    error!("relay public accept error: {e}");
+                    continue;
+                }
+            };
+            let domains = domains.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_public_connection(socket, domains).await {
+                    debug!("relay public connection from {peer} ended: {e}");
+                }
+            });
+        }
+    });
+
+    tokio::try_join!(control_future, public_future)?;
+    Ok(())
+}
+
+/// Handle one relay client's control connection for as long as it's
+/// registered: the TLS accept, the `Hello`/token check, then forwarding
+/// `Open`/`Data`/`Close` frames both ways between [`handle_public_connection`]
+/// (via the domain table) and the client.
+async fn handle_control_connection(
+    socket: TcpStream,
+    acceptor: tokio_rustls::TlsAcceptor,
+    domains: DomainTable,
+    token: Option<String>,
+    url_template: String,
+) -> Result<()> {
+    let tls = acceptor.accept(socket).await.context("relay control TLS accept failed")?;
+    let (read_half, write_half) = tokio::io::split(tls);
+    let mut reader = BufReader::new(read_half);
+    let writer: RelayWriter = Arc::new(Mutex::new(Box::new(write_half)));
+
+    let (domain, hello_token) = match read_message(&mut reader).await? {
+        Some(RelayMessage::Hello { domain, token }) => (domain, token),
+        Some(other) => anyhow::bail!("expected Hello, got {other:?}"),
+        None => anyhow::bail!("connection closed before sending Hello"),
+    };
+
+    if token.is_some() && token != hello_token {
+        write_message(
+            &mut *writer.lock().await,
+            &RelayMessage::Rejected { reason: "invalid token".to_string() },
+        )
+        .await?;
+        anyhow::bail!("{domain} sent an invalid token");
+    }
+    if domains.lock().await.contains_key(&domain) {
+        write_message(
+            &mut *writer.lock().await,
+            &RelayMessage::Rejected { reason: "domain already registered".to_string() },
+        )
+        .await?;
+        anyhow::bail!("{domain} is already registered by another client");
+    }
+
+    let (to_client, mut from_domain_table) = mpsc::unbounded_channel();
+    domains.lock().await.insert(
+        domain.clone(),
+        RegisteredDomain {
+            to_client,
+            streams: Arc::new(Mutex::new(HashMap::new())),
+            next_stream_id: AtomicU64::new(1),
+        },
+    );
+
+    let url = url_template.replace("{domain}", &domain);
+    write_message(&mut *writer.lock().await, &RelayMessage::Registered { url }).await?;
+    info!("relay client registered {domain}");
+
+    // Drain frames `handle_public_connection` queues for this domain (via
+    // `to_client`) out to the client's TLS connection.
+    let outbound_writer = writer.clone();
+    let outbound_domain = domain.clone();
+    let outbound_future = tokio::spawn(async move {
+        while let Some(message) = from_domain_table.recv().await {
+            if write_message(&mut *outbound_writer.lock().await, &message).await.is_err() {
+                break;
+            }
+        }
+        debug!("relay outbound writer for {outbound_domain} stopped");
+    });
+
+    // Frames coming back *from* the client (`Data`/`Close` for streams it
+    // opened on the client's local site) dispatch into that stream's
+    // registry so the waiting `handle_public_connection` task sees them.
+    let result = loop {
+        match read_message(&mut reader).await {
+            Ok(Some(RelayMessage::Data { stream_id, data })) => {
+                let streams = domains
+                    .lock()
+                    .await
+                    .get(&domain)
+                    .map(|registered| registered.streams.clone());
+                if let Some(streams) = streams {
+                    if let Some(sender) = streams.lock().await.get(&stream_id) {
+                        let _ = sender.send(BASE64.decode(&data).unwrap_or_default());
+                    }
+                }
+            }
+            Ok(Some(RelayMessage::Close { stream_id })) => {
+                let streams = domains
+                    .lock()
+                    .await
+                    .get(&domain)
+                    .map(|registered| registered.streams.clone());
+                if let Some(streams) = streams {
+                    streams.lock().await.remove(&stream_id);
+                }
+            }
+            Ok(Some(other)) => warn!("unexpected message from relay client {domain}: {other:?}"),
+            Ok(None) => break Ok(()),
+            Err(e) => break Err(e),
+        }
+    };
+
+    outbound_future.abort();
+    domains.lock().await.remove(&domain);
+    info!("relay client {domain} disconnected");
+    result
+}
+
+/// Handle one visitor connection on the public listener: buffer bytes until
+/// a `Host` header can be read out of them, look up that domain's
+/// registered client, and pump the connection through a freshly allocated
+/// stream id.
+async fn handle_public_connection(mut socket: TcpStream, domains: DomainTable) -> Result<()> {
+    let mut buf = vec![0u8; 16 * 1024];
+    let mut len = 0;
+    let host = loop {
+        if len == buf.len() {
+            anyhow::bail!("request headers exceeded {} bytes without completing", buf.len());
+        }
+        let n = socket.read(&mut buf[len..]).await?;
+        if n == 0 {
+            anyhow::bail!("visitor closed the connection before sending a complete request");
+        }
+        len += n;
+        if let Some(host) = extract_host_header(&buf[..len]) {
+            break host;
+        }
+    };
+
+    let registered = {
+        let domains = domains.lock().await;
+        match domains.get(&host) {
+            Some(registered) => (registered.to_client.clone(), registered.streams.clone(), registered.next_stream_id.fetch_add(1, Ordering::Relaxed)),
+            None => {
+                socket
+                    .write_all(b"HTTP/1.1 502 Bad Gateway\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                    .await?;
+                anyhow::bail!("no relay client registered for {host}");
+            }
+        }
+    };
+    let (to_client, streams, stream_id) = registered;
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    streams.lock().await.insert(stream_id, sender);
+
+    to_client.send(RelayMessage::Open { stream_id }).ok();
+    to_client
+        .send(RelayMessage::Data { stream_id, data: BASE64.encode(&buf[..len]) })
+        .ok();
+
+    // There's no relay connection to write `Data`/`Close` frames onto here -
+    // those go out over `to_client`, the same channel `Open`/the initial
+    // `Data` above used, not a `RelayWriter`. Bridge the two by relaying
+    // `pump_stream`'s socket-read half manually instead of reusing it
+    // wholesale, since `pump_stream` assumes a shared `RelayWriter`.
+    let mut local = socket;
+    let mut incoming = receiver;
+    let mut read_buf = vec![0u8; 16 * 1024];
+    loop {
+        tokio::select! {
+            result = local.read(&mut read_buf) => {
+                match result {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        if to_client
+                            .send(RelayMessage::Data { stream_id, data: BASE64.encode(&read_buf[..n]) })
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                }
+            }
+            chunk = incoming.recv() => {
+                match chunk {
+                    Some(chunk) if local.write_all(&chunk).await.is_ok() => {}
+                    _ => break,
+                }
+            }
+        }
+    }
+    let _ = to_client.send(RelayMessage::Close { stream_id });
+    streams.lock().await.remove(&stream_id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_host_header_finds_it_case_insensitively() {
+        let request = b"GET / HTTP/1.1\r\nhost: app.example.com\r\nAccept: */*\r\n\r\n";
+        assert_eq!(extract_host_header(request), Some("app.example.com".to_string()));
+    }
+
+    #[test]
+    fn test_extract_host_header_returns_none_without_a_complete_header_block() {
+        let partial = b"GET / HTTP/1.1\r\nHost: app.example";
+        assert_eq!(extract_host_header(partial), None);
+    }
+
+    #[test]
+    fn test_rewrite_host_header_replaces_the_value() {
+        let request = b"GET / HTTP/1.1\r\nHost: relay.example.com\r\nAccept: */*\r\n\r\nbody";
+        let rewritten = rewrite_host_header(request, "app.test");
+        assert_eq!(extract_host_header(&rewritten), Some("app.test".to_string()));
+        assert!(rewritten.ends_with(b"body"));
+    }
+
+    #[test]
+    fn test_rewrite_host_header_leaves_incomplete_requests_untouched() {
+        let partial = b"GET / HTTP/1.1\r\nHost: relay.example.com";
+        assert_eq!(rewrite_host_header(partial, "app.test"), partial.to_vec());
+    }
+
+    #[test]
+    fn test_host_only_strips_the_port() {
+        assert_eq!(host_only("relay.example.com:4443"), "relay.example.com");
+        assert_eq!(host_only("relay.example.com"), "relay.example.com");
+    }
+}
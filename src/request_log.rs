@@ -0,0 +1,128 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// One proxied request, as recorded by [`crate::MyProxy::logging`] - the
+/// admin API's `/api/requests/recent` serves these back as a lightweight
+/// network-tab-style view of recent traffic.
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestLogEntry {
+    pub timestamp_unix: u64,
+    pub method: String,
+    pub host: String,
+    pub path: String,
+    pub status: u16,
+    pub duration_ms: Option<u64>,
+    /// The site this request was routed to, once routing actually consults
+    /// `SiteManager` by host - `None` until then (see
+    /// [`crate::MyProxy::upstream_peer`]).
+    pub matched_site: Option<String>,
+    /// Present only when header capture is enabled; omitted entirely (not
+    /// just empty) otherwise, so a quick look at the JSON tells you whether
+    /// capture was on for this request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_headers: Option<Vec<(String, String)>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_headers: Option<Vec<(String, String)>>,
+}
+
+/// A fixed-capacity ring buffer of the most recently proxied requests. Old
+/// entries are evicted as new ones arrive rather than this growing forever,
+/// since it exists to answer "what just happened", not as a durable log -
+/// `error_log`/`logs` already cover that.
+pub struct RequestLog {
+    capacity: usize,
+    entries: Mutex<VecDeque<RequestLogEntry>>,
+}
+
+impl RequestLog {
+    pub fn new(capacity: usize) -> Self {
+        RequestLog {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn record(&self, mut entry: RequestLogEntry) {
+        entry.timestamp_unix = now_unix();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// The most recent entries, newest first, capped at `limit` (or
+    /// everything held, if `limit` is `None` or larger than that).
+    pub fn recent(&self, limit: Option<usize>) -> Vec<RequestLogEntry> {
+        let entries = self.entries.lock().unwrap();
+        let limit = limit.unwrap_or(entries.len()).min(entries.len());
+        entries.iter().rev().take(limit).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str) -> RequestLogEntry {
+        RequestLogEntry {
+            timestamp_unix: 0,
+            method: "GET".to_string(),
+            host: "example.test".to_string(),
+            path: path.to_string(),
+            status: 200,
+            duration_ms: Some(5),
+            matched_site: None,
+            request_headers: None,
+            response_headers: None,
+        }
+    }
+
+    #[test]
+    fn test_recent_returns_newest_first() {
+        let log = RequestLog::new(10);
+        log.record(entry("/a"));
+        log.record(entry("/b"));
+
+        let recent = log.recent(None);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].path, "/b");
+        assert_eq!(recent[1].path, "/a");
+    }
+
+    #[test]
+    fn test_recent_honors_limit() {
+        let log = RequestLog::new(10);
+        log.record(entry("/a"));
+        log.record(entry("/b"));
+        log.record(entry("/c"));
+
+        let recent = log.recent(Some(2));
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].path, "/c");
+        assert_eq!(recent[1].path, "/b");
+    }
+
+    #[test]
+    fn test_record_evicts_oldest_past_capacity() {
+        let log = RequestLog::new(2);
+        log.record(entry("/a"));
+        log.record(entry("/b"));
+        log.record(entry("/c"));
+
+        let recent = log.recent(None);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].path, "/c");
+        assert_eq!(recent[1].path, "/b");
+    }
+}
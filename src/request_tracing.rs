@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr};
+
+use anyhow::Result;
+use cf_rustracing::sampler::AllSampler;
+use cf_rustracing_jaeger::reporter::JaegerCompactReporter;
+use cf_rustracing_jaeger::span::{SpanContext, SpanContextState, SpanReceiver, TraceId};
+use cf_rustracing_jaeger::Tracer;
+use http::HeaderMap;
+use tracing::warn;
+
+/// The W3C standard header for propagating trace context, e.g.
+/// `00-<32 hex trace id>-<16 hex span id>-<2 hex flags>`.
+const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// The W3C header that rides alongside `traceparent` carrying
+/// vendor-specific state (`key1=value1,key2=value2`). `cf-rustracing-jaeger`
+/// has no concept of this - its `SpanContext` only knows trace id, span id,
+/// and sampling flags - so mini can't fold it into the trace context the
+/// way it does `traceparent`. Instead it's carried alongside the context as
+/// an opaque string and passed straight through unmodified, same as a
+/// reverse proxy with no tracestate of its own to add would.
+const TRACESTATE_HEADER: &str = "tracestate";
+
+/// Service name spans are reported to Jaeger under.
+const SERVICE_NAME: &str = "mini";
+
+/// Starts a [`Tracer`] that samples every span - this proxy serves local dev
+/// traffic rather than production load, so there's no sampling cost to
+/// justify dropping any - and spawns a task that drains finished spans to
+/// the Jaeger agent at `agent_addr` over UDP using the compact thrift
+/// protocol.
+///
+/// `JaegerCompactReporter` is the only reporter `cf-rustracing-jaeger`
+/// ships - it has no OTLP option, and adding one means extending that
+/// crate's `reporter` module, which lives at crates.io rather than in this
+/// repo. Wiring an OTLP exporter in from mini's side instead would mean
+/// re-deriving a `Span`'s fields into the OTLP trace protobufs (mini
+/// doesn't generate those today - `build.rs` only compiles `proto/mini.proto`,
+/// mini's own control-plane schema) and shipping them over a tonic client,
+/// which is a real feature on its own rather than something to land
+/// alongside everything else changing in this module.
+pub async fn init(agent_addr: SocketAddr) -> Result<Tracer> {
+    let (tracer, span_rx) = Tracer::new(AllSampler);
+    let reporter =
+        JaegerCompactReporter::new(SERVICE_NAME, agent_addr, (Ipv4Addr::UNSPECIFIED, 0).into())
+            .await?;
+    tokio::spawn(report_spans(span_rx, reporter));
+    Ok(tracer)
+}
+
+/// Reports each finished span to Jaeger as soon as it completes. Request
+/// volume through this proxy is low enough that batching spans before
+/// reporting isn't worth the complexity.
+async fn report_spans(mut span_rx: SpanReceiver, reporter: JaegerCompactReporter) {
+    while let Some(span) = span_rx.recv().await {
+        if let Err(e) = reporter.report(&[span]).await {
+            warn!("Failed to report span to Jaeger: {e}");
+        }
+    }
+}
+
+/// Extracts an incoming trace context from `headers`, so a request arriving
+/// from a hop that's already tracing it (another `mini` instance, or
+/// whatever sits in front of this one) continues that trace instead of
+/// starting a new one. Tries the W3C `traceparent` header first, since
+/// that's the standard [`inject_headers`] writes, then falls back to
+/// Jaeger's own `uber-trace-id` for callers that only speak that format.
+pub fn extract_context(headers: &HeaderMap) -> Option<SpanContext> {
+    if let Some(context) = headers
+        .get(TRACEPARENT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_traceparent)
+    {
+        return Some(context);
+    }
+
+    let carrier: HashMap<String, String> = headers
+        .iter()
+        .filter_map(|(name, value)| {
+            Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+        })
+        .collect();
+    SpanContext::extract_from_http_header(&carrier).ok().flatten()
+}
+
+/// Extracts the raw `tracestate` value from an incoming request, if any -
+/// see [`TRACESTATE_HEADER`] for why this rides alongside a [`SpanContext`]
+/// rather than inside one.
+pub fn extract_tracestate(headers: &HeaderMap) -> Option<String> {
+    headers.get(TRACESTATE_HEADER).and_then(|v| v.to_str().ok()).map(str::to_string)
+}
+
+fn parse_traceparent(value: &str) -> Option<SpanContext> {
+    let mut parts = value.split('-');
+    let _version = parts.next()?;
+    let trace_id: TraceId = parts.next()?.parse().ok()?;
+    let span_id = u64::from_str_radix(parts.next()?, 16).ok()?;
+    let flags = u8::from_str_radix(parts.next()?, 16).ok()?;
+    let state = SpanContextState::new(trace_id, span_id, flags, String::new());
+    Some(SpanContext::new(state, Vec::new()))
+}
+
+/// Builds the headers an outgoing request needs to carry `context` forward:
+/// a Jaeger `uber-trace-id` header (so a downstream hop that's also
+/// `cf-rustracing-jaeger`-instrumented needs no translation) and a W3C
+/// `traceparent` header (so PHP/Node frameworks with their own W3C-aware
+/// tracing can pick the trace back up). The same pair works verbatim as
+/// FastCGI params once prefixed `HTTP_` by [`crate::fastcgi`]'s existing
+/// header-to-param mapping.
+///
+/// `tracestate`, if the incoming request carried one (see
+/// [`extract_tracestate`]), is forwarded unmodified alongside the two - mini
+/// has nothing of its own to add to it, so passing it through verbatim is
+/// all a hop with no tracestate to contribute can correctly do.
+pub fn inject_headers(
+    context: &SpanContext,
+    tracestate: Option<&str>,
+) -> Result<Vec<(&'static str, String)>> {
+    let mut carrier = HashMap::new();
+    context.inject_to_http_header(&mut carrier)?;
+    let uber_trace_id = carrier
+        .remove("uber-trace-id")
+        .unwrap_or_else(|| context.state().to_string());
+
+    let state = context.state();
+    let trace_id = state.trace_id();
+    let flags = if state.is_sampled() { "01" } else { "00" };
+    let traceparent = format!(
+        "00-{:016x}{:016x}-{:016x}-{}",
+        trace_id.high,
+        trace_id.low,
+        state.span_id(),
+        flags
+    );
+
+    let mut headers = vec![
+        ("uber-trace-id", uber_trace_id),
+        (TRACEPARENT_HEADER, traceparent),
+    ];
+    if let Some(tracestate) = tracestate {
+        headers.push((TRACESTATE_HEADER, tracestate.to_string()));
+    }
+    Ok(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::{HeaderName, HeaderValue};
+
+    fn headers_from(pairs: &[(&'static str, String)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(
+                HeaderName::from_static(name),
+                HeaderValue::from_str(value).unwrap(),
+            );
+        }
+        headers
+    }
+
+    #[test]
+    fn test_extract_context_round_trips_through_traceparent() {
+        let (tracer, _span_rx) = Tracer::new(AllSampler);
+        let span = tracer.span("test").start();
+        let context = span.context().unwrap().clone();
+
+        let headers = headers_from(&inject_headers(&context, None).unwrap());
+        let extracted = extract_context(&headers).unwrap();
+
+        assert_eq!(extracted.state().trace_id(), context.state().trace_id());
+        assert_eq!(extracted.state().span_id(), context.state().span_id());
+    }
+
+    #[test]
+    fn test_inject_headers_carries_tracestate_through_unmodified() {
+        let (tracer, _span_rx) = Tracer::new(AllSampler);
+        let span = tracer.span("test").start();
+        let context = span.context().unwrap().clone();
+
+        let headers = headers_from(&inject_headers(&context, Some("vendor1=value1")).unwrap());
+        assert_eq!(extract_tracestate(&headers), Some("vendor1=value1".to_string()));
+    }
+
+    #[test]
+    fn test_extract_tracestate_is_none_without_the_header() {
+        assert_eq!(extract_tracestate(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn test_extract_context_prefers_traceparent_over_uber_trace_id() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static("traceparent"),
+            HeaderValue::from_static("00-00000000000000000000000000000001-0000000000000002-01"),
+        );
+        headers.insert(
+            HeaderName::from_static("uber-trace-id"),
+            HeaderValue::from_static("3:4:0:1"),
+        );
+
+        let context = extract_context(&headers).unwrap();
+        assert_eq!(context.state().trace_id(), TraceId { high: 0, low: 1 });
+        assert_eq!(context.state().span_id(), 2);
+    }
+
+    #[test]
+    fn test_extract_context_falls_back_to_uber_trace_id() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static("uber-trace-id"),
+            HeaderValue::from_static("3:4:0:1"),
+        );
+
+        let context = extract_context(&headers).unwrap();
+        assert_eq!(context.state().trace_id(), TraceId { high: 0, low: 3 });
+        assert_eq!(context.state().span_id(), 4);
+    }
+
+    #[test]
+    fn test_extract_context_returns_none_without_trace_headers() {
+        assert!(extract_context(&HeaderMap::new()).is_none());
+    }
+}
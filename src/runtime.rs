@@ -0,0 +1,166 @@
+//! An embeddable runtime wrapping a [`Mini`] instance with start/reload/
+//! shutdown control, for hosts (GUIs, test harnesses) that want to run mini
+//! without spawning the `mini` binary as a subprocess.
+//!
+//! The HTTP proxy, TLS listener, DNS server and metrics endpoint mini ships
+//! with are still wired up inline in `main.rs`'s `main()`, not through this
+//! type - extracting that wiring without breaking the one binary that
+//! actually serves traffic is follow-up work. What [`Runtime`] and
+//! [`RuntimeHandle`] provide today is the control surface an embedder needs
+//! around the config/[`Mini`] lifecycle, including [`RuntimeHandle::shutdown`]'s
+//! cancel/drain/persist sequence; they're where the real server tasks will
+//! get stored and watch [`RuntimeHandle::cancellation_token`] once that
+//! extraction happens.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{MiniError, Result};
+use crate::instance::Mini;
+
+/// Entry point for embedding mini: load `config_path` and start a
+/// [`RuntimeHandle`] for it.
+pub struct Runtime;
+
+impl Runtime {
+    pub fn start(config_path: impl Into<PathBuf>) -> Result<RuntimeHandle> {
+        let mini = Mini::builder().config(config_path).build()?;
+        Ok(RuntimeHandle::new(mini))
+    }
+}
+
+/// A running (today: loaded) [`Mini`] instance that can be reloaded or told
+/// to shut down without the embedder dropping and recreating it.
+pub struct RuntimeHandle {
+    mini: Mutex<Arc<Mini>>,
+    cancel: CancellationToken,
+}
+
+impl RuntimeHandle {
+    fn new(mini: Mini) -> Self {
+        Self { mini: Mutex::new(Arc::new(mini)), cancel: CancellationToken::new() }
+    }
+
+    /// The current [`Mini`] instance, swapped out wholesale by [`Self::reload`].
+    pub async fn instance(&self) -> Arc<Mini> {
+        self.mini.lock().await.clone()
+    }
+
+    /// Re-read config from the same path the instance was started with,
+    /// rebuilding its [`crate::registry::DriverRegistry`]/
+    /// [`crate::site::SiteManager`] along with it, without the caller
+    /// needing to drop and recreate the handle.
+    pub async fn reload(&self) -> Result<()> {
+        let config_path = self.instance().await.config_path().clone();
+        let mini = Mini::builder().config(config_path).build()?;
+        *self.mini.lock().await = Arc::new(mini);
+        Ok(())
+    }
+
+    /// A token that flips to cancelled the moment [`Self::shutdown`] is
+    /// called. `main.rs`'s real HTTP/DNS/admin server tasks aren't spawned
+    /// through `Runtime` yet (see the module doc comment), so today nothing
+    /// in this crate actually watches it - but it's the handle an embedder
+    /// can clone into its own long-running tasks and `select!` against, and
+    /// the seam `main.rs`'s SIGINT/SIGTERM handling will drain through once
+    /// that wiring moves here.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    /// Begin a graceful shutdown: cancel [`Self::cancellation_token`], give
+    /// anything watching it up to `graceful` to drain, then persist the
+    /// current config back to `config_path` - the "flush and persist state"
+    /// half of graceful shutdown. There's no running HTTP/DNS server task
+    /// here to actually wait on draining yet, so the `graceful` window is
+    /// just a sleep for now; real drain work slots in where that sleep is
+    /// once the server tasks move into `Runtime`.
+    pub async fn shutdown(&self, graceful: Duration) -> Result<()> {
+        self.cancel.cancel();
+        tokio::time::sleep(graceful).await;
+
+        let mini = self.instance().await;
+        crate::cli::save_config(mini.config(), mini.config_path())
+            .map_err(|e| MiniError::Config(e.to_string()))?;
+        Ok(())
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_start_loads_the_given_config_path() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("config.yaml");
+
+        let handle = Runtime::start(&config_path).unwrap();
+        assert_eq!(handle.instance().await.config_path(), &config_path);
+    }
+
+    #[tokio::test]
+    async fn test_reload_picks_up_changes_written_to_disk() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("config.yaml");
+
+        let handle = Runtime::start(&config_path).unwrap();
+        assert_eq!(handle.instance().await.config().sites.len(), 0);
+
+        {
+            let mut config = handle.instance().await.config().clone();
+            config.sites.insert(
+                "app.test".to_string(),
+                crate::config::SiteConfig {
+                    root_dir: "/app".to_string(),
+                    domain: "app.test".to_string(),
+                    secure: false,
+                    php_version: None,
+                    env_vars: Default::default(),
+                    driver: None,
+                    notes: None,
+                    tags: Vec::new(),
+                    php_ini: Default::default(),
+                    xdebug: false,
+                    proxy_target: None,
+                },
+            );
+            crate::cli::save_config(&config, &config_path).unwrap();
+        }
+
+        handle.reload().await.unwrap();
+        assert_eq!(handle.instance().await.config().sites.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_cancels_the_token() {
+        let dir = TempDir::new().unwrap();
+        let handle = Runtime::start(dir.path().join("config.yaml")).unwrap();
+        let token = handle.cancellation_token();
+
+        assert!(!handle.is_shutdown());
+        assert!(!token.is_cancelled());
+        handle.shutdown(Duration::from_millis(0)).await.unwrap();
+        assert!(handle.is_shutdown());
+        assert!(token.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_persists_the_current_config() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("config.yaml");
+        let handle = Runtime::start(&config_path).unwrap();
+
+        handle.shutdown(Duration::from_millis(0)).await.unwrap();
+        assert!(config_path.exists());
+    }
+}
@@ -0,0 +1,252 @@
+//! Secure storage for provider tokens - ngrok/Cloudflare tunnel auth tokens,
+//! ACME account keys - that currently round-trip as plaintext through
+//! `~/.mini/config.yaml` (see [`crate::config::ServerConfig::ngrok_auth_token`]).
+//!
+//! [`SecretStore`] prefers the OS keychain (macOS Keychain / Secret Service /
+//! Windows Credential Manager, via the `keyring` crate) and falls back to an
+//! AES-256-GCM encrypted file alongside the config when no keychain backend
+//! is reachable, e.g. a headless box with no Secret Service daemon running.
+//! The encryption key itself lives in a sibling file with owner-only
+//! permissions on Unix - not hardware-backed security, but strictly better
+//! than the plaintext YAML it replaces.
+//!
+//! Not wired into `config.rs`/`share.rs` yet; this is the standalone building
+//! block a later pass routes `ServerConfig`'s token fields through.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rand::RngCore;
+
+/// Keychain service name under which every entry is namespaced.
+const SERVICE: &str = "dev.mini.mini";
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// Stores and retrieves secrets for a single config directory, preferring
+/// the OS keychain and falling back to an encrypted file next to it.
+pub struct SecretStore {
+    fallback_path: PathBuf,
+    key_path: PathBuf,
+}
+
+impl SecretStore {
+    /// `config_path` is the daemon's `config.yaml` path; the fallback store
+    /// and its key are kept alongside it.
+    pub fn new(config_path: &Path) -> Self {
+        Self {
+            fallback_path: config_path.with_file_name("secrets.enc"),
+            key_path: config_path.with_file_name("secrets.key"),
+        }
+    }
+
+    /// Store `value` under `account`, preferring the OS keychain.
+    pub fn store(&self, account: &str, value: &str) -> Result<()> {
+        match keyring::Entry::new(SERVICE, account) {
+            Ok(entry) if entry.set_password(value).is_ok() => Ok(()),
+            _ => self.store_fallback(account, value),
+        }
+    }
+
+    /// Look up the secret for `account`, checking the OS keychain first.
+    pub fn load(&self, account: &str) -> Result<Option<String>> {
+        if let Ok(entry) = keyring::Entry::new(SERVICE, account) {
+            match entry.get_password() {
+                Ok(value) => return Ok(Some(value)),
+                Err(keyring::Error::NoEntry) => {}
+                Err(_) => { /* keychain backend unavailable - fall through */ }
+            }
+        }
+        self.load_fallback(account)
+    }
+
+    /// Remove the secret for `account` from both the keychain and the
+    /// fallback store, ignoring either one if it was never set there.
+    pub fn delete(&self, account: &str) -> Result<()> {
+        if let Ok(entry) = keyring::Entry::new(SERVICE, account) {
+            let _ = entry.delete_credential();
+        }
+        self.delete_fallback(account)
+    }
+
+    fn store_fallback(&self, account: &str, value: &str) -> Result<()> {
+        let key = self.load_or_create_key()?;
+        let mut entries = self.read_fallback_entries()?;
+        entries.insert(account.to_string(), encrypt(&key, value)?);
+        self.write_fallback_entries(&entries)
+    }
+
+    fn load_fallback(&self, account: &str) -> Result<Option<String>> {
+        if !self.fallback_path.exists() {
+            return Ok(None);
+        }
+        let key = self.load_or_create_key()?;
+        let entries = self.read_fallback_entries()?;
+        entries.get(account).map(|blob| decrypt(&key, blob)).transpose()
+    }
+
+    fn delete_fallback(&self, account: &str) -> Result<()> {
+        if !self.fallback_path.exists() {
+            return Ok(());
+        }
+        let mut entries = self.read_fallback_entries()?;
+        entries.remove(account);
+        self.write_fallback_entries(&entries)
+    }
+
+    fn read_fallback_entries(&self) -> Result<HashMap<String, String>> {
+        if !self.fallback_path.exists() {
+            return Ok(HashMap::new());
+        }
+        let raw = fs::read_to_string(&self.fallback_path)
+            .with_context(|| format!("failed to read {}", self.fallback_path.display()))?;
+        Ok(serde_json::from_str(&raw).unwrap_or_default())
+    }
+
+    fn write_fallback_entries(&self, entries: &HashMap<String, String>) -> Result<()> {
+        let raw = serde_json::to_string(entries)?;
+        write_private(&self.fallback_path, raw.as_bytes())
+    }
+
+    fn load_or_create_key(&self) -> Result<[u8; KEY_LEN]> {
+        if let Ok(raw) = fs::read(&self.key_path) {
+            if raw.len() == KEY_LEN {
+                let mut key = [0u8; KEY_LEN];
+                key.copy_from_slice(&raw);
+                return Ok(key);
+            }
+        }
+        let mut key = [0u8; KEY_LEN];
+        rand::thread_rng().fill_bytes(&mut key);
+        write_private(&self.key_path, &key)?;
+        Ok(key)
+    }
+}
+
+/// Encrypt `value`, returning a base64 blob of `nonce || ciphertext`.
+fn encrypt(key: &[u8; KEY_LEN], value: &str) -> Result<String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+    let nonce = Nonce::from_slice(&nonce_bytes);
+    let ciphertext = cipher
+        .encrypt(nonce, value.as_bytes())
+        .map_err(|e| anyhow::anyhow!("failed to encrypt secret: {e}"))?;
+
+    let mut blob = nonce_bytes.to_vec();
+    blob.extend(ciphertext);
+    Ok(BASE64.encode(blob))
+}
+
+/// Inverse of [`encrypt`].
+fn decrypt(key: &[u8; KEY_LEN], blob: &str) -> Result<String> {
+    let raw = BASE64.decode(blob).context("fallback secret store is corrupt (bad base64)")?;
+    if raw.len() < NONCE_LEN {
+        anyhow::bail!("fallback secret store is corrupt (truncated entry)");
+    }
+    let (nonce_bytes, ciphertext) = raw.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce_bytes), ciphertext)
+        .map_err(|e| anyhow::anyhow!("failed to decrypt secret: {e}"))?;
+    String::from_utf8(plaintext).context("decrypted secret is not valid UTF-8")
+}
+
+/// Write `data` to `path`, creating it owner-readable-only from the start on
+/// Unix rather than writing with the umask's default mode and tightening
+/// permissions afterward - the key/entries file holds raw key material or
+/// ciphertext, and a chmod-after-write leaves a window where another local
+/// user could read it before the mode change lands.
+#[cfg(unix)]
+fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    file.write_all(data).with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+    fs::write(path, data).with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// The real keychain backends aren't available in a sandboxed test
+    /// environment, so these exercise the fallback path directly through
+    /// the public API - `store`/`load` will hit the keychain first, find no
+    /// backend, and fall through automatically.
+
+    fn store(dir: &tempfile::TempDir) -> SecretStore {
+        SecretStore::new(&dir.path().join("config.yaml"))
+    }
+
+    #[test]
+    fn test_store_then_load_round_trips_through_the_fallback_store() {
+        let dir = tempdir().unwrap();
+        let store = store(&dir);
+        store.store("ngrok", "super-secret-token").unwrap();
+        assert_eq!(store.load("ngrok").unwrap(), Some("super-secret-token".to_string()));
+    }
+
+    #[test]
+    fn test_load_returns_none_for_an_unknown_account() {
+        let dir = tempdir().unwrap();
+        let store = store(&dir);
+        assert_eq!(store.load("nobody").unwrap(), None);
+    }
+
+    #[test]
+    fn test_fallback_file_does_not_contain_the_plaintext_secret() {
+        let dir = tempdir().unwrap();
+        let store = store(&dir);
+        store.store("ngrok", "super-secret-token").unwrap();
+        let raw = fs::read_to_string(&store.fallback_path).unwrap();
+        assert!(!raw.contains("super-secret-token"));
+    }
+
+    #[test]
+    fn test_delete_removes_the_secret() {
+        let dir = tempdir().unwrap();
+        let store = store(&dir);
+        store.store("ngrok", "super-secret-token").unwrap();
+        store.delete("ngrok").unwrap();
+        assert_eq!(store.load("ngrok").unwrap(), None);
+    }
+
+    #[test]
+    fn test_store_overwrites_an_existing_secret() {
+        let dir = tempdir().unwrap();
+        let store = store(&dir);
+        store.store("ngrok", "first").unwrap();
+        store.store("ngrok", "second").unwrap();
+        assert_eq!(store.load("ngrok").unwrap(), Some("second".to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fallback_files_are_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempdir().unwrap();
+        let store = store(&dir);
+        store.store("ngrok", "super-secret-token").unwrap();
+        let mode = fs::metadata(&store.fallback_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
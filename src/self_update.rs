@@ -0,0 +1,220 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::config::ServerConfig;
+
+const RELEASES_API: &str = "https://api.github.com/repos/find-how/mini/releases/latest";
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// The release asset name mini publishes for the platform it's running on,
+/// e.g. `mini-linux-x86_64`.
+fn platform_asset_name() -> String {
+    format!("mini-{}-{}", env::consts::OS, env::consts::ARCH)
+}
+
+fn find_asset<'a>(release: &'a Release, name: &str) -> Result<&'a ReleaseAsset> {
+    release
+        .assets
+        .iter()
+        .find(|asset| asset.name == name)
+        .ok_or_else(|| anyhow::anyhow!("release {} has no asset named {name}", release.tag_name))
+}
+
+/// Ask GitHub for the latest published release.
+fn fetch_latest_release() -> Result<Release> {
+    let output = Command::new("curl")
+        .args(["-sSL", "-H", "User-Agent: mini-self-update", RELEASES_API])
+        .output()
+        .map_err(|e| anyhow::anyhow!("failed to run curl: {e}"))?;
+    if !output.status.success() {
+        anyhow::bail!("failed to fetch latest release: curl exited with {}", output.status);
+    }
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| anyhow::anyhow!("failed to parse release metadata: {e}"))
+}
+
+fn download(url: &str, dest: &Path) -> Result<()> {
+    let status = Command::new("curl")
+        .args(["-sSL", "-o"])
+        .arg(dest)
+        .arg(url)
+        .status()
+        .map_err(|e| anyhow::anyhow!("failed to run curl: {e}"))?;
+    if !status.success() {
+        anyhow::bail!("failed to download {url} (curl exited with {status})");
+    }
+    Ok(())
+}
+
+/// Verify `binary_path` against a `sha256sum`-formatted checksum file
+/// (`<hex digest>  <filename>`).
+fn verify_checksum(binary_path: &Path, checksum_path: &Path) -> Result<()> {
+    let checksum_file = fs::read_to_string(checksum_path)?;
+    let expected = checksum_file
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("malformed checksum file at {}", checksum_path.display()))?;
+
+    let output = Command::new("sha256sum")
+        .arg(binary_path)
+        .output()
+        .map_err(|e| anyhow::anyhow!("failed to run sha256sum: {e}"))?;
+    if !output.status.success() {
+        anyhow::bail!("failed to checksum {}", binary_path.display());
+    }
+    let actual = String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_string();
+
+    if actual != expected {
+        anyhow::bail!("checksum mismatch for {}: expected {expected}, got {actual}", binary_path.display());
+    }
+    Ok(())
+}
+
+/// Replace `current_exe` with the verified download at `staged_path`,
+/// preserving the executable bit. `fs::rename` within the same directory
+/// is atomic, so there's no window where `current_exe` is missing or
+/// partially written.
+fn swap_binary(staged_path: &Path, current_exe: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(staged_path)?.permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(staged_path, perms)?;
+    }
+    fs::rename(staged_path, current_exe)?;
+    Ok(())
+}
+
+/// Check for a newer release, verify its checksum, swap the running binary
+/// for it, and restart the daemon so the new version picks up ports
+/// 53/80/443 without the user having to do it by hand.
+pub fn run(config: &ServerConfig) -> Result<()> {
+    let release = fetch_latest_release()?;
+    let current_version = env!("CARGO_PKG_VERSION");
+    let latest_version = release.tag_name.trim_start_matches('v');
+
+    if latest_version == current_version {
+        println!("Already up to date ({current_version})");
+        return Ok(());
+    }
+    println!("Updating mini {current_version} -> {latest_version}");
+
+    let asset_name = platform_asset_name();
+    let asset = find_asset(&release, &asset_name)?;
+    let checksum_asset = find_asset(&release, &format!("{asset_name}.sha256"))?;
+
+    let current_exe = env::current_exe()?;
+    let staged_path = current_exe.with_file_name(format!("{asset_name}.new"));
+    let checksum_path = current_exe.with_file_name(format!("{asset_name}.sha256"));
+
+    download(&asset.browser_download_url, &staged_path)?;
+    download(&checksum_asset.browser_download_url, &checksum_path)?;
+
+    let verified = verify_checksum(&staged_path, &checksum_path);
+    let _ = fs::remove_file(&checksum_path);
+    verified?;
+
+    swap_binary(&staged_path, &current_exe)?;
+    println!("Updated to {latest_version}, restarting the daemon");
+
+    crate::daemon::restart(config, &crate::cli::default_config_path())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_platform_asset_name_includes_os_and_arch() {
+        let name = platform_asset_name();
+        assert!(name.starts_with("mini-"));
+        assert!(name.contains(env::consts::OS));
+        assert!(name.contains(env::consts::ARCH));
+    }
+
+    #[test]
+    fn test_find_asset_matches_by_name() {
+        let release = Release {
+            tag_name: "v1.2.3".to_string(),
+            assets: vec![ReleaseAsset {
+                name: "mini-linux-x86_64".to_string(),
+                browser_download_url: "https://example.test/mini".to_string(),
+            }],
+        };
+
+        assert!(find_asset(&release, "mini-linux-x86_64").is_ok());
+        assert!(find_asset(&release, "mini-macos-arm64").is_err());
+    }
+
+    #[test]
+    fn test_verify_checksum_accepts_a_matching_digest() {
+        let dir = TempDir::new().unwrap();
+        let binary_path = dir.path().join("mini-linux-x86_64");
+        fs::write(&binary_path, b"pretend binary contents").unwrap();
+
+        let digest = Command::new("sha256sum").arg(&binary_path).output().unwrap();
+        let checksum_path = dir.path().join("mini-linux-x86_64.sha256");
+        fs::write(&checksum_path, digest.stdout).unwrap();
+
+        assert!(verify_checksum(&binary_path, &checksum_path).is_ok());
+    }
+
+    #[test]
+    fn test_verify_checksum_rejects_a_tampered_binary() {
+        let dir = TempDir::new().unwrap();
+        let binary_path = dir.path().join("mini-linux-x86_64");
+        fs::write(&binary_path, b"pretend binary contents").unwrap();
+
+        let digest = Command::new("sha256sum").arg(&binary_path).output().unwrap();
+        let checksum_path = dir.path().join("mini-linux-x86_64.sha256");
+        fs::write(&checksum_path, digest.stdout).unwrap();
+
+        fs::write(&binary_path, b"tampered contents").unwrap();
+        assert!(verify_checksum(&binary_path, &checksum_path).is_err());
+    }
+
+    #[test]
+    fn test_swap_binary_replaces_the_current_exe_and_sets_it_executable() {
+        let dir = TempDir::new().unwrap();
+        let current_exe = dir.path().join("mini");
+        fs::write(&current_exe, b"old").unwrap();
+
+        let staged_path = dir.path().join("mini.new");
+        fs::write(&staged_path, b"new").unwrap();
+
+        swap_binary(&staged_path, &current_exe).unwrap();
+        assert_eq!(fs::read_to_string(&current_exe).unwrap(), "new");
+        assert!(!staged_path.exists());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&current_exe).unwrap().permissions().mode();
+            assert_eq!(mode & 0o111, 0o111);
+        }
+    }
+}
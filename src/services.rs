@@ -0,0 +1,185 @@
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// A database or cache mini can run in a container with sensible dev
+/// defaults, Herd/DBngin-style - `mini services start db` beats hand-rolling
+/// a `docker run` line and remembering which port you picked last time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManagedServiceKind {
+    Mysql,
+    Postgres,
+    Redis,
+}
+
+/// One container's sensible-default shape: the image to run, the port it
+/// listens on (also used as the host port, so `db.test:3306` - once DNS
+/// resolves `db.test`, which it already does for any name under a
+/// recognized TLD, see `dns::DnsHandler` - reaches the container directly),
+/// and the name it's started under.
+pub struct ServiceDefaults {
+    pub container_name: &'static str,
+    pub image: &'static str,
+    pub port: u16,
+    pub env: &'static [(&'static str, &'static str)],
+}
+
+impl ManagedServiceKind {
+    pub fn defaults(&self) -> ServiceDefaults {
+        match self {
+            ManagedServiceKind::Mysql => ServiceDefaults {
+                container_name: "mini-mysql",
+                image: "mysql:8",
+                port: 3306,
+                env: &[("MYSQL_ALLOW_EMPTY_PASSWORD", "yes")],
+            },
+            ManagedServiceKind::Postgres => ServiceDefaults {
+                container_name: "mini-postgres",
+                image: "postgres:16",
+                port: 5432,
+                env: &[("POSTGRES_HOST_AUTH_METHOD", "trust")],
+            },
+            ManagedServiceKind::Redis => ServiceDefaults {
+                container_name: "mini-redis",
+                image: "redis:7",
+                port: 6379,
+                env: &[],
+            },
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ManagedServiceKind::Mysql => "mysql",
+            ManagedServiceKind::Postgres => "postgres",
+            ManagedServiceKind::Redis => "redis",
+        }
+    }
+}
+
+fn docker(args: &[&str]) -> Result<std::process::Output> {
+    Command::new("docker")
+        .args(args)
+        .output()
+        .context("failed to run docker - is it installed and running?")
+}
+
+/// Start `kind`'s container if it isn't already running, using its default
+/// image/port/env. Idempotent: starting an already-running service is a
+/// no-op rather than an error, since "make sure the db is up" is the common
+/// case this gets called for.
+pub fn start(kind: ManagedServiceKind) -> Result<()> {
+    let defaults = kind.defaults();
+    if is_running(kind)? {
+        return Ok(());
+    }
+
+    // A previous `stop` leaves the (exited) container around rather than
+    // removing it - `docker start` revives it if so, otherwise `docker run`
+    // creates it fresh.
+    let revived = docker(&["start", defaults.container_name])?;
+    if revived.status.success() {
+        return Ok(());
+    }
+
+    let mut args = vec![
+        "run".to_string(),
+        "-d".to_string(),
+        "--name".to_string(),
+        defaults.container_name.to_string(),
+        "-p".to_string(),
+        format!("{port}:{port}", port = defaults.port),
+    ];
+    for (key, value) in defaults.env {
+        args.push("-e".to_string());
+        args.push(format!("{key}={value}"));
+    }
+    args.push(defaults.image.to_string());
+
+    let args_ref: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = docker(&args_ref)?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "docker run failed for {}: {}",
+            defaults.container_name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Stop `kind`'s container, leaving it in place (rather than removing it) so
+/// the next `start` can just revive it instead of re-pulling the image.
+pub fn stop(kind: ManagedServiceKind) -> Result<()> {
+    let defaults = kind.defaults();
+    let output = docker(&["stop", defaults.container_name])?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "docker stop failed for {}: {}",
+            defaults.container_name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Whether `kind`'s container is currently running - used by `mini status`
+/// and by `start` to decide whether there's anything to do.
+pub fn is_running(kind: ManagedServiceKind) -> Result<bool> {
+    let defaults = kind.defaults();
+    let filter = format!("name=^{}$", defaults.container_name);
+    let output = docker(&["ps", "--filter", filter.as_str(), "--format", "{{.Names}}"])?;
+    Ok(String::from_utf8_lossy(&output.stdout).lines().any(|name| name == defaults.container_name))
+}
+
+/// Point-in-time status for every known managed service kind, for `mini
+/// status` and `mini services list`.
+#[derive(Debug, Serialize)]
+pub struct ManagedServiceStatus {
+    pub kind: ManagedServiceKind,
+    pub container_name: String,
+    pub port: u16,
+    pub running: bool,
+}
+
+/// Gather status for every [`ManagedServiceKind`], best-effort - a kind
+/// whose `docker ps` call fails (no docker installed, daemon not running) is
+/// reported as not running rather than aborting the whole report.
+pub fn list() -> Vec<ManagedServiceStatus> {
+    [ManagedServiceKind::Mysql, ManagedServiceKind::Postgres, ManagedServiceKind::Redis]
+        .into_iter()
+        .map(|kind| {
+            let defaults = kind.defaults();
+            ManagedServiceStatus {
+                kind,
+                container_name: defaults.container_name.to_string(),
+                port: defaults.port,
+                running: is_running(kind).unwrap_or(false),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_use_distinct_ports_and_container_names() {
+        let all = [ManagedServiceKind::Mysql, ManagedServiceKind::Postgres, ManagedServiceKind::Redis];
+        let ports: Vec<u16> = all.iter().map(|k| k.defaults().port).collect();
+        let names: Vec<&str> = all.iter().map(|k| k.defaults().container_name).collect();
+
+        assert_eq!(ports.len(), ports.iter().collect::<std::collections::HashSet<_>>().len());
+        assert_eq!(names.len(), names.iter().collect::<std::collections::HashSet<_>>().len());
+    }
+
+    #[test]
+    fn test_mysql_defaults() {
+        let defaults = ManagedServiceKind::Mysql.defaults();
+        assert_eq!(defaults.image, "mysql:8");
+        assert_eq!(defaults.port, 3306);
+    }
+}
@@ -0,0 +1,541 @@
+use std::io::BufRead;
+use std::io::BufReader;
+use std::process::{Child, ChildStderr, ChildStdout, Command, Stdio};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use nix::sys::signal::{self, Signal};
+use nix::unistd::Pid;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::ServerConfig;
+
+/// A running tunnel session: the public URL a provider assigned, the pid of
+/// the process backing it so `unshare` can tear it down, and when it
+/// started, for scripts that want to know how fresh a URL is before
+/// registering it as a webhook target.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+pub struct ShareSession {
+    pub provider: String,
+    pub url: String,
+    pub pid: u32,
+    #[serde(default)]
+    pub started_at: u64,
+    /// When this share is considered expired, per [`ServerConfig::share_ttl_secs`]
+    /// at the time it started - `None` if no TTL was configured.
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+}
+
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// A way to expose a local address to the public internet.
+pub trait TunnelProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// Start a tunnel to `local_addr`, blocking until the provider reports
+    /// the public URL it assigned. `host_header` is the `Host` header the
+    /// provider should forward to `local_addr` instead of its own public
+    /// hostname, so mini's reverse proxy routes the tunneled traffic to the
+    /// right site.
+    fn start(&self, local_addr: &str, host_header: &str) -> Result<ShareSession>;
+    /// Tear down a tunnel previously returned by `start`.
+    fn stop(&self, session: &ShareSession) -> Result<()>;
+}
+
+/// Exposes a local address with `ngrok http`, reading the assigned public
+/// URL back from ngrok's own JSON-formatted log output.
+pub struct NgrokProvider {
+    /// Passed as `--authtoken`, if set, so `mini share` can use a
+    /// registered ngrok account rather than its anonymous tier.
+    pub auth_token: Option<String>,
+}
+
+impl TunnelProvider for NgrokProvider {
+    fn name(&self) -> &'static str {
+        "ngrok"
+    }
+
+    fn start(&self, local_addr: &str, host_header: &str) -> Result<ShareSession> {
+        let mut args = vec![
+            "http".to_string(),
+            local_addr.to_string(),
+            format!("--host-header={host_header}"),
+            "--log=stdout".to_string(),
+            "--log-format=json".to_string(),
+        ];
+        if let Some(token) = &self.auth_token {
+            args.push(format!("--authtoken={token}"));
+        }
+
+        let mut child = Command::new("ngrok")
+            .args(&args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("failed to launch ngrok: {e}"))?;
+        let pid = child.id();
+
+        let mut reader = BufReader::new(child.stdout.take().expect("stdout was piped"));
+        let url = read_tunnel_url(&mut reader, &mut child)?;
+
+        // Keep draining stdout in the background so ngrok's pipe never fills
+        // up and blocks the tunnel once we stop reading from it here.
+        thread::spawn(move || {
+            let mut discarded = String::new();
+            while reader.read_line(&mut discarded).unwrap_or(0) > 0 {
+                discarded.clear();
+            }
+        });
+
+        Ok(ShareSession {
+            provider: self.name().to_string(),
+            url,
+            pid,
+            ..Default::default()
+        })
+    }
+
+    fn stop(&self, session: &ShareSession) -> Result<()> {
+        signal::kill(Pid::from_raw(session.pid as i32), Signal::SIGTERM)
+            .map_err(|e| anyhow::anyhow!("failed to stop {}: {e}", session.provider))
+    }
+}
+
+/// Read ngrok's JSON-formatted log lines until one reports a started tunnel
+/// with its public URL.
+fn read_tunnel_url(reader: &mut BufReader<ChildStdout>, child: &mut Child) -> Result<String> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            anyhow::bail!(
+                "ngrok exited before reporting a public URL (status: {:?})",
+                child.try_wait()?
+            );
+        }
+        let Ok(entry) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        if entry.get("msg").and_then(Value::as_str) == Some("started tunnel") {
+            if let Some(url) = entry.get("url").and_then(Value::as_str) {
+                return Ok(url.to_string());
+            }
+        }
+    }
+}
+
+/// Exposes a local address with Cloudflare Tunnel (`cloudflared`), as an
+/// alternative to [`NgrokProvider`]. Without `tunnel_token` this runs a
+/// throwaway "quick tunnel" (a random `*.trycloudflare.com` hostname, no
+/// Cloudflare account required); with `tunnel_token` set it runs a named
+/// tunnel against credentials created ahead of time with `cloudflared
+/// tunnel create`, whose public hostname is already fixed in the Cloudflare
+/// dashboard rather than something `start` needs to discover.
+pub struct CloudflaredProvider {
+    pub tunnel_token: Option<String>,
+}
+
+impl TunnelProvider for CloudflaredProvider {
+    fn name(&self) -> &'static str {
+        "cloudflared"
+    }
+
+    fn start(&self, local_addr: &str, host_header: &str) -> Result<ShareSession> {
+        match &self.tunnel_token {
+            Some(token) => self.start_named(token, local_addr, host_header),
+            None => self.start_quick(local_addr, host_header),
+        }
+    }
+
+    fn stop(&self, session: &ShareSession) -> Result<()> {
+        signal::kill(Pid::from_raw(session.pid as i32), Signal::SIGTERM)
+            .map_err(|e| anyhow::anyhow!("failed to stop {}: {e}", session.provider))
+    }
+}
+
+impl CloudflaredProvider {
+    /// A throwaway `*.trycloudflare.com` tunnel, whose HTTPS origin is
+    /// `local_addr` over plain HTTP (Cloudflare terminates TLS at the edge,
+    /// same as ngrok's default), with `host_header` forwarded instead of
+    /// the `trycloudflare.com` hostname so mini's reverse proxy routes the
+    /// tunneled traffic to the right site.
+    fn start_quick(&self, local_addr: &str, host_header: &str) -> Result<ShareSession> {
+        let mut child = Command::new("cloudflared")
+            .args([
+                "tunnel",
+                "--no-autoupdate",
+                "--url",
+                &format!("http://{local_addr}"),
+                "--http-host-header",
+                host_header,
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("failed to launch cloudflared: {e}"))?;
+        let pid = child.id();
+
+        let mut reader = BufReader::new(child.stderr.take().expect("stderr was piped"));
+        let url = read_quick_tunnel_url(&mut reader, &mut child)?;
+
+        // cloudflared logs continuously to stderr; keep draining it in the
+        // background so the pipe never fills up and blocks the tunnel once
+        // we stop reading from it here (same reasoning as NgrokProvider).
+        thread::spawn(move || {
+            let mut discarded = String::new();
+            while reader.read_line(&mut discarded).unwrap_or(0) > 0 {
+                discarded.clear();
+            }
+        });
+
+        Ok(ShareSession { provider: self.name().to_string(), url, pid, ..Default::default() })
+    }
+
+    /// A named tunnel, connected with credentials for a tunnel already
+    /// created and pointed at a fixed public hostname via the Cloudflare
+    /// dashboard/DNS - `host_header` *is* that hostname, so there's no
+    /// startup output to parse a URL out of.
+    fn start_named(&self, token: &str, local_addr: &str, host_header: &str) -> Result<ShareSession> {
+        let child = Command::new("cloudflared")
+            .args([
+                "tunnel",
+                "--no-autoupdate",
+                "run",
+                "--token",
+                token,
+                "--url",
+                &format!("http://{local_addr}"),
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("failed to launch cloudflared: {e}"))?;
+        let pid = child.id();
+
+        Ok(ShareSession {
+            provider: self.name().to_string(),
+            url: format!("https://{host_header}"),
+            pid,
+            ..Default::default()
+        })
+    }
+}
+
+/// Read cloudflared's plain-text log lines until one reports the quick
+/// tunnel's assigned `*.trycloudflare.com` URL. Unlike ngrok, cloudflared
+/// doesn't have a stable JSON log format for this, so we scan for the
+/// hostname it's known to print rather than parsing structured output.
+fn read_quick_tunnel_url(reader: &mut BufReader<ChildStderr>, child: &mut Child) -> Result<String> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            anyhow::bail!(
+                "cloudflared exited before reporting a public URL (status: {:?})",
+                child.try_wait()?
+            );
+        }
+        if let Some(start) = line.find("https://") {
+            let candidate = line[start..].split_whitespace().next().unwrap_or("");
+            if candidate.contains(".trycloudflare.com") {
+                return Ok(candidate.trim_end_matches('|').trim().to_string());
+            }
+        }
+    }
+}
+
+/// Exposes a local address with Expose (`expose share`), the tunnel tool
+/// popular in the Laravel community and the one Valet itself integrates
+/// with. Supports an existing Expose account token, a self-hosted Expose
+/// server, a requested subdomain, and HTTP basic-auth passthrough so a
+/// shared site keeps requiring credentials through the tunnel.
+pub struct ExposeProvider {
+    /// Passed as `--token`, if set, to authenticate against an Expose
+    /// account rather than sharing anonymously.
+    pub token: Option<String>,
+    /// Passed as `--server-host`, if set, to use a self-hosted Expose
+    /// server instead of the hosted `sharedwithexpose.com` service.
+    pub server: Option<String>,
+    /// Passed as `--subdomain`, if set, to request a specific subdomain
+    /// rather than a randomly assigned one.
+    pub subdomain: Option<String>,
+    /// `user:password`, if set, passed as `--auth` so requests through the
+    /// tunnel are still gated behind HTTP basic auth.
+    pub basic_auth: Option<String>,
+}
+
+impl TunnelProvider for ExposeProvider {
+    fn name(&self) -> &'static str {
+        "expose"
+    }
+
+    fn start(&self, local_addr: &str, host_header: &str) -> Result<ShareSession> {
+        let mut args = vec![
+            "share".to_string(),
+            format!("http://{local_addr}"),
+            format!("--host-header={host_header}"),
+        ];
+        if let Some(token) = &self.token {
+            args.push(format!("--token={token}"));
+        }
+        if let Some(server) = &self.server {
+            args.push(format!("--server-host={server}"));
+        }
+        if let Some(subdomain) = &self.subdomain {
+            args.push(format!("--subdomain={subdomain}"));
+        }
+        if let Some(auth) = &self.basic_auth {
+            args.push(format!("--auth={auth}"));
+        }
+
+        let mut child = Command::new("expose")
+            .args(&args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("failed to launch expose: {e}"))?;
+        let pid = child.id();
+
+        let mut reader = BufReader::new(child.stdout.take().expect("stdout was piped"));
+        let url = read_expose_url(&mut reader, &mut child)?;
+
+        // Expose keeps printing request logs to stdout once the tunnel is
+        // up; keep draining them in the background for the same reason as
+        // the other providers above.
+        thread::spawn(move || {
+            let mut discarded = String::new();
+            while reader.read_line(&mut discarded).unwrap_or(0) > 0 {
+                discarded.clear();
+            }
+        });
+
+        Ok(ShareSession { provider: self.name().to_string(), url, pid, ..Default::default() })
+    }
+
+    fn stop(&self, session: &ShareSession) -> Result<()> {
+        signal::kill(Pid::from_raw(session.pid as i32), Signal::SIGTERM)
+            .map_err(|e| anyhow::anyhow!("failed to stop {}: {e}", session.provider))
+    }
+}
+
+/// Read Expose's plain-text startup banner until it prints the public URL
+/// it assigned. Like cloudflared, Expose has no stable structured log
+/// format to parse instead - unlike cloudflared's fixed `trycloudflare.com`
+/// suffix, the hostname here depends on `--server-host`/`--subdomain`, so
+/// the first `https://` URL on the line announcing the tunnel is taken as
+/// authoritative.
+fn read_expose_url(reader: &mut BufReader<ChildStdout>, child: &mut Child) -> Result<String> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            anyhow::bail!(
+                "expose exited before reporting a public URL (status: {:?})",
+                child.try_wait()?
+            );
+        }
+        if let Some(start) = line.find("https://") {
+            let candidate = line[start..].split_whitespace().next().unwrap_or("");
+            if !candidate.is_empty() {
+                return Ok(candidate.trim_end_matches('|').trim().to_string());
+            }
+        }
+    }
+}
+
+/// Start sharing `domain`, recording the resulting session on `config` so
+/// it's queryable while the tunnel runs. Re-running this for an already
+/// shared domain just returns the existing session instead of starting a
+/// second tunnel.
+pub fn start(
+    config: &mut ServerConfig,
+    domain: &str,
+    provider: &dyn TunnelProvider,
+) -> Result<ShareSession> {
+    if let Some(existing) = config.shares.get(domain) {
+        return Ok(existing.clone());
+    }
+    match config.sites.get(domain) {
+        Some(site) if !site.secure => {
+            anyhow::bail!("{domain} is not secured - run `mini secure {domain}` before sharing it")
+        }
+        None => anyhow::bail!("{domain} is not a linked site"),
+        Some(_) => {}
+    }
+
+    let local_addr = format!("127.0.0.1:{}", config.share_port);
+    let mut session = provider.start(&local_addr, domain)?;
+    session.started_at = now_unix();
+    session.expires_at = config.share_ttl_secs.map(|ttl| session.started_at + ttl);
+    config.shares.insert(domain.to_string(), session.clone());
+    Ok(session)
+}
+
+/// Stop sharing `domain`, if it's currently shared.
+pub fn stop(config: &mut ServerConfig, domain: &str, provider: &dyn TunnelProvider) -> Result<()> {
+    match config.shares.remove(domain) {
+        Some(session) => provider.stop(&session),
+        None => anyhow::bail!("{domain} is not currently shared"),
+    }
+}
+
+/// Render `url` as a QR code of half-block Unicode characters, compact
+/// enough to scan directly from a terminal - so a share's public URL can be
+/// opened on a phone for responsive testing without retyping it.
+pub fn terminal_qr(url: &str) -> Result<String> {
+    use qrcode::render::unicode;
+    use qrcode::QrCode;
+
+    let code = QrCode::new(url).context("failed to encode share URL as a QR code")?;
+    Ok(code.render::<unicode::Dense1x2>().build())
+}
+
+/// Look up the public URL for an already-active share, e.g. for a script
+/// that needs the current tunnel URL to register a webhook. Doesn't start
+/// a tunnel on its own - use `start` for that.
+pub fn fetch_url(config: &ServerConfig, domain: &str) -> Result<&str> {
+    config
+        .shares
+        .get(domain)
+        .map(|session| session.url.as_str())
+        .ok_or_else(|| anyhow::anyhow!("{domain} is not currently shared"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SiteConfig;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn secured_site(domain: &str) -> SiteConfig {
+        SiteConfig {
+            root_dir: format!("/sites/{domain}"),
+            domain: domain.to_string(),
+            secure: true,
+            php_version: None,
+            env_vars: Default::default(),
+            driver: None,
+            notes: None,
+            tags: Vec::new(),
+            php_ini: Default::default(),
+            xdebug: false,
+            proxy_target: None,
+        }
+    }
+
+    /// A fake provider that hands back a deterministic session without
+    /// shelling out to a real tunnel binary.
+    struct MockProvider {
+        stopped: AtomicU32,
+    }
+
+    impl TunnelProvider for MockProvider {
+        fn name(&self) -> &'static str {
+            "mock"
+        }
+
+        fn start(&self, local_addr: &str, host_header: &str) -> Result<ShareSession> {
+            Ok(ShareSession {
+                provider: "mock".to_string(),
+                url: format!("https://example.test/{local_addr}/{host_header}"),
+                pid: 4242,
+                ..Default::default()
+            })
+        }
+
+        fn stop(&self, _session: &ShareSession) -> Result<()> {
+            self.stopped.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_start_records_the_session_and_is_idempotent() {
+        let mut config = ServerConfig::default();
+        config.sites.insert("app.test".to_string(), secured_site("app.test"));
+        let provider = MockProvider { stopped: AtomicU32::new(0) };
+
+        let session = start(&mut config, "app.test", &provider).unwrap();
+        assert_eq!(config.shares.get("app.test"), Some(&session));
+        assert!(session.started_at > 0);
+        assert!(session.url.ends_with("/app.test"));
+
+        // Sharing an already-shared domain returns the stored session
+        // rather than starting a second tunnel.
+        let again = start(&mut config, "app.test", &provider).unwrap();
+        assert_eq!(again, session);
+    }
+
+    #[test]
+    fn test_start_computes_expiry_from_the_configured_ttl() {
+        let mut config = ServerConfig::default();
+        config.sites.insert("app.test".to_string(), secured_site("app.test"));
+        config.share_ttl_secs = Some(3600);
+        let provider = MockProvider { stopped: AtomicU32::new(0) };
+
+        let session = start(&mut config, "app.test", &provider).unwrap();
+        assert_eq!(session.expires_at, Some(session.started_at + 3600));
+    }
+
+    #[test]
+    fn test_start_leaves_expiry_unset_without_a_configured_ttl() {
+        let mut config = ServerConfig::default();
+        config.sites.insert("app.test".to_string(), secured_site("app.test"));
+        let provider = MockProvider { stopped: AtomicU32::new(0) };
+
+        let session = start(&mut config, "app.test", &provider).unwrap();
+        assert_eq!(session.expires_at, None);
+    }
+
+    #[test]
+    fn test_start_requires_a_linked_site() {
+        let mut config = ServerConfig::default();
+        let provider = MockProvider { stopped: AtomicU32::new(0) };
+
+        assert!(start(&mut config, "app.test", &provider).is_err());
+    }
+
+    #[test]
+    fn test_start_requires_the_site_to_be_secured() {
+        let mut config = ServerConfig::default();
+        let mut site = secured_site("app.test");
+        site.secure = false;
+        config.sites.insert("app.test".to_string(), site);
+        let provider = MockProvider { stopped: AtomicU32::new(0) };
+
+        assert!(start(&mut config, "app.test", &provider).is_err());
+    }
+
+    #[test]
+    fn test_fetch_url_requires_an_active_share() {
+        let mut config = ServerConfig::default();
+        config.sites.insert("app.test".to_string(), secured_site("app.test"));
+        let provider = MockProvider { stopped: AtomicU32::new(0) };
+
+        assert!(fetch_url(&config, "app.test").is_err());
+
+        let session = start(&mut config, "app.test", &provider).unwrap();
+        assert_eq!(fetch_url(&config, "app.test").unwrap(), session.url);
+    }
+
+    #[test]
+    fn test_stop_requires_an_active_share() {
+        let mut config = ServerConfig::default();
+        config.sites.insert("app.test".to_string(), secured_site("app.test"));
+        let provider = MockProvider { stopped: AtomicU32::new(0) };
+
+        assert!(stop(&mut config, "app.test", &provider).is_err());
+
+        start(&mut config, "app.test", &provider).unwrap();
+        stop(&mut config, "app.test", &provider).unwrap();
+        assert!(!config.shares.contains_key("app.test"));
+        assert_eq!(provider.stopped.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,19 +1,81 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use anyhow::Result;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
 use tokio::sync::RwLock;
+use tracing::info;
 
+use crate::config::ParkedPath;
+use crate::driver::Driver;
+use crate::error::{MiniError, Result};
+use crate::parking::{scan_parked_paths, ParkConflict};
 use crate::registry::DriverRegistry;
 
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Cumulative usage counters for a site, tracked in memory by the running
+/// daemon (not persisted) so "which of my 40 linked sites have I actually
+/// touched lately" is answerable without grepping logs. Held behind an
+/// [`Arc`] inside [`Site`] so a [`Site`] returned by [`SiteManager::get_site`]
+/// shares the same counters as the one in the registry, rather than freezing
+/// a snapshot at lookup time.
+#[derive(Debug, Default)]
+struct SiteStatsInner {
+    requests: AtomicU64,
+    bytes: AtomicU64,
+    errors: AtomicU64,
+    last_accessed_unix: AtomicU64,
+}
+
+/// A point-in-time, serializable snapshot of a site's [`SiteStatsInner`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct SiteStats {
+    pub requests: u64,
+    pub bytes: u64,
+    pub errors: u64,
+    /// Unix timestamp of the last recorded request, or `None` if the site
+    /// hasn't seen one since the daemon started.
+    pub last_accessed_unix: Option<u64>,
+}
+
+/// What changed after a parked-directory rescan.
+#[derive(Debug, Clone, Default)]
+pub struct RescanDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub conflicts: Vec<ParkConflict>,
+}
+
 /// Represents a site configuration.
 /// Currently only used for testing, but will be expanded in the future
 /// to support more site-specific configuration.
+///
+/// [`Driver`] trait objects aren't [`std::fmt::Debug`] or [`Serialize`], so
+/// both impls are written by hand below rather than derived, substituting
+/// [`Site::driver_name`] for the `driver` field itself - the same
+/// substitution [`SiteStatus`] makes for API/CLI consumers that want a
+/// fully owned, serializable snapshot instead.
 #[derive(Clone)]
 pub struct Site {
     domain: String,
     path: PathBuf,
     secure: bool,
+    driver: Option<Arc<dyn Driver>>,
+    notes: Option<String>,
+    tags: Vec<String>,
+    disabled: bool,
+    from_parked: bool,
+    php_version: Option<String>,
+    aliases: Vec<String>,
+    stats: Arc<SiteStatsInner>,
 }
 
 impl Site {
@@ -22,13 +84,52 @@ impl Site {
             domain,
             path,
             secure: false,
+            driver: None,
+            notes: None,
+            tags: Vec::new(),
+            disabled: false,
+            from_parked: false,
+            php_version: None,
+            aliases: Vec::new(),
+            stats: Arc::new(SiteStatsInner::default()),
+        }
+    }
+
+    /// Record one request against this site's usage counters.
+    pub fn record_request(&self, bytes: u64, is_error: bool) {
+        self.stats.requests.fetch_add(1, Ordering::Relaxed);
+        self.stats.bytes.fetch_add(bytes, Ordering::Relaxed);
+        if is_error {
+            self.stats.errors.fetch_add(1, Ordering::Relaxed);
         }
+        self.stats.last_accessed_unix.store(now_unix(), Ordering::Relaxed);
+    }
+
+    /// A snapshot of this site's cumulative usage counters.
+    pub fn stats(&self) -> SiteStats {
+        let last_accessed_unix = self.stats.last_accessed_unix.load(Ordering::Relaxed);
+        SiteStats {
+            requests: self.stats.requests.load(Ordering::Relaxed),
+            bytes: self.stats.bytes.load(Ordering::Relaxed),
+            errors: self.stats.errors.load(Ordering::Relaxed),
+            last_accessed_unix: if last_accessed_unix == 0 { None } else { Some(last_accessed_unix) },
+        }
+    }
+
+    /// True if this site was discovered by scanning a parked directory,
+    /// rather than linked explicitly.
+    pub fn is_from_parked(&self) -> bool {
+        self.from_parked
     }
 
     pub fn secure(&mut self) {
         self.secure = true;
     }
 
+    pub fn is_disabled(&self) -> bool {
+        self.disabled
+    }
+
     pub fn domain(&self) -> &str {
         &self.domain
     }
@@ -40,6 +141,121 @@ impl Site {
     pub fn is_secure(&self) -> bool {
         self.secure
     }
+
+    /// Name of the driver currently serving this site, if one was resolved.
+    pub fn driver_name(&self) -> Option<&'static str> {
+        self.driver.as_ref().map(|d| d.name())
+    }
+
+    pub fn notes(&self) -> Option<&str> {
+        self.notes.as_deref()
+    }
+
+    pub fn set_notes(&mut self, notes: Option<String>) {
+        self.notes = notes;
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags = tags;
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    pub fn php_version(&self) -> Option<&str> {
+        self.php_version.as_deref()
+    }
+
+    /// Other domains that should also route to this site.
+    pub fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    /// An owned, serializable snapshot of this site - what the admin API
+    /// and the CLI's `--json` mode should both converge on returning instead
+    /// of each shaping their own ad hoc JSON.
+    pub fn status(&self) -> SiteStatus {
+        SiteStatus {
+            domain: self.domain.clone(),
+            path: self.path.clone(),
+            secure: self.secure,
+            driver: self.driver_name().map(str::to_string),
+            notes: self.notes.clone(),
+            tags: self.tags.clone(),
+            disabled: self.disabled,
+            from_parked: self.from_parked,
+            php_version: self.php_version.clone(),
+            aliases: self.aliases.clone(),
+            stats: self.stats(),
+            cert_expiry_unix: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for Site {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Site")
+            .field("domain", &self.domain)
+            .field("path", &self.path)
+            .field("secure", &self.secure)
+            .field("driver", &self.driver_name())
+            .field("notes", &self.notes)
+            .field("tags", &self.tags)
+            .field("disabled", &self.disabled)
+            .field("from_parked", &self.from_parked)
+            .field("php_version", &self.php_version)
+            .field("aliases", &self.aliases)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl Serialize for Site {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        self.status().serialize(serializer)
+    }
+}
+
+/// An owned, serializable snapshot of a [`Site`] - a driver name in place of
+/// the [`Driver`] trait object `Site` actually holds, so this can derive
+/// [`Serialize`] and be handed to an API/CLI consumer without reaching back
+/// into the live [`SiteManager`].
+#[derive(Debug, Clone, Serialize)]
+pub struct SiteStatus {
+    pub domain: String,
+    pub path: PathBuf,
+    pub secure: bool,
+    pub driver: Option<String>,
+    pub notes: Option<String>,
+    pub tags: Vec<String>,
+    pub disabled: bool,
+    pub from_parked: bool,
+    pub php_version: Option<String>,
+    pub aliases: Vec<String>,
+    pub stats: SiteStats,
+    /// Unix timestamp the site's TLS certificate expires at, if mini issued
+    /// one. Always `None` today - `secure_site` just flips [`Site::secure`]
+    /// to `true`, and there's no certificate store tracking expiry anywhere
+    /// in this tree yet - but it's reserved here so that when one exists it
+    /// has a field to report through without another breaking change to
+    /// this type.
+    pub cert_expiry_unix: Option<u64>,
+}
+
+/// Options bundled onto a single [`SiteManager::link`] call, so setting up a
+/// new project is one operation instead of separate `link`/`secure`/isolate
+/// calls that would each rewrite the site in turn.
+#[derive(Debug, Clone, Default)]
+pub struct LinkOptions {
+    pub driver_override: Option<String>,
+    pub secure: bool,
+    pub php_version: Option<String>,
+    pub aliases: Vec<String>,
 }
 
 /// Manages site configurations and their associated drivers.
@@ -58,19 +274,134 @@ impl SiteManager {
         }
     }
 
+    /// Link a site, resolving its driver.
+    ///
+    /// When `driver_override` is set (typically from `SiteConfig.driver`), the
+    /// named driver is looked up in the registry and used as-is, erroring if
+    /// it isn't registered. Otherwise the driver is auto-detected from the
+    /// site's files.
+    pub async fn link_site(
+        &self,
+        domain: &str,
+        path: PathBuf,
+        driver_override: Option<&str>,
+    ) -> Result<()> {
+        let driver = match driver_override {
+            Some(name) => Some(
+                self.registry
+                    .get(name)
+                    .ok_or_else(|| MiniError::Driver(format!("unknown driver: {name}")))?,
+            ),
+            None => self.registry.detect(&path),
+        };
+
+        let mut site = Site::new(domain.to_string(), path);
+        site.driver = driver;
+
+        info!(domain, driver = ?site.driver_name(), "linked site");
+
+        let mut sites = self.sites.write().await;
+        sites.insert(domain.to_string(), site);
+        Ok(())
+    }
+
     pub async fn add_site(&self, domain: &str, path: PathBuf) -> Result<()> {
+        self.link_site(domain, path, None).await
+    }
+
+    /// Link a site with every `LinkOptions` applied in the same call,
+    /// instead of `link_site` followed by separate `secure_site`/isolate/
+    /// alias calls that would each rewrite the site on their own.
+    pub async fn link(&self, domain: &str, path: PathBuf, options: LinkOptions) -> Result<()> {
+        self.link_site(domain, path, options.driver_override.as_deref())
+            .await?;
+
         let mut sites = self.sites.write().await;
-        sites.insert(domain.to_string(), Site::new(domain.to_string(), path));
+        let site = sites
+            .get_mut(domain)
+            .expect("link_site just inserted this domain");
+        if options.secure {
+            site.secure();
+        }
+        site.php_version = options.php_version;
+        site.aliases = options.aliases;
         Ok(())
     }
 
+    /// Record one request against `domain`'s usage counters. A no-op if the
+    /// domain isn't linked - nothing in the live request path consults
+    /// `SiteManager` to route yet (see [`MyProxy::upstream_peer`][peer]), so
+    /// callers that want real per-site stats today have to call this
+    /// themselves once that routing exists.
+    ///
+    /// [peer]: crate::MyProxy::upstream_peer
+    pub async fn record_request(&self, domain: &str, bytes: u64, is_error: bool) {
+        let sites = self.sites.read().await;
+        if let Some(site) = sites.get(domain) {
+            site.record_request(bytes, is_error);
+        }
+    }
+
+    pub async fn unlink_site(&self, domain: &str) -> Result<()> {
+        let mut sites = self.sites.write().await;
+        if sites.remove(domain).is_some() {
+            Ok(())
+        } else {
+            Err(MiniError::NotFound(domain.to_string()))
+        }
+    }
+
+    /// Rescan the configured parked directories, idempotently reconciling
+    /// them against currently-linked sites: new folders are linked, folders
+    /// that disappeared are unlinked, and sites linked explicitly (not from
+    /// a parked directory) are left untouched. Returns what changed.
+    pub async fn rescan_parked(&self, parked_paths: &[ParkedPath]) -> Result<RescanDiff> {
+        let (candidates, conflicts) = scan_parked_paths(parked_paths);
+
+        let previously_parked: Vec<String> = {
+            let sites = self.sites.read().await;
+            sites
+                .values()
+                .filter(|site| site.from_parked)
+                .map(|site| site.domain.clone())
+                .collect()
+        };
+
+        let mut added = Vec::new();
+        for candidate in &candidates {
+            if !previously_parked.contains(&candidate.domain) {
+                self.link_site(&candidate.domain, candidate.path.clone(), None)
+                    .await?;
+                let mut sites = self.sites.write().await;
+                if let Some(site) = sites.get_mut(&candidate.domain) {
+                    site.from_parked = true;
+                }
+                added.push(candidate.domain.clone());
+            }
+        }
+
+        let mut removed = Vec::new();
+        for domain in previously_parked {
+            if !candidates.iter().any(|c| c.domain == domain) {
+                self.unlink_site(&domain).await?;
+                removed.push(domain);
+            }
+        }
+
+        Ok(RescanDiff {
+            added,
+            removed,
+            conflicts,
+        })
+    }
+
     pub async fn secure_site(&self, domain: &str) -> Result<()> {
         let mut sites = self.sites.write().await;
         if let Some(site) = sites.get_mut(domain) {
             site.secure();
             Ok(())
         } else {
-            anyhow::bail!("Site not found")
+            Err(MiniError::NotFound(domain.to_string()))
         }
     }
 
@@ -79,18 +410,80 @@ impl SiteManager {
         sites.get(domain).cloned()
     }
 
+    /// Look up a site for serving a live request. Returns `None` for disabled
+    /// sites even though they remain configured, so routing (HTTP/DNS) can
+    /// show a "disabled" page instead of the real site.
+    pub async fn lookup_for_routing(&self, domain: &str) -> Option<Site> {
+        let sites = self.sites.read().await;
+        sites.get(domain).filter(|site| !site.disabled).cloned()
+    }
+
+    /// Pause a site: it stays configured (cert, isolation, driver) but is
+    /// skipped by routing until re-enabled.
+    pub async fn disable(&self, domain: &str) -> Result<()> {
+        let mut sites = self.sites.write().await;
+        if let Some(site) = sites.get_mut(domain) {
+            site.disabled = true;
+            Ok(())
+        } else {
+            Err(MiniError::NotFound(domain.to_string()))
+        }
+    }
+
+    pub async fn enable(&self, domain: &str) -> Result<()> {
+        let mut sites = self.sites.write().await;
+        if let Some(site) = sites.get_mut(domain) {
+            site.disabled = false;
+            Ok(())
+        } else {
+            Err(MiniError::NotFound(domain.to_string()))
+        }
+    }
+
+    /// List all sites, optionally filtered to those carrying `tag`.
+    pub async fn list_sites(&self, tag: Option<&str>) -> Vec<Site> {
+        let sites = self.sites.read().await;
+        sites
+            .values()
+            .filter(|site| tag.map_or(true, |t| site.has_tag(t)))
+            .cloned()
+            .collect()
+    }
+
+    /// Attach free-form notes and tags to an already-linked site.
+    pub async fn set_metadata(
+        &self,
+        domain: &str,
+        notes: Option<String>,
+        tags: Vec<String>,
+    ) -> Result<()> {
+        let mut sites = self.sites.write().await;
+        if let Some(site) = sites.get_mut(domain) {
+            site.set_notes(notes);
+            site.set_tags(tags);
+            Ok(())
+        } else {
+            Err(MiniError::NotFound(domain.to_string()))
+        }
+    }
+
     pub async fn start_site(&self, domain: &str) -> Result<()> {
         let sites = self.sites.read().await;
         if let Some(site) = sites.get(domain) {
-            // Try to find a driver that supports this site
-            if let Some(driver) = self.registry.get("Laravel") {
-                if driver.supports(site.path()) {
-                    return driver.start().await;
+            match &site.driver {
+                Some(driver) => {
+                    let ctx = crate::driver::DriverContext {
+                        path: site.path.clone(),
+                        php_version: site.php_version.clone(),
+                        pool_manager: Some(self.registry.pool_manager()),
+                        ..Default::default()
+                    };
+                    driver.start(&ctx).await.map_err(|e| MiniError::Driver(e.to_string()))
                 }
+                None => Err(MiniError::Driver("no suitable driver found for site".to_string())),
             }
-            anyhow::bail!("No suitable driver found for site")
         } else {
-            anyhow::bail!("Site not found")
+            Err(MiniError::NotFound(domain.to_string()))
         }
     }
 }
@@ -101,6 +494,11 @@ mod tests {
     use tempfile::TempDir;
     use tokio::fs;
     use crate::driver::LaravelDriver;
+    use crate::php_fpm::{self, PoolManager};
+
+    fn test_pool_manager() -> Arc<PoolManager> {
+        Arc::new(PoolManager::new(php_fpm::default_runtime_dir()))
+    }
 
     #[tokio::test]
     async fn test_site_manager() {
@@ -119,6 +517,9 @@ mod tests {
         registry.register(Arc::new(LaravelDriver::new(
             site_path.clone(),
             "8.2".to_string(),
+            HashMap::new(),
+            false,
+            test_pool_manager(),
         )));
 
         // Test adding a site
@@ -141,4 +542,246 @@ mod tests {
         // Test getting a non-existent site
         assert!(manager.get_site("nonexistent.test").await.is_none());
     }
+
+    #[tokio::test]
+    async fn test_link_site_driver_override() {
+        let registry = Arc::new(DriverRegistry::new());
+        let manager = SiteManager::new(registry.clone());
+
+        let temp_dir = TempDir::new().unwrap();
+        let site_path = temp_dir.path().to_path_buf();
+
+        // A Laravel-shaped site that would normally auto-detect as Laravel.
+        fs::create_dir_all(site_path.join("public")).await.unwrap();
+        fs::write(site_path.join("artisan"), "").await.unwrap();
+        fs::write(site_path.join("public/index.php"), "").await.unwrap();
+
+        registry.register(Arc::new(LaravelDriver::new(
+            site_path.clone(),
+            "8.2".to_string(),
+            HashMap::new(),
+            false,
+            test_pool_manager(),
+        )));
+
+        // Auto-detection picks up Laravel.
+        manager
+            .link_site("auto.test", site_path.clone(), None)
+            .await
+            .unwrap();
+        let site = manager.get_site("auto.test").await.unwrap();
+        assert_eq!(site.driver_name(), Some("Laravel"));
+
+        // An unknown override errors clearly instead of falling back to detection.
+        let err = manager
+            .link_site("forced.test", site_path.clone(), Some("static"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("static"));
+    }
+
+    #[tokio::test]
+    async fn test_link_applies_every_option_in_one_call() {
+        let registry = Arc::new(DriverRegistry::new());
+        let manager = SiteManager::new(registry);
+
+        manager
+            .link(
+                "app.test",
+                PathBuf::from("/app"),
+                LinkOptions {
+                    driver_override: None,
+                    secure: true,
+                    php_version: Some("8.3".to_string()),
+                    aliases: vec!["app-alias.test".to_string()],
+                },
+            )
+            .await
+            .unwrap();
+
+        let site = manager.get_site("app.test").await.unwrap();
+        assert!(site.is_secure());
+        assert_eq!(site.php_version(), Some("8.3"));
+        assert_eq!(site.aliases(), ["app-alias.test".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_site_metadata_and_tag_filtering() {
+        let registry = Arc::new(DriverRegistry::new());
+        let manager = SiteManager::new(registry);
+
+        manager
+            .add_site("a.test", PathBuf::from("/a"))
+            .await
+            .unwrap();
+        manager
+            .add_site("b.test", PathBuf::from("/b"))
+            .await
+            .unwrap();
+
+        manager
+            .set_metadata(
+                "a.test",
+                Some("client X, staging DB".to_string()),
+                vec!["client-x".to_string(), "staging".to_string()],
+            )
+            .await
+            .unwrap();
+
+        let site = manager.get_site("a.test").await.unwrap();
+        assert_eq!(site.notes(), Some("client X, staging DB"));
+        assert!(site.has_tag("staging"));
+
+        assert_eq!(manager.list_sites(None).await.len(), 2);
+        let staging = manager.list_sites(Some("staging")).await;
+        assert_eq!(staging.len(), 1);
+        assert_eq!(staging[0].domain(), "a.test");
+
+        assert!(manager.set_metadata("missing.test", None, vec![]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_disable_enable_site() {
+        let registry = Arc::new(DriverRegistry::new());
+        let manager = SiteManager::new(registry);
+
+        manager
+            .add_site("a.test", PathBuf::from("/a"))
+            .await
+            .unwrap();
+
+        assert!(manager.lookup_for_routing("a.test").await.is_some());
+
+        manager.disable("a.test").await.unwrap();
+        assert!(manager.lookup_for_routing("a.test").await.is_none());
+        // Still configured, just not routed.
+        assert!(manager.get_site("a.test").await.unwrap().is_disabled());
+
+        manager.enable("a.test").await.unwrap();
+        assert!(manager.lookup_for_routing("a.test").await.is_some());
+
+        assert!(manager.disable("missing.test").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rescan_parked_adds_and_removes() {
+        let registry = Arc::new(DriverRegistry::new());
+        let manager = SiteManager::new(registry);
+
+        let parked_dir = TempDir::new().unwrap();
+        fs::create_dir_all(parked_dir.path().join("blog")).await.unwrap();
+        fs::create_dir_all(parked_dir.path().join("shop")).await.unwrap();
+
+        let parked_paths = vec![crate::config::ParkedPath::new(
+            parked_dir.path().to_str().unwrap(),
+        )];
+
+        let diff = manager.rescan_parked(&parked_paths).await.unwrap();
+        assert_eq!(diff.added.len(), 2);
+        assert!(diff.removed.is_empty());
+        assert!(manager.get_site("blog").await.is_some());
+        assert!(manager.get_site("shop").await.is_some());
+
+        // Rescanning unchanged directories is a no-op.
+        let diff = manager.rescan_parked(&parked_paths).await.unwrap();
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+
+        // Remove "shop" on disk; rescan should unlink it but keep "blog".
+        fs::remove_dir(parked_dir.path().join("shop")).await.unwrap();
+        let diff = manager.rescan_parked(&parked_paths).await.unwrap();
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.removed, vec!["shop".to_string()]);
+        assert!(manager.get_site("shop").await.is_none());
+        assert!(manager.get_site("blog").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_rescan_leaves_explicitly_linked_sites_alone() {
+        let registry = Arc::new(DriverRegistry::new());
+        let manager = SiteManager::new(registry);
+
+        manager
+            .add_site("explicit.test", PathBuf::from("/explicit"))
+            .await
+            .unwrap();
+
+        let diff = manager.rescan_parked(&[]).await.unwrap();
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert!(manager.get_site("explicit.test").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_record_request_accumulates_usage_stats() {
+        let registry = Arc::new(DriverRegistry::new());
+        let manager = SiteManager::new(registry);
+
+        manager.add_site("example.test", PathBuf::from("/explicit")).await.unwrap();
+
+        let stats = manager.get_site("example.test").await.unwrap().stats();
+        assert_eq!(stats, SiteStats::default());
+
+        manager.record_request("example.test", 100, false).await;
+        manager.record_request("example.test", 50, true).await;
+
+        let stats = manager.get_site("example.test").await.unwrap().stats();
+        assert_eq!(stats.requests, 2);
+        assert_eq!(stats.bytes, 150);
+        assert_eq!(stats.errors, 1);
+        assert!(stats.last_accessed_unix.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_record_request_on_an_unlinked_domain_is_a_no_op() {
+        let registry = Arc::new(DriverRegistry::new());
+        let manager = SiteManager::new(registry);
+
+        manager.record_request("nonexistent.test", 100, false).await;
+    }
+
+    #[tokio::test]
+    async fn test_unlink_missing_site_returns_not_found() {
+        let registry = Arc::new(DriverRegistry::new());
+        let manager = SiteManager::new(registry);
+
+        let err = manager.unlink_site("missing.test").await.unwrap_err();
+        assert!(matches!(err, MiniError::NotFound(ref domain) if domain == "missing.test"));
+    }
+
+    #[tokio::test]
+    async fn test_link_site_with_an_unknown_driver_override_returns_driver_error() {
+        let registry = Arc::new(DriverRegistry::new());
+        let manager = SiteManager::new(registry);
+
+        let err = manager
+            .link_site("app.test", PathBuf::from("/app"), Some("no-such-driver"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MiniError::Driver(_)));
+    }
+
+    #[test]
+    fn test_site_status_has_no_driver_for_a_driverless_site() {
+        let site = Site::new("app.test".to_string(), PathBuf::from("/app"));
+        let status = site.status();
+
+        assert_eq!(status.domain, "app.test");
+        assert_eq!(status.path, PathBuf::from("/app"));
+        assert_eq!(status.driver, None);
+        assert_eq!(status.cert_expiry_unix, None);
+    }
+
+    #[test]
+    fn test_site_serializes_as_its_status() {
+        let mut site = Site::new("app.test".to_string(), PathBuf::from("/app"));
+        site.secure();
+        site.record_request(1024, false);
+
+        let as_value = serde_json::to_value(&site).unwrap();
+        let status_value = serde_json::to_value(site.status()).unwrap();
+        assert_eq!(as_value, status_value);
+        assert_eq!(as_value["secure"], true);
+        assert_eq!(as_value["stats"]["requests"], 1);
+    }
 }
@@ -0,0 +1,119 @@
+//! systemd socket activation (`sd_listen_fds(3)`), so the daemon can
+//! receive already-bound 53/80/443 sockets from systemd and run as an
+//! unprivileged user - the counterpart to `systemd.rs`'s
+//! `AmbientCapabilities=CAP_NET_BIND_SERVICE`, for an admin who'd rather
+//! hand out sockets than grant a capability.
+//!
+//! Not wired into `main.rs`'s real listener setup - pingora's
+//! `Service::add_tcp`/`add_udp` take a socket address and bind it
+//! themselves, not a pre-bound file descriptor, so actually using what
+//! this module returns there needs pingora API support this commit
+//! doesn't add.
+
+use std::env;
+#[cfg(unix)]
+use std::net::{TcpListener, UdpSocket};
+#[cfg(unix)]
+use std::os::unix::io::FromRawFd;
+
+/// The first fd systemd hands off to an activated unit, per
+/// `sd_listen_fds(3)` - fds `0`/`1`/`2` stay stdin/stdout/stderr.
+const SD_LISTEN_FDS_START: i32 = 3;
+
+/// How many sockets systemd passed this process, from `LISTEN_FDS` - `0` if
+/// unset, or if `LISTEN_PID` doesn't match this process. systemd sets both
+/// when activating a unit; checking `LISTEN_PID` avoids treating a stale
+/// `LISTEN_FDS` inherited across an `exec` (with no matching `LISTEN_PID`)
+/// as real.
+pub fn listen_fds() -> u32 {
+    let pid_matches = env::var("LISTEN_PID")
+        .ok()
+        .and_then(|pid| pid.parse::<u32>().ok())
+        .map(|pid| pid == std::process::id())
+        .unwrap_or(false);
+    if !pid_matches {
+        return 0;
+    }
+    env::var("LISTEN_FDS").ok().and_then(|n| n.parse().ok()).unwrap_or(0)
+}
+
+/// Take the `index`-th socket systemd passed (0-based) as a [`TcpListener`] -
+/// `None` if `index >= `[`listen_fds`]`()`.
+///
+/// # Safety
+/// Only valid to call once per `index` - this takes ownership of the fd, so
+/// calling it twice for the same index hands out two owners of the same
+/// underlying socket, and the fd must actually be a bound, listening TCP
+/// socket (true of anything systemd passes via socket activation, not
+/// necessarily true otherwise).
+#[cfg(unix)]
+pub unsafe fn take_tcp_listener(index: u32) -> Option<TcpListener> {
+    if index >= listen_fds() {
+        return None;
+    }
+    Some(TcpListener::from_raw_fd(SD_LISTEN_FDS_START + index as i32))
+}
+
+/// Like [`take_tcp_listener`], but as a [`UdpSocket`] - for the DNS
+/// listener, which binds UDP rather than TCP.
+///
+/// # Safety
+/// Same caveats as [`take_tcp_listener`], for a bound UDP socket instead of
+/// a listening TCP one.
+#[cfg(unix)]
+pub unsafe fn take_udp_socket(index: u32) -> Option<UdpSocket> {
+    if index >= listen_fds() {
+        return None;
+    }
+    Some(UdpSocket::from_raw_fd(SD_LISTEN_FDS_START + index as i32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // `LISTEN_FDS`/`LISTEN_PID` are process-global state, and tests in this
+    // module run concurrently on Rust's default test harness - this mutex
+    // keeps them from stomping on each other's env vars mid-assertion.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_listen_fds_is_zero_with_no_env_vars_set() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        env::remove_var("LISTEN_PID");
+        env::remove_var("LISTEN_FDS");
+        assert_eq!(listen_fds(), 0);
+    }
+
+    #[test]
+    fn test_listen_fds_ignores_a_stale_listen_fds_for_another_pid() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        env::set_var("LISTEN_PID", "1");
+        env::set_var("LISTEN_FDS", "3");
+        assert_eq!(listen_fds(), 0);
+        env::remove_var("LISTEN_PID");
+        env::remove_var("LISTEN_FDS");
+    }
+
+    #[test]
+    fn test_listen_fds_reports_the_count_for_this_pid() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        env::set_var("LISTEN_PID", std::process::id().to_string());
+        env::set_var("LISTEN_FDS", "2");
+        assert_eq!(listen_fds(), 2);
+        env::remove_var("LISTEN_PID");
+        env::remove_var("LISTEN_FDS");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_take_tcp_listener_is_none_past_the_passed_count() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        env::set_var("LISTEN_PID", std::process::id().to_string());
+        env::set_var("LISTEN_FDS", "1");
+        assert!(unsafe { take_tcp_listener(1) }.is_none());
+        env::remove_var("LISTEN_PID");
+        env::remove_var("LISTEN_FDS");
+    }
+}
@@ -0,0 +1,201 @@
+use serde::Serialize;
+use std::net::{TcpListener, UdpSocket};
+use std::path::Path;
+
+use crate::config::ServerConfig;
+
+/// Whether something is already listening on an address, probed by trying
+/// (and failing) to bind it ourselves.
+fn is_tcp_listening(addr: &str) -> bool {
+    TcpListener::bind(addr).is_err()
+}
+
+fn is_udp_listening(addr: &str) -> bool {
+    UdpSocket::bind(addr).is_err()
+}
+
+/// Common locations for a PHP-FPM version's unix socket across platforms.
+/// We only check for the socket file's existence, not that anything is
+/// actually listening on it.
+pub(crate) fn php_fpm_socket_candidates(version: &str) -> [String; 3] {
+    [
+        format!("/var/run/php/php{version}-fpm.sock"),
+        format!("/usr/local/var/run/php/php{version}-fpm.sock"),
+        format!("/opt/homebrew/var/run/php/php{version}-fpm.sock"),
+    ]
+}
+
+fn is_php_fpm_available(version: &str) -> bool {
+    php_fpm_socket_candidates(version)
+        .iter()
+        .any(|candidate| Path::new(candidate).exists())
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListenerStatus {
+    pub addr: String,
+    pub running: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PhpFpmStatus {
+    pub version: String,
+    pub available: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusReport {
+    pub http: ListenerStatus,
+    pub https: ListenerStatus,
+    pub dns: ListenerStatus,
+    /// Whether mini's local CA is installed in the system trust store.
+    /// Certificate management isn't implemented yet, so this is always false.
+    pub ca_trusted: bool,
+    /// PHP-FPM availability for every version isolated by a linked site,
+    /// sorted by version.
+    pub php_fpm: Vec<PhpFpmStatus>,
+    pub site_count: usize,
+    pub parked_path_count: usize,
+    /// Managed database/cache containers and whether each is running - see
+    /// `services::list`.
+    pub managed_services: Vec<crate::services::ManagedServiceStatus>,
+    pub healthy: bool,
+}
+
+/// Gather a point-in-time status report by probing the configured listener
+/// addresses. This only tells you whether *something* is bound to those
+/// ports, not necessarily mini itself.
+pub fn gather_status(config: &ServerConfig) -> StatusReport {
+    let http = ListenerStatus {
+        addr: config.http_listen_addr.clone(),
+        running: is_tcp_listening(&config.http_listen_addr),
+    };
+    let https = ListenerStatus {
+        addr: config.https_listen_addr.clone(),
+        running: is_tcp_listening(&config.https_listen_addr),
+    };
+    let dns = ListenerStatus {
+        addr: "0.0.0.0:53".to_string(),
+        running: is_udp_listening("0.0.0.0:53"),
+    };
+
+    let mut php_versions: Vec<&str> = config
+        .sites
+        .values()
+        .filter_map(|site| site.php_version.as_deref())
+        .collect();
+    php_versions.sort_unstable();
+    php_versions.dedup();
+    let php_fpm = php_versions
+        .into_iter()
+        .map(|version| PhpFpmStatus {
+            version: version.to_string(),
+            available: is_php_fpm_available(version),
+        })
+        .collect();
+
+    let healthy = http.running && dns.running;
+
+    StatusReport {
+        http,
+        https,
+        dns,
+        ca_trusted: false,
+        php_fpm,
+        site_count: config.sites.len(),
+        parked_path_count: config.parked_paths.len(),
+        managed_services: crate::services::list(),
+        healthy,
+    }
+}
+
+impl StatusReport {
+    pub fn print_human(&self) {
+        println!("HTTP   {}  {}", self.http.addr, running_label(self.http.running));
+        println!("HTTPS  {}  {}", self.https.addr, running_label(self.https.running));
+        println!("DNS    {}  {}", self.dns.addr, running_label(self.dns.running));
+        println!("CA trusted: {}", self.ca_trusted);
+        for fpm in &self.php_fpm {
+            println!(
+                "PHP-FPM {:<6} {}",
+                fpm.version,
+                running_label(fpm.available)
+            );
+        }
+        println!("Sites: {}  Parked paths: {}", self.site_count, self.parked_path_count);
+        for service in &self.managed_services {
+            println!(
+                "{:<10} {}",
+                service.kind.label(),
+                running_label(service.running)
+            );
+        }
+        println!("Overall: {}", if self.healthy { "healthy" } else { "degraded" });
+    }
+}
+
+fn running_label(running: bool) -> &'static str {
+    if running {
+        "running"
+    } else {
+        "stopped"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gather_status_reports_free_ports_as_stopped() {
+        let mut config = ServerConfig::default();
+        // Use ports that are most likely free in a test environment.
+        config.http_listen_addr = "127.0.0.1:18080".to_string();
+        config.https_listen_addr = "127.0.0.1:18443".to_string();
+
+        let status = gather_status(&config);
+        assert!(!status.http.running);
+        assert!(!status.https.running);
+        assert!(!status.healthy);
+    }
+
+    #[test]
+    fn test_gather_status_counts_sites_and_parked_paths() {
+        let mut config = ServerConfig::default();
+        config.add_parked_path("/Users/test/Sites");
+        let status = gather_status(&config);
+        assert_eq!(status.parked_path_count, 1);
+        assert_eq!(status.site_count, 0);
+    }
+
+    #[test]
+    fn test_gather_status_dedupes_php_versions_across_sites() {
+        use crate::config::SiteConfig;
+        use std::collections::HashMap;
+
+        let mut config = ServerConfig::default();
+        for (domain, php_version) in [("a.test", "8.2"), ("b.test", "8.2"), ("c.test", "8.1")] {
+            config.add_site(
+                domain.to_string(),
+                SiteConfig {
+                    root_dir: format!("/Users/test/Sites/{domain}"),
+                    domain: domain.to_string(),
+                    secure: false,
+                    php_version: Some(php_version.to_string()),
+                    env_vars: HashMap::new(),
+                    driver: None,
+                    notes: None,
+                    tags: Vec::new(),
+                    php_ini: Default::default(),
+                    xdebug: false,
+                    proxy_target: None,
+                },
+            );
+        }
+
+        let status = gather_status(&config);
+        let versions: Vec<&str> = status.php_fpm.iter().map(|f| f.version.as_str()).collect();
+        assert_eq!(versions, vec!["8.1", "8.2"]);
+        assert!(status.php_fpm.iter().all(|f| !f.available));
+    }
+}
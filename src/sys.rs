@@ -0,0 +1,251 @@
+//! Filesystem and process abstractions for testability.
+//!
+//! `driver.rs`, `php_fpm.rs`, and mini's (not-yet-built, see `tld.rs`'s
+//! `reissue_certificates`) cert store all call `std::fs`/`tokio::fs` and
+//! `tokio::process::Command` directly today - that's not changed by this
+//! module. `Fs` and `ProcessRunner` exist so *new* unit tests (driver
+//! detection, setup logic) can swap in [`MockFs`]/[`MockProcessRunner`]
+//! instead of touching the real filesystem or spawning real processes;
+//! migrating the existing call sites over to them is a separate, larger
+//! change this commit doesn't make.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+/// A filesystem, abstracted so code that only needs to check for a file's
+/// presence or read/write small amounts of text can be unit-tested against
+/// [`MockFs`] instead of a real [`tempfile::TempDir`].
+#[async_trait]
+pub trait Fs: Send + Sync {
+    fn exists(&self, path: &Path) -> bool;
+    async fn read_to_string(&self, path: &Path) -> io::Result<String>;
+    async fn write(&self, path: &Path, contents: &str) -> io::Result<()>;
+    async fn copy(&self, from: &Path, to: &Path) -> io::Result<()>;
+    async fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+}
+
+/// The real filesystem, via `tokio::fs`.
+pub struct RealFs;
+
+#[async_trait]
+impl Fs for RealFs {
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    async fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        tokio::fs::read_to_string(path).await
+    }
+
+    async fn write(&self, path: &Path, contents: &str) -> io::Result<()> {
+        tokio::fs::write(path, contents).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> io::Result<()> {
+        tokio::fs::copy(from, to).await.map(|_| ())
+    }
+
+    async fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        tokio::fs::create_dir_all(path).await
+    }
+}
+
+/// An in-memory [`Fs`] for tests - paths that were `write`/`copy`d into it
+/// exist and read back what was written; nothing ever touches disk.
+#[derive(Default)]
+pub struct MockFs {
+    files: Mutex<HashMap<PathBuf, String>>,
+}
+
+impl MockFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seed a file as if it had already existed before the test started.
+    pub fn with_file(self, path: impl Into<PathBuf>, contents: impl Into<String>) -> Self {
+        self.files.lock().unwrap().insert(path.into(), contents.into());
+        self
+    }
+}
+
+#[async_trait]
+impl Fs for MockFs {
+    fn exists(&self, path: &Path) -> bool {
+        self.files.lock().unwrap().contains_key(path)
+    }
+
+    async fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(path)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.display().to_string()))
+    }
+
+    async fn write(&self, path: &Path, contents: &str) -> io::Result<()> {
+        self.files.lock().unwrap().insert(path.to_path_buf(), contents.to_string());
+        Ok(())
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let contents = self.read_to_string(from).await?;
+        self.write(to, &contents).await
+    }
+
+    async fn create_dir_all(&self, _path: &Path) -> io::Result<()> {
+        // MockFs has no concept of directories - a file's existence is
+        // tracked by its full path, not by walking a tree - so there's
+        // nothing to do beyond succeeding like the real thing would.
+        Ok(())
+    }
+}
+
+/// The outcome of [`ProcessRunner::run`]: a real child process's exit
+/// status can't be constructed outside `std::process` (there's no public
+/// constructor), so this stands in for it wherever a mock needs to report
+/// success or failure without actually spawning anything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunOutcome {
+    success: bool,
+}
+
+impl RunOutcome {
+    pub fn success(&self) -> bool {
+        self.success
+    }
+}
+
+impl From<ExitStatus> for RunOutcome {
+    fn from(status: ExitStatus) -> Self {
+        RunOutcome { success: status.success() }
+    }
+}
+
+/// A child-process launcher, abstracted so code that shells out (`composer
+/// install`, a driver's build step) can be unit-tested against
+/// [`MockProcessRunner`] instead of requiring that binary to actually be
+/// installed in the test environment.
+#[async_trait]
+pub trait ProcessRunner: Send + Sync {
+    async fn run(&self, program: &str, args: &[&str], current_dir: &Path) -> io::Result<RunOutcome>;
+}
+
+/// The real process launcher, via `tokio::process::Command`.
+pub struct RealProcessRunner;
+
+#[async_trait]
+impl ProcessRunner for RealProcessRunner {
+    async fn run(&self, program: &str, args: &[&str], current_dir: &Path) -> io::Result<RunOutcome> {
+        let status = tokio::process::Command::new(program)
+            .args(args)
+            .current_dir(current_dir)
+            .status()
+            .await?;
+        Ok(status.into())
+    }
+}
+
+/// A [`ProcessRunner`] for tests that records every invocation and returns
+/// a scripted outcome instead of actually spawning `program`.
+#[derive(Default)]
+pub struct MockProcessRunner {
+    outcome: RunOutcome,
+    calls: Mutex<Vec<(String, Vec<String>)>>,
+}
+
+impl Default for RunOutcome {
+    /// A bare `MockProcessRunner::new()` reports success, so a test only
+    /// has to opt into failure via [`MockProcessRunner::failing`].
+    fn default() -> Self {
+        RunOutcome { success: true }
+    }
+}
+
+impl MockProcessRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a runner whose `run` always reports failure.
+    pub fn failing() -> Self {
+        MockProcessRunner {
+            outcome: RunOutcome { success: false },
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Every `(program, args)` pair `run` was called with, in call order.
+    pub fn calls(&self) -> Vec<(String, Vec<String>)> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl ProcessRunner for MockProcessRunner {
+    async fn run(&self, program: &str, args: &[&str], _current_dir: &Path) -> io::Result<RunOutcome> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push((program.to_string(), args.iter().map(|a| a.to_string()).collect()));
+        Ok(self.outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_fs_roundtrips_writes() {
+        let fs = MockFs::new();
+        let path = PathBuf::from("/site/.env");
+        assert!(!fs.exists(&path));
+
+        fs.write(&path, "APP_ENV=local").await.unwrap();
+        assert!(fs.exists(&path));
+        assert_eq!(fs.read_to_string(&path).await.unwrap(), "APP_ENV=local");
+    }
+
+    #[tokio::test]
+    async fn test_mock_fs_with_file_seeds_existing_contents() {
+        let fs = MockFs::new().with_file("/site/.env.example", "APP_ENV=local");
+        assert!(fs.exists(Path::new("/site/.env.example")));
+        assert!(!fs.exists(Path::new("/site/.env")));
+    }
+
+    #[tokio::test]
+    async fn test_mock_fs_copy_reads_source_and_writes_destination() {
+        let fs = MockFs::new().with_file("/site/.env.example", "APP_ENV=local");
+        fs.copy(Path::new("/site/.env.example"), Path::new("/site/.env")).await.unwrap();
+        assert_eq!(fs.read_to_string(Path::new("/site/.env")).await.unwrap(), "APP_ENV=local");
+    }
+
+    #[tokio::test]
+    async fn test_mock_fs_read_missing_file_errors() {
+        let fs = MockFs::new();
+        assert!(fs.read_to_string(Path::new("/nope")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mock_process_runner_records_calls_and_succeeds_by_default() {
+        let runner = MockProcessRunner::new();
+        let outcome = runner.run("composer", &["install"], Path::new("/site")).await.unwrap();
+
+        assert!(outcome.success());
+        assert_eq!(runner.calls(), vec![("composer".to_string(), vec!["install".to_string()])]);
+    }
+
+    #[tokio::test]
+    async fn test_mock_process_runner_failing_reports_failure() {
+        let runner = MockProcessRunner::failing();
+        let outcome = runner.run("composer", &["install"], Path::new("/site")).await.unwrap();
+        assert!(!outcome.success());
+    }
+}
@@ -0,0 +1,136 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+use crate::cli::ServiceAction;
+use crate::config::ServerConfig;
+
+const UNIT_NAME: &str = "mini.service";
+
+/// Run a `ServiceAction` against the systemd unit for `config`.
+pub fn dispatch(action: ServiceAction, config: &ServerConfig) -> Result<()> {
+    if cfg!(not(target_os = "linux")) {
+        anyhow::bail!("systemd service management is only supported on Linux");
+    }
+
+    match action {
+        ServiceAction::Install { user } => install(config, user),
+        ServiceAction::Uninstall { user } => uninstall(user),
+    }
+}
+
+/// Where the unit file lives for a system-wide vs. per-user install.
+fn unit_path(user: bool) -> Result<PathBuf> {
+    if user {
+        let home = std::env::var("HOME").context("HOME is not set")?;
+        Ok(PathBuf::from(home)
+            .join(".config/systemd/user")
+            .join(UNIT_NAME))
+    } else {
+        Ok(PathBuf::from("/etc/systemd/system").join(UNIT_NAME))
+    }
+}
+
+/// Render the unit file contents: points at the current executable, runs it
+/// in the foreground, and ships the same hardening directives a careful
+/// admin would add by hand.
+fn unit_contents(exe: &Path, config: &ServerConfig) -> String {
+    format!(
+        "[Unit]\n\
+         Description=mini local development server\n\
+         After=network.target\n\
+         \n\
+         [Service]\n\
+         Type=simple\n\
+         # Listens on {http} (HTTP), {https} (HTTPS), and 0.0.0.0:53 (DNS)\n\
+         ExecStart={exe} start --foreground\n\
+         Restart=on-failure\n\
+         RestartSec=2\n\
+         AmbientCapabilities=CAP_NET_BIND_SERVICE\n\
+         NoNewPrivileges=true\n\
+         ProtectSystem=strict\n\
+         ProtectHome=read-only\n\
+         PrivateTmp=true\n\
+         PrivateDevices=true\n\
+         \n\
+         [Install]\n\
+         WantedBy=default.target\n",
+        exe = exe.display(),
+        http = config.http_listen_addr,
+        https = config.https_listen_addr,
+    )
+}
+
+fn run_systemctl(user: bool, args: &[&str]) -> Result<()> {
+    let mut command = Command::new("systemctl");
+    if user {
+        command.arg("--user");
+    }
+    command.args(args);
+
+    let status = command
+        .status()
+        .context("failed to run systemctl - is it installed?")?;
+    if !status.success() {
+        anyhow::bail!("systemctl {args:?} exited with {status}");
+    }
+    Ok(())
+}
+
+fn install(config: &ServerConfig, user: bool) -> Result<()> {
+    let exe = std::env::current_exe().context("could not determine the mini executable path")?;
+    let path = unit_path(user)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, unit_contents(&exe, config))?;
+
+    run_systemctl(user, &["daemon-reload"])?;
+    run_systemctl(user, &["enable", "--now", UNIT_NAME])?;
+
+    println!("Installed and started {}", path.display());
+    Ok(())
+}
+
+pub(crate) fn uninstall(user: bool) -> Result<()> {
+    let path = unit_path(user)?;
+
+    // Best-effort: the unit may already be stopped or missing from systemd's
+    // view if the file was deleted out from under it.
+    let _ = run_systemctl(user, &["disable", "--now", UNIT_NAME]);
+
+    if path.exists() {
+        fs::remove_file(&path)?;
+    }
+    run_systemctl(user, &["daemon-reload"])?;
+
+    println!("Uninstalled {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_contents_references_executable_and_listeners() {
+        let config = ServerConfig::default();
+        let contents = unit_contents(Path::new("/usr/local/bin/mini"), &config);
+
+        assert!(contents.contains("ExecStart=/usr/local/bin/mini start --foreground"));
+        assert!(contents.contains(&config.http_listen_addr));
+        assert!(contents.contains(&config.https_listen_addr));
+        assert!(contents.contains("AmbientCapabilities=CAP_NET_BIND_SERVICE"));
+    }
+
+    #[test]
+    fn test_unit_path_differs_for_user_vs_system() {
+        let system_path = unit_path(false).unwrap();
+        assert_eq!(system_path, PathBuf::from("/etc/systemd/system/mini.service"));
+
+        let user_path = unit_path(true).unwrap();
+        assert!(user_path.ends_with(".config/systemd/user/mini.service"));
+    }
+}
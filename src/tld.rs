@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use tracing::info;
+
+use crate::config::ServerConfig;
+
+/// What changed when the TLD was switched.
+#[derive(Debug, Default, PartialEq)]
+pub struct TldChangeReport {
+    /// `(old domain, new domain)` for every site that was re-keyed.
+    pub renamed: Vec<(String, String)>,
+}
+
+/// Switch the TLD used for linked/parked sites: re-key every domain that
+/// carried the old TLD, then re-issue certificates, refresh the OS
+/// resolver, and reload DNS/HTTP routing to match. The whole operation is
+/// atomic from the caller's point of view — on any failure `config` is left
+/// exactly as it was found.
+pub fn change(config: &mut ServerConfig, new_tld: &str) -> Result<TldChangeReport> {
+    let snapshot = config.clone();
+    match try_change(config, new_tld) {
+        Ok(report) => Ok(report),
+        Err(e) => {
+            *config = snapshot;
+            Err(e)
+        }
+    }
+}
+
+fn try_change(config: &mut ServerConfig, new_tld: &str) -> Result<TldChangeReport> {
+    let old_tld = config.tld.clone();
+    let renamed = rekey_sites(config, &old_tld, new_tld)?;
+    config.tld = new_tld.to_string();
+
+    reissue_certificates(config)?;
+    refresh_resolver_entries(config)?;
+    reload_routing(config)?;
+
+    Ok(TldChangeReport { renamed })
+}
+
+/// Re-key every site whose domain carries `old_tld` to use `new_tld`
+/// instead, leaving domains that don't carry the old TLD untouched. Errors
+/// if two domains would collide once renamed, without otherwise mutating
+/// `config`.
+fn rekey_sites(config: &mut ServerConfig, old_tld: &str, new_tld: &str) -> Result<Vec<(String, String)>> {
+    let mut renamed = Vec::new();
+    let mut next_sites = HashMap::new();
+
+    for (domain, mut site) in config.sites.drain() {
+        let new_domain = match domain.strip_suffix(old_tld) {
+            Some(stem) if !old_tld.is_empty() => format!("{stem}{new_tld}"),
+            _ => domain.clone(),
+        };
+
+        if next_sites.contains_key(&new_domain) {
+            anyhow::bail!("renaming {domain} to {new_domain} collides with an existing site");
+        }
+
+        if new_domain != domain {
+            renamed.push((domain.clone(), new_domain.clone()));
+            site.domain = new_domain.clone();
+        }
+        next_sites.insert(new_domain, site);
+    }
+
+    config.sites = next_sites;
+    Ok(renamed)
+}
+
+/// Re-issue a locally-trusted TLS certificate for every secured site under
+/// its new domain. No certificate authority is wired up yet, so this is a
+/// placeholder that only logs what would happen.
+fn reissue_certificates(config: &ServerConfig) -> Result<()> {
+    for site in config.sites.values().filter(|s| s.secure) {
+        info!("would re-issue a TLS certificate for {}", site.domain);
+    }
+    Ok(())
+}
+
+/// Point the OS resolver at mini's embedded DNS server for the new TLD. No
+/// resolver integration exists yet, so this is a placeholder that only logs
+/// what would happen.
+fn refresh_resolver_entries(config: &ServerConfig) -> Result<()> {
+    info!("would refresh OS resolver entries for {}", config.tld);
+    Ok(())
+}
+
+/// Ask the running daemon to reload its DNS/HTTP routing tables. No
+/// inter-process reload signal exists yet, so this is a placeholder that
+/// only logs what would happen.
+fn reload_routing(config: &ServerConfig) -> Result<()> {
+    info!("would reload DNS/HTTP routing for {} sites", config.sites.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SiteConfig;
+
+    fn site(domain: &str, secure: bool) -> SiteConfig {
+        SiteConfig {
+            root_dir: format!("/sites/{domain}"),
+            domain: domain.to_string(),
+            secure,
+            php_version: None,
+            env_vars: Default::default(),
+            driver: None,
+            notes: None,
+            tags: Vec::new(),
+            php_ini: Default::default(),
+            xdebug: false,
+            proxy_target: None,
+        }
+    }
+
+    #[test]
+    fn test_change_rekeys_domains_carrying_the_old_tld() {
+        let mut config = ServerConfig::default();
+        config.add_site("blog.test".to_string(), site("blog.test", true));
+        config.add_site("external.example.com".to_string(), site("external.example.com", false));
+
+        let report = change(&mut config, ".localhost").unwrap();
+
+        assert_eq!(config.tld, ".localhost");
+        assert!(config.sites.contains_key("blog.localhost"));
+        assert_eq!(config.sites["blog.localhost"].domain, "blog.localhost");
+        // A domain that never carried the old TLD is left alone.
+        assert!(config.sites.contains_key("external.example.com"));
+        assert_eq!(report.renamed, vec![("blog.test".to_string(), "blog.localhost".to_string())]);
+    }
+
+    #[test]
+    fn test_change_rolls_back_on_a_rename_collision() {
+        let mut config = ServerConfig::default();
+        config.add_site("blog.test".to_string(), site("blog.test", false));
+        // Already occupies the domain "blog.test" would collide into.
+        config.add_site("blog.localhost".to_string(), site("blog.localhost", false));
+
+        let before = config.clone();
+        let err = change(&mut config, ".localhost").unwrap_err();
+
+        assert!(err.to_string().contains("collides"));
+        assert_eq!(config, before);
+    }
+}
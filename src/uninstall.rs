@@ -0,0 +1,93 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use tracing::info;
+use serde::Serialize;
+
+use crate::config::ServerConfig;
+
+/// What `mini uninstall` actually did, for a human- or JSON-formatted report.
+#[derive(Debug, Default, Serialize)]
+pub struct UninstallReport {
+    pub daemon_stopped: bool,
+    pub service_removed: bool,
+    pub certs_removed: bool,
+    pub config_removed: bool,
+}
+
+/// Reverse every system mutation mini makes today: stop the running daemon,
+/// remove the systemd unit (Linux only, best-effort either install flavor),
+/// delete generated certs, then the config directory itself unless
+/// `keep_config` is set.
+///
+/// Resolver entries, hosts file entries, and trust-store CA installation
+/// aren't implemented anywhere else in mini yet (see
+/// [`crate::tld::refresh_resolver_entries`] and
+/// [`crate::status::StatusReport::ca_trusted`]), so there's nothing real to
+/// reverse there either - this only undoes mutations mini actually makes.
+pub fn run(config: &ServerConfig, config_path: &Path, keep_config: bool) -> Result<UninstallReport> {
+    let mut report = UninstallReport::default();
+
+    report.daemon_stopped = crate::daemon::stop(config).is_ok();
+
+    if cfg!(target_os = "linux") {
+        let system = crate::systemd::uninstall(false).is_ok();
+        let user = crate::systemd::uninstall(true).is_ok();
+        report.service_removed = system || user;
+    } else {
+        info!("service supervision is only implemented via systemd (Linux); nothing to remove here");
+    }
+
+    report.certs_removed = remove_if_exists(Path::new("certs"))?;
+
+    if !keep_config {
+        if let Some(config_dir) = config_path.parent() {
+            report.config_removed = remove_if_exists(config_dir)?;
+        }
+    }
+
+    Ok(report)
+}
+
+fn remove_if_exists(path: &Path) -> Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    if path.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else {
+        fs::remove_file(path)?;
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_run_removes_the_config_directory_by_default() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("config.yaml");
+        fs::write(&config_path, "").unwrap();
+
+        let report = run(&ServerConfig::default(), &config_path, false).unwrap();
+
+        assert!(report.config_removed);
+        assert!(!dir.path().exists());
+    }
+
+    #[test]
+    fn test_run_keeps_the_config_directory_when_asked() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("config.yaml");
+        fs::write(&config_path, "").unwrap();
+
+        let report = run(&ServerConfig::default(), &config_path, true).unwrap();
+
+        assert!(!report.config_removed);
+        assert!(dir.path().exists());
+    }
+}
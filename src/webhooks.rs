@@ -0,0 +1,372 @@
+//! Captures incoming webhook requests (headers + body) to a ring buffer and
+//! can replay any of them against a site later - built for Stripe/GitHub-style
+//! integrations where triggering a real event over and over to test a
+//! handler is painful.
+//!
+//! Nothing in mini's live request path calls [`WebhookStore::record`] yet -
+//! like [`crate::error_feed::ErrorFeed`], it's meant to be fed by
+//! [`crate::MyProxy`] once that actually routes a shared site's traffic
+//! through `SiteManager` rather than always forwarding to a fixed upstream
+//! (see [`crate::MyProxy::upstream_peer`]'s doc comment). [`serve`] and
+//! [`replay`] are built and tested standalone, ready to wire in then.
+
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use hyper::client::HttpConnector;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Client, Method, Request, Response, Server, StatusCode};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Assigns each [`CapturedWebhook`] a process-lifetime-unique id, the same
+/// role [`crate::error_feed::NEXT_ERROR_ID`] plays for error feed entries.
+static NEXT_WEBHOOK_ID: AtomicU64 = AtomicU64::new(1);
+
+/// One incoming webhook request, captured verbatim so it can be replayed
+/// exactly as it first arrived.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedWebhook {
+    pub id: u64,
+    pub received_at_unix: u64,
+    /// The shared site's domain this request arrived for.
+    pub domain: String,
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// A fixed-capacity ring buffer of recently captured webhooks, the same
+/// eviction trade-off as [`crate::mailbox::MailStore`] - this exists to
+/// answer "replay the webhook Stripe just sent", not as a durable log.
+pub struct WebhookStore {
+    capacity: usize,
+    webhooks: Mutex<VecDeque<CapturedWebhook>>,
+}
+
+impl WebhookStore {
+    pub fn new(capacity: usize) -> Self {
+        WebhookStore { capacity, webhooks: Mutex::new(VecDeque::with_capacity(capacity)) }
+    }
+
+    pub fn record(&self, domain: String, method: String, path: String, headers: Vec<(String, String)>, body: Vec<u8>) -> u64 {
+        let webhook = CapturedWebhook {
+            id: NEXT_WEBHOOK_ID.fetch_add(1, Ordering::Relaxed),
+            received_at_unix: now_unix(),
+            domain,
+            method,
+            path,
+            headers,
+            body,
+        };
+        let id = webhook.id;
+        let mut webhooks = self.webhooks.lock().unwrap();
+        if webhooks.len() >= self.capacity {
+            webhooks.pop_front();
+        }
+        webhooks.push_back(webhook);
+        id
+    }
+
+    /// The most recently captured webhooks, newest first, optionally scoped
+    /// to `domain` and capped at `limit` (or everything held/matching, if
+    /// `limit` is `None` or larger).
+    pub fn recent(&self, domain: Option<&str>, limit: Option<usize>) -> Vec<CapturedWebhook> {
+        let webhooks = self.webhooks.lock().unwrap();
+        let matching: Vec<_> = webhooks
+            .iter()
+            .rev()
+            .filter(|webhook| domain.is_none_or(|domain| webhook.domain == domain))
+            .cloned()
+            .collect();
+        let limit = limit.unwrap_or(matching.len()).min(matching.len());
+        matching.into_iter().take(limit).collect()
+    }
+
+    pub fn get(&self, id: u64) -> Option<CapturedWebhook> {
+        self.webhooks.lock().unwrap().iter().find(|w| w.id == id).cloned()
+    }
+}
+
+/// Resend `webhook` to `target_base_url` (e.g. `http://myapp.test`), with
+/// the same method, path, headers, and body it originally arrived with,
+/// returning the replayed response's status code.
+pub async fn replay(client: &Client<HttpConnector>, webhook: &CapturedWebhook, target_base_url: &str) -> Result<u16> {
+    let uri: hyper::Uri = format!("{}{}", target_base_url.trim_end_matches('/'), webhook.path)
+        .parse()
+        .context("invalid replay target URL")?;
+    let method: Method = webhook.method.parse().context("invalid captured method")?;
+
+    let mut builder = Request::builder().method(method).uri(uri);
+    for (name, value) in &webhook.headers {
+        builder = builder.header(name, value);
+    }
+    let request = builder.body(Body::from(webhook.body.clone()))?;
+
+    let response = client.request(request).await.context("failed to replay webhook")?;
+    Ok(response.status().as_u16())
+}
+
+fn escape_html(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn render_index(webhooks: &[CapturedWebhook]) -> String {
+    let mut rows = String::new();
+    for webhook in webhooks {
+        rows.push_str(&format!(
+            "<tr><td><a href=\"/webhooks/{id}\">{id}</a></td><td>{domain}</td><td>{method}</td><td>{path}</td></tr>\n",
+            id = webhook.id,
+            domain = escape_html(&webhook.domain),
+            method = escape_html(&webhook.method),
+            path = escape_html(&webhook.path),
+        ));
+    }
+    format!(
+        "<!DOCTYPE html><html><head><title>mini webhooks</title></head><body>\
+         <h1>mini webhooks</h1>\
+         <table border=\"1\" cellpadding=\"4\"><tr><th>Id</th><th>Domain</th><th>Method</th><th>Path</th></tr>\n{rows}</table>\
+         </body></html>"
+    )
+}
+
+fn render_webhook(webhook: &CapturedWebhook) -> String {
+    let headers: String = webhook
+        .headers
+        .iter()
+        .map(|(name, value)| format!("{}: {}\n", escape_html(name), escape_html(value)))
+        .collect();
+    format!(
+        "<!DOCTYPE html><html><head><title>webhook {id}</title></head><body>\
+         <p><a href=\"/\">&larr; back</a></p>\
+         <h1>{method} {path}</h1>\
+         <p>Domain: {domain}</p>\
+         <pre>{headers}</pre>\
+         <pre>{body}</pre>\
+         <form method=\"post\" action=\"/webhooks/{id}/replay\">\
+         <input name=\"target\" placeholder=\"http://{domain}\">\
+         <button type=\"submit\">Replay</button></form>\
+         </body></html>",
+        id = webhook.id,
+        domain = escape_html(&webhook.domain),
+        method = escape_html(&webhook.method),
+        path = escape_html(&webhook.path),
+        headers = headers,
+        body = escape_html(&String::from_utf8_lossy(&webhook.body)),
+    )
+}
+
+fn html_response(status: StatusCode, body: String) -> Response<Body> {
+    Response::builder().status(status).header("content-type", "text/html; charset=utf-8").body(Body::from(body)).unwrap()
+}
+
+fn json_response<T: Serialize>(status: StatusCode, value: &T) -> Response<Body> {
+    let body = serde_json::to_string(value).unwrap_or_else(|_| "null".to_string());
+    Response::builder().status(status).header("content-type", "application/json").body(Body::from(body)).unwrap()
+}
+
+/// Pull the numeric id out of a `/webhooks/{id}`-shaped path segment.
+fn webhook_id(path: &str, prefix: &str) -> Option<u64> {
+    let rest = path.strip_prefix(prefix)?;
+    rest.strip_suffix('/').unwrap_or(rest).parse().ok()
+}
+
+#[derive(Deserialize)]
+struct ReplayRequest {
+    target: String,
+}
+
+async fn handle_replay(
+    req: Request<Body>,
+    id: u64,
+    store: &WebhookStore,
+    client: &Client<HttpConnector>,
+    json: bool,
+) -> Response<Body> {
+    let Some(webhook) = store.get(id) else {
+        return if json {
+            json_response(StatusCode::NOT_FOUND, &serde_json::json!({ "error": "webhook not found" }))
+        } else {
+            html_response(StatusCode::NOT_FOUND, "webhook not found".to_string())
+        };
+    };
+
+    let target = if json {
+        match hyper::body::to_bytes(req.into_body()).await.ok().and_then(|b| serde_json::from_slice::<ReplayRequest>(&b).ok()) {
+            Some(body) => body.target,
+            None => return json_response(StatusCode::BAD_REQUEST, &serde_json::json!({ "error": "missing target" })),
+        }
+    } else {
+        match hyper::body::to_bytes(req.into_body()).await.ok().map(|b| parse_form_target(&b)) {
+            Some(Some(target)) => target,
+            _ => format!("http://{}", webhook.domain),
+        }
+    };
+
+    match replay(client, &webhook, &target).await {
+        Ok(status) if json => json_response(StatusCode::OK, &serde_json::json!({ "status": status })),
+        Ok(status) => html_response(StatusCode::OK, format!("Replayed to {target}, got status {status}")),
+        Err(e) if json => json_response(StatusCode::BAD_GATEWAY, &serde_json::json!({ "error": e.to_string() })),
+        Err(e) => html_response(StatusCode::BAD_GATEWAY, format!("Replay failed: {e}")),
+    }
+}
+
+/// Pull `target=...` out of a `application/x-www-form-urlencoded` body, just
+/// enough decoding for the plain URL this form ever submits (no encoded
+/// characters expected in a target URL).
+fn parse_form_target(body: &[u8]) -> Option<String> {
+    let body = std::str::from_utf8(body).ok()?;
+    body.split('&').find_map(|pair| pair.strip_prefix("target=")).map(|v| v.to_string())
+}
+
+async fn handle(req: Request<Body>, store: Arc<WebhookStore>, client: Client<HttpConnector>) -> Result<Response<Body>, Infallible> {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+
+    let response = match (&method, path.as_str()) {
+        (&Method::GET, "/") => html_response(StatusCode::OK, render_index(&store.recent(None, None))),
+        (&Method::GET, "/api/webhooks") => json_response(StatusCode::OK, &store.recent(None, None)),
+        (&Method::GET, path) if path.starts_with("/webhooks/") => match webhook_id(path, "/webhooks/").and_then(|id| store.get(id)) {
+            Some(webhook) => html_response(StatusCode::OK, render_webhook(&webhook)),
+            None => html_response(StatusCode::NOT_FOUND, "webhook not found".to_string()),
+        },
+        (&Method::GET, path) if path.starts_with("/api/webhooks/") => {
+            match webhook_id(path, "/api/webhooks/").and_then(|id| store.get(id)) {
+                Some(webhook) => json_response(StatusCode::OK, &webhook),
+                None => json_response(StatusCode::NOT_FOUND, &serde_json::json!({ "error": "webhook not found" })),
+            }
+        }
+        (&Method::POST, path) if path.starts_with("/webhooks/") && path.ends_with("/replay") => {
+            match webhook_id(path.trim_end_matches("/replay"), "/webhooks/") {
+                Some(id) => handle_replay(req, id, &store, &client, false).await,
+                None => html_response(StatusCode::NOT_FOUND, "webhook not found".to_string()),
+            }
+        }
+        (&Method::POST, path) if path.starts_with("/api/webhooks/") && path.ends_with("/replay") => {
+            match webhook_id(path.trim_end_matches("/replay"), "/api/webhooks/") {
+                Some(id) => handle_replay(req, id, &store, &client, true).await,
+                None => json_response(StatusCode::NOT_FOUND, &serde_json::json!({ "error": "webhook not found" })),
+            }
+        }
+        _ => html_response(StatusCode::NOT_FOUND, "not found".to_string()),
+    };
+    Ok(response)
+}
+
+/// Serve the webhook capture/replay web UI and API on `addr` until the
+/// process exits.
+pub async fn serve(addr: SocketAddr, store: Arc<WebhookStore>) -> Result<()> {
+    let client = Client::new();
+    let make_svc = make_service_fn(move |_conn| {
+        let store = store.clone();
+        let client = client.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(req, store.clone(), client.clone()))) }
+    });
+
+    info!("Webhook capture/replay listening on {addr}");
+    Server::bind(&addr).serve(make_svc).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_assigns_increasing_ids() {
+        let store = WebhookStore::new(10);
+        let first = store.record("a.test".to_string(), "POST".to_string(), "/webhook".to_string(), Vec::new(), Vec::new());
+        let second = store.record("a.test".to_string(), "POST".to_string(), "/webhook".to_string(), Vec::new(), Vec::new());
+        assert!(second > first);
+    }
+
+    #[test]
+    fn test_recent_returns_newest_first_and_honors_limit() {
+        let store = WebhookStore::new(10);
+        store.record("a.test".to_string(), "POST".to_string(), "/one".to_string(), Vec::new(), Vec::new());
+        store.record("a.test".to_string(), "POST".to_string(), "/two".to_string(), Vec::new(), Vec::new());
+        store.record("a.test".to_string(), "POST".to_string(), "/three".to_string(), Vec::new(), Vec::new());
+
+        let recent = store.recent(None, Some(2));
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].path, "/three");
+        assert_eq!(recent[1].path, "/two");
+    }
+
+    #[test]
+    fn test_recent_filters_by_domain() {
+        let store = WebhookStore::new(10);
+        store.record("a.test".to_string(), "POST".to_string(), "/one".to_string(), Vec::new(), Vec::new());
+        store.record("b.test".to_string(), "POST".to_string(), "/two".to_string(), Vec::new(), Vec::new());
+
+        let recent = store.recent(Some("b.test"), None);
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].path, "/two");
+    }
+
+    #[test]
+    fn test_record_evicts_oldest_past_capacity() {
+        let store = WebhookStore::new(2);
+        let first = store.record("a.test".to_string(), "POST".to_string(), "/one".to_string(), Vec::new(), Vec::new());
+        store.record("a.test".to_string(), "POST".to_string(), "/two".to_string(), Vec::new(), Vec::new());
+        store.record("a.test".to_string(), "POST".to_string(), "/three".to_string(), Vec::new(), Vec::new());
+
+        assert!(store.get(first).is_none());
+        assert_eq!(store.recent(None, None).len(), 2);
+    }
+
+    #[test]
+    fn test_webhook_id_parses_trailing_segment() {
+        assert_eq!(webhook_id("/webhooks/42", "/webhooks/"), Some(42));
+        assert_eq!(webhook_id("/webhooks/42/", "/webhooks/"), Some(42));
+        assert_eq!(webhook_id("/webhooks/nope", "/webhooks/"), None);
+    }
+
+    #[test]
+    fn test_parse_form_target_extracts_the_target_field() {
+        assert_eq!(parse_form_target(b"target=http%3A%2F%2Fa.test"), Some("http%3A%2F%2Fa.test".to_string()));
+        assert_eq!(parse_form_target(b"other=1"), None);
+    }
+
+    #[tokio::test]
+    async fn test_replay_sends_the_captured_request_and_returns_its_status() {
+        use std::convert::Infallible as StdInfallible;
+
+        let make_svc = make_service_fn(|_conn| async {
+            Ok::<_, StdInfallible>(service_fn(|_req: Request<Body>| async {
+                Ok::<_, StdInfallible>(Response::builder().status(204).body(Body::empty()).unwrap())
+            }))
+        });
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = Server::from_tcp(listener).unwrap().serve(make_svc);
+        tokio::spawn(server);
+
+        let webhook = CapturedWebhook {
+            id: 1,
+            received_at_unix: 0,
+            domain: "a.test".to_string(),
+            method: "POST".to_string(),
+            path: "/webhook".to_string(),
+            headers: Vec::new(),
+            body: b"payload".to_vec(),
+        };
+
+        let client = Client::new();
+        let status = replay(&client, &webhook, &format!("http://{addr}")).await.unwrap();
+        assert_eq!(status, 204);
+    }
+}